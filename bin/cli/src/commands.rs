@@ -1,4 +1,5 @@
 pub(crate) mod init;
 pub(crate) mod make_migration;
 pub(crate) mod make_seeder;
+pub(crate) mod migration_diff;
 pub(crate) mod new;
@@ -1,11 +1,34 @@
 use std::process::Command;
 
 use crate::{
+    commands::migration_diff,
     content::{dump_a_stub, make_a_directory, read_entry_file, stubs, update_entry_file},
     metadata::read_package_metadata,
 };
 
-pub fn make(package: Option<&String>, name: &str) {
+pub fn make(package: Option<&String>, name: &str, diff: bool, guess_renames: bool) {
+    let built = if diff {
+        match migration_diff::fetch_diffs(package, guess_renames) {
+            Ok(diffs) if diffs.is_empty() => {
+                println!("Nothing to migrate, the database is in sync with the entities");
+                return;
+            }
+            Ok(diffs) => Some(
+                stubs()
+                    .get("diff_migration")
+                    .unwrap()
+                    .replace("up_body", &migration_diff::up_code(&diffs))
+                    .replace("down_body", &migration_diff::down_code(&diffs)),
+            ),
+            Err(e) => {
+                eprintln!("{e}");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
     let path_buf = if let Some(package) = package {
         read_package_metadata(package)
     } else {
@@ -23,9 +46,9 @@ pub fn make(package: Option<&String>, name: &str) {
         dirtybase_helper::cruet::case::to_pascal_case(name)
     );
 
-    let built = stubs()
-        .get("new_migration")
-        .unwrap()
+    let built = built
+        .as_deref()
+        .unwrap_or_else(|| stubs().get("new_migration").unwrap())
         .replace("struct_name", &struct_name);
 
     let migration_dir = path_buf.join("dirtybase_entry").join("migration");
@@ -0,0 +1,211 @@
+use std::process::Command;
+
+use dirtybase_contract::db_contract::base::{
+    column::{ColumnBlueprint, ColumnDefault, ColumnType},
+    schema_diff::{IndexChange, TableDiff},
+};
+
+/// Runs the application's `db:diff` command and returns the tables that differ
+pub(crate) fn fetch_diffs(
+    package: Option<&String>,
+    guess_renames: bool,
+) -> Result<Vec<TableDiff>, String> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("run").arg("-q");
+    if let Some(package) = package {
+        cmd.arg("-p").arg(package);
+    }
+    cmd.arg("--").arg("db:diff").arg("--json");
+    if guess_renames {
+        cmd.arg("--guess-renames");
+    }
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("could not run the application: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // the application may log to stdout, the diff is printed on a single line
+    match stdout.lines().rev().find(|line| line.starts_with('[')) {
+        Some(line) => serde_json::from_str(line).map_err(|e| format!("invalid diff: {e}")),
+        None => Err(format!(
+            "could not diff the schema: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )),
+    }
+}
+
+/// The statements of the `up` method
+pub(crate) fn up_code(diffs: &[TableDiff]) -> String {
+    let mut code = Vec::new();
+    for diff in diffs {
+        let table = &diff.table;
+        if diff.is_new {
+            let mut body = diff.added.iter().map(column_code).collect::<Vec<_>>();
+            body.extend(diff.added_indexes.iter().map(add_index_code));
+            code.push(schema_call("create_table_schema", table, &body));
+            continue;
+        }
+
+        for (old, new) in &diff.renamed {
+            code.push(format!(
+                "// guessed rename, check before running\nmanager.rename_column({table:?}, {old:?}, {new:?}).await?;"
+            ));
+        }
+
+        let mut body = diff.added.iter().map(column_code).collect::<Vec<_>>();
+        body.extend(diff.dropped_indexes.iter().map(drop_index_code));
+        body.extend(diff.added_indexes.iter().map(add_index_code));
+        if !body.is_empty() {
+            code.push(schema_call("update_table_schema", table, &body));
+        }
+
+        for column in &diff.dropped {
+            code.push(format!(
+                "manager.drop_column({table:?}, {:?}).await?;",
+                column.name
+            ));
+        }
+
+        for change in &diff.changed {
+            code.push(format!(
+                "// `{table}.{}` changed from `{}` to `{}`, alter it manually",
+                change.name, change.from, change.to
+            ));
+        }
+    }
+    code.join("\n")
+}
+
+/// The statements of the `down` method, undoing `up_code` in reverse
+pub(crate) fn down_code(diffs: &[TableDiff]) -> String {
+    let mut code = Vec::new();
+    for diff in diffs.iter().rev() {
+        let table = &diff.table;
+        if diff.is_new {
+            code.push(format!("manager.drop_table({table:?}).await?;"));
+            continue;
+        }
+
+        let mut body = diff
+            .dropped
+            .iter()
+            .map(live_column_code)
+            .collect::<Vec<_>>();
+        body.extend(diff.added_indexes.iter().map(drop_index_code));
+        body.extend(diff.dropped_indexes.iter().map(add_index_code));
+        if !body.is_empty() {
+            code.push(schema_call("update_table_schema", table, &body));
+        }
+
+        for column in &diff.added {
+            code.push(format!(
+                "manager.drop_column({table:?}, {:?}).await?;",
+                column.name
+            ));
+        }
+
+        for (old, new) in &diff.renamed {
+            code.push(format!(
+                "// guessed rename, check before running\nmanager.rename_column({table:?}, {new:?}, {old:?}).await?;"
+            ));
+        }
+    }
+    code.join("\n")
+}
+
+fn schema_call(method: &str, table: &str, body: &[String]) -> String {
+    format!(
+        "manager.{method}({table:?}, |table| {{\n{}\n}}).await?;",
+        body.join("\n")
+    )
+}
+
+fn add_index_code(index: &IndexChange) -> String {
+    let method = if index.is_unique {
+        "unique_index"
+    } else {
+        "index"
+    };
+    format!("table.{method}(&{:?});", index.columns)
+}
+
+fn drop_index_code(index: &IndexChange) -> String {
+    match &index.name {
+        Some(name) => format!("table.drop_index_named({name:?});"),
+        None => format!("table.drop_index(&{:?});", index.columns),
+    }
+}
+
+/// The call that recreates a column read from the database. Defaults that are SQL
+/// expressions, like `nextval(...)` or `'x'::character varying`, are left out
+fn live_column_code(column: &ColumnBlueprint) -> String {
+    if let Some(ColumnDefault::Custom(value)) = &column.default
+        && is_sql_expression(value)
+    {
+        let mut column = column.clone();
+        column.default = None;
+        return column_code(&column);
+    }
+    column_code(column)
+}
+
+fn is_sql_expression(value: &str) -> bool {
+    value.contains('(') || value.contains("::") || value.starts_with('\'')
+}
+
+/// The `TableBlueprint` call that creates the column
+fn column_code(column: &ColumnBlueprint) -> String {
+    let name = &column.name;
+    let mut code = match &column.column_type {
+        ColumnType::AutoIncrementId => format!("table.id(Some({name:?}))"),
+        ColumnType::Boolean => format!("table.boolean({name:?})"),
+        ColumnType::Char(length) => format!("table.char({name:?}, {length})"),
+        ColumnType::Datetime => format!("table.datetime({name:?})"),
+        ColumnType::Date => format!("table.date({name:?})"),
+        ColumnType::Timestamp => format!("table.timestamp({name:?})"),
+        ColumnType::Float => format!("table.float({name:?})"),
+        ColumnType::Integer => format!("table.integer({name:?})"),
+        ColumnType::Json => format!("table.json({name:?})"),
+        ColumnType::Binary => format!("table.binary({name:?})"),
+        ColumnType::Enum(options) => format!("table.enum_({name:?}, &{options:?})"),
+        ColumnType::Number => format!("table.number({name:?})"),
+        ColumnType::String(255) => format!("table.string({name:?})"),
+        ColumnType::String(length) => format!("table.sized_string({name:?}, {length})"),
+        ColumnType::Text => format!("table.text({name:?})"),
+        ColumnType::Uuid => format!("table.uuid({name:?})"),
+    };
+
+    if column.column_type != ColumnType::AutoIncrementId {
+        if column.is_primary {
+            code.push_str(".set_as_primary()");
+        }
+        if column.is_nullable == Some(true) {
+            code.push_str(".set_is_nullable(true)");
+        }
+    }
+    if column.is_unique {
+        code.push_str(".set_is_unique(true)");
+    }
+    match &column.default {
+        Some(ColumnDefault::Custom(value)) => code.push_str(&format!(".set_default({value:?})")),
+        Some(ColumnDefault::Boolean(true)) => code.push_str(".default_is_true()"),
+        Some(ColumnDefault::Boolean(false)) => code.push_str(".default_is_false()"),
+        Some(ColumnDefault::EmptyString) => code.push_str(".default_is_empty_string()"),
+        Some(ColumnDefault::Zero) => code.push_str(".default_is_zero()"),
+        Some(ColumnDefault::EmptyObject) => code.push_str(".default_is_empty_object()"),
+        Some(ColumnDefault::EmptyArray) => code.push_str(".default_is_empty_array()"),
+        None => (),
+    }
+    if let Some(fk) = &column.relationship {
+        code.push_str(&format!(
+            ".references({:?}, {:?}, {})",
+            fk.table(),
+            fk.column(),
+            fk.cascade_delete()
+        ));
+    }
+
+    code.push(';');
+    code
+}
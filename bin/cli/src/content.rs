@@ -66,6 +66,11 @@ pub(crate) fn stubs<'a>() -> HashMap<&'a str, &'a str> {
         include_str!("./stubs/new_migration.stub.txt"),
     );
 
+    file_content.insert(
+        "diff_migration",
+        include_str!("./stubs/diff_migration.stub.txt"),
+    );
+
     file_content.insert(
         "../.env.defaults",
         include_str!("./stubs/.env.defaults.stub.txt"),
@@ -15,8 +15,12 @@ fn main() {
             commands::init::init(args.package.as_ref());
         }
         Commands::Make { what } => match what {
-            MakeSubcommand::Migration { name } => {
-                commands::make_migration::make(args.package.as_ref(), name);
+            MakeSubcommand::Migration {
+                name,
+                diff,
+                guess_renames,
+            } => {
+                commands::make_migration::make(args.package.as_ref(), name, *diff, *guess_renames);
             }
             MakeSubcommand::Seeder { name } => {
                 commands::make_seeder::make(args.package.as_ref(), name);
@@ -54,6 +58,12 @@ enum MakeSubcommand {
     Migration {
         /// Migration name
         name: String,
+        /// Generate the migration from the differences between the entities and the database
+        #[arg(long)]
+        diff: bool,
+        /// Treat a dropped and an added column of the same kind as a rename
+        #[arg(long, requires = "diff")]
+        guess_renames: bool,
     },
     /// Database seeder
     Seeder {
@@ -21,7 +21,7 @@ pub use app_context::*;
 pub use context_manager::*;
 pub use context_metadata::*;
 
-use crate::db_contract::types::ArcUuid7;
+use crate::db_contract::{base::manager::Manager, types::ArcUuid7};
 
 #[derive(Clone)]
 pub struct Context {
@@ -146,6 +146,11 @@ impl Context {
         self.get().await.ok()
     }
 
+    /// The database manager of a named connection set in database.toml
+    pub async fn db(&self, name: &str) -> Result<Manager, anyhow::Error> {
+        self.get::<Manager>().await?.connection(name).await
+    }
+
     pub async fn set<T: Clone + Send + Sync + 'static>(&self, value: T) -> &Self {
         self.sc.set_type(value).await;
         self
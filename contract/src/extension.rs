@@ -21,6 +21,7 @@ pub(crate) static EXTENSION_COLLECTION: OnceLock<RwLock<Vec<Box<dyn ExtensionSet
 pub(crate) static EXTENSIONS_READY: OnceLock<bool> = OnceLock::new();
 
 pub type ExtensionMigrations = Vec<Box<dyn super::db_contract::migration::Migration>>;
+pub type ExtensionEntities = Vec<super::db_contract::base::table::TableBlueprint>;
 
 #[async_trait::async_trait]
 pub trait ExtensionSetup: Send + Sync {
@@ -99,6 +100,12 @@ pub trait ExtensionSetup: Send + Sync {
         None
     }
 
+    /// The blueprints of the entities whose tables this extension owns.
+    /// Used to diff the entities against the database
+    fn entities(&self, context: &Context) -> Option<ExtensionEntities> {
+        None
+    }
+
     fn id(&self) -> &str {
         std::any::type_name::<Self>()
     }
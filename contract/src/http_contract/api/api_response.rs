@@ -5,6 +5,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use dirtybase_common::db::base::page_result::PageResult;
 use serde::Serialize;
 
 type MoreErrorData = serde_json::map::Map<String, serde_json::Value>;
@@ -107,6 +108,12 @@ impl<D: serde::Serialize + Debug, E: Debug> From<Result<D, E>> for ApiResponse<D
     }
 }
 
+impl<D: serde::Serialize> From<PageResult<D>> for ApiResponse<PageResult<D>> {
+    fn from(page: PageResult<D>) -> Self {
+        Self::success(page)
+    }
+}
+
 impl<D: serde::Serialize> From<Option<D>> for ApiResponse<D> {
     fn from(data: Option<D>) -> Self {
         Self::new(data, None)
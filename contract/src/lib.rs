@@ -19,6 +19,7 @@ pub use async_trait::async_trait;
 pub use axum;
 pub use busybody;
 pub use dirtybase_helper;
+pub use extension::ExtensionEntities;
 pub use extension::ExtensionManager;
 pub use extension::ExtensionMigrations;
 pub use extension::ExtensionSetup;
@@ -43,11 +44,26 @@ macro_rules! register_migration {
     };
 }
 
+#[macro_export]
+macro_rules! register_entity {
+    () => {
+        None
+    };
+    ($($t:ty),+ $(,)?) => {
+        Some(vec![
+            $(
+                <$t as ::dirtybase_contract::db_contract::TableModel>::blueprint(),
+            )*
+        ])
+    };
+}
+
 pub mod prelude {
     pub use super::app_contract::*;
     pub use super::auth_contract::prelude;
     pub use super::cli_contract::prelude::*;
     pub use super::config_contract::*;
+    pub use super::extension::ExtensionEntities;
     pub use super::extension::ExtensionManager;
     pub use super::extension::ExtensionMigrations;
     pub use super::extension::ExtensionSetup;
@@ -1,5 +1,9 @@
 mod connector;
-mod manager;
+mod failed_job;
+mod job;
+mod job_payload;
 
-pub use connector::Connector;
-pub use manager::Manager;
+pub use connector::*;
+pub use failed_job::*;
+pub use job::*;
+pub use job_payload::*;
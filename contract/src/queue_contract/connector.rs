@@ -1,5 +1,84 @@
-pub trait Connector {
-    fn fetch(&self) -> i32; // for now..
-    fn put(&self, job: i32);
-    fn delete(&self, job: i32);
+use std::sync::Arc;
+
+use crate::queue_contract::{FailedJob, JobPayload};
+
+#[async_trait::async_trait]
+pub trait Connector: Send + Sync {
+    /// Adds a job to its queue
+    async fn push(&self, job: JobPayload) -> Result<(), anyhow::Error>;
+
+    /// Takes the next available job of the queue and reserves it
+    /// for the specified number of seconds
+    async fn pop(&self, queue: &str, reserve_for: u64)
+    -> Result<Option<JobPayload>, anyhow::Error>;
+
+    /// Puts a reserved job back on the queue after the specified delay (seconds)
+    async fn release(&self, job: JobPayload, delay: u64) -> Result<(), anyhow::Error>;
+
+    /// Removes a reserved job from the queue
+    async fn delete(&self, job: JobPayload) -> Result<(), anyhow::Error>;
+
+    /// Removes a job from the queue and record it as failed
+    async fn fail(&self, job: FailedJob) -> Result<(), anyhow::Error>;
+
+    /// Returns the failed jobs record
+    async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error>;
+
+    /// Removes a job from the failed jobs record
+    async fn forget(&self, id: &str) -> Result<Option<FailedJob>, anyhow::Error>;
+
+    /// Number of jobs waiting in the queue, delayed jobs included.
+    /// Jobs reserved by a worker are not counted until their reservation ends
+    async fn size(&self, queue: &str) -> Result<usize, anyhow::Error>;
+}
+
+#[derive(Clone)]
+pub struct ConnectorProvider(Arc<Box<dyn Connector>>);
+
+impl ConnectorProvider {
+    pub fn new<T>(inner: T) -> Self
+    where
+        T: Connector + 'static,
+    {
+        Self(Arc::new(Box::new(inner)))
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for ConnectorProvider {
+    async fn push(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        self.0.push(job).await
+    }
+
+    async fn pop(
+        &self,
+        queue: &str,
+        reserve_for: u64,
+    ) -> Result<Option<JobPayload>, anyhow::Error> {
+        self.0.pop(queue, reserve_for).await
+    }
+
+    async fn release(&self, job: JobPayload, delay: u64) -> Result<(), anyhow::Error> {
+        self.0.release(job, delay).await
+    }
+
+    async fn delete(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        self.0.delete(job).await
+    }
+
+    async fn fail(&self, job: FailedJob) -> Result<(), anyhow::Error> {
+        self.0.fail(job).await
+    }
+
+    async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error> {
+        self.0.failed().await
+    }
+
+    async fn forget(&self, id: &str) -> Result<Option<FailedJob>, anyhow::Error> {
+        self.0.forget(id).await
+    }
+
+    async fn size(&self, queue: &str) -> Result<usize, anyhow::Error> {
+        self.0.size(queue).await
+    }
 }
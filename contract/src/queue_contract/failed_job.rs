@@ -0,0 +1,41 @@
+use chrono::Utc;
+
+use crate::queue_contract::JobPayload;
+
+/// A job that ran out of attempts
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FailedJob {
+    job: JobPayload,
+    reason: String,
+    failed_at: i64,
+}
+
+impl FailedJob {
+    pub fn new(job: JobPayload, reason: &str) -> Self {
+        Self {
+            job,
+            reason: reason.to_string(),
+            failed_at: Utc::now().timestamp(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.job.id()
+    }
+
+    pub fn job(&self) -> &JobPayload {
+        &self.job
+    }
+
+    pub fn into_job(self) -> JobPayload {
+        self.job
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn failed_at(&self) -> i64 {
+        self.failed_at
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::app_contract::Context;
+
+/// A unit of work that can be pushed onto a queue and handled later
+///
+/// The job is serialized when dispatched and deserialized by the worker
+/// that picks it up. Its name must therefore be stable between the process
+/// that dispatches the job and the one that handles it.
+#[async_trait::async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Unique name used to find the handler of this job, ex: `"mail::send_welcome"`
+    ///
+    /// It is stored with the dispatched job, renaming the type or moving
+    /// it to another module must not change it
+    fn name() -> &'static str;
+
+    /// The queue this job will be pushed onto
+    fn queue(&self) -> &str {
+        "default"
+    }
+
+    /// Maximum number of times this job will be tried
+    ///
+    /// When `None`, the queue's configured value is used
+    fn max_attempts(&self) -> Option<u32> {
+        None
+    }
+
+    /// Initial number of seconds to wait before retrying a failed job
+    ///
+    /// The delay doubles on every new attempt. When `None`, the queue's
+    /// configured value is used
+    fn backoff(&self) -> Option<u64> {
+        None
+    }
+
+    /// Handles the job
+    ///
+    /// Returning an error causes the job to be retried until it runs out of attempts
+    async fn handle(&self, context: Context) -> Result<(), anyhow::Error>;
+}
@@ -0,0 +1,186 @@
+use chrono::Utc;
+
+use crate::{db_contract::types::ArcUuid7, queue_contract::Job};
+
+/// The serialized form of a job as it is stored by a connector
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct JobPayload {
+    id: String,
+    name: String,
+    queue: String,
+    data: serde_json::Value,
+    attempts: u32,
+    max_attempts: u32,
+    backoff: u64,
+    available_at: i64,
+    reserved_until: Option<i64>,
+    #[serde(default)]
+    reserved_by: Option<String>,
+    created_at: i64,
+}
+
+impl JobPayload {
+    pub fn new<J: Job>(job: &J, max_attempts: u32, backoff: u64) -> Result<Self, anyhow::Error> {
+        let now = Utc::now().timestamp();
+        Ok(Self {
+            id: ArcUuid7::default().to_string(),
+            name: J::name().to_string(),
+            queue: job.queue().to_string(),
+            data: serde_json::to_value(job)?,
+            attempts: 0,
+            max_attempts: job.max_attempts().unwrap_or(max_attempts).max(1),
+            backoff: job.backoff().unwrap_or(backoff),
+            available_at: now,
+            reserved_until: None,
+            reserved_by: None,
+            created_at: now,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn queue(&self) -> &str {
+        &self.queue
+    }
+
+    pub fn data(&self) -> &serde_json::Value {
+        &self.data
+    }
+
+    /// Number of times this job has been reserved by a worker
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn backoff(&self) -> u64 {
+        self.backoff
+    }
+
+    /// Timestamp from which the job can be picked by a worker
+    pub fn available_at(&self) -> i64 {
+        self.available_at
+    }
+
+    pub fn reserved_until(&self) -> Option<i64> {
+        self.reserved_until
+    }
+
+    /// Token of the current reservation
+    pub fn reserved_by(&self) -> Option<&str> {
+        self.reserved_by.as_deref()
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available_at <= Utc::now().timestamp()
+    }
+
+    pub fn is_reserved(&self) -> bool {
+        self.reserved_until
+            .is_some_and(|ts| ts > Utc::now().timestamp())
+    }
+
+    pub fn has_attempts_left(&self) -> bool {
+        self.attempts < self.max_attempts
+    }
+
+    /// Delays the job by the specified number of seconds
+    pub fn delay(&mut self, seconds: u64) -> &mut Self {
+        self.available_at = Utc::now().timestamp() + seconds as i64;
+        self
+    }
+
+    /// Marks the job as taken by a worker for the specified number of seconds
+    pub fn reserve(&mut self, seconds: u64) -> &mut Self {
+        self.attempts += 1;
+        self.reserved_until = Some(Utc::now().timestamp() + seconds as i64);
+        self.reserved_by = Some(ArcUuid7::default().to_string());
+        self
+    }
+
+    /// Puts the job back on the queue after the specified delay
+    pub fn release(&mut self, delay: u64) -> &mut Self {
+        self.reserved_until = None;
+        self.reserved_by = None;
+        self.delay(delay)
+    }
+
+    /// Clears the attempts made so far so that the job can be tried again
+    pub fn reset_attempts(&mut self) -> &mut Self {
+        self.attempts = 0;
+        self
+    }
+
+    /// Number of seconds to wait before the next attempt
+    ///
+    /// The delay doubles on each attempt
+    pub fn next_backoff(&self) -> u64 {
+        let exp = self.attempts.saturating_sub(1).min(16);
+        self.backoff.saturating_mul(2_u64.pow(exp))
+    }
+
+    /// Rebuild the job instance
+    pub fn to_job<J: Job>(&self) -> Result<J, anyhow::Error> {
+        Ok(serde_json::from_value(self.data.clone())?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app_contract::Context;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct SendEmail {
+        to: String,
+    }
+
+    #[async_trait::async_trait]
+    impl Job for SendEmail {
+        fn name() -> &'static str {
+            "test::send_email"
+        }
+
+        async fn handle(&self, _context: Context) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_payload_creation() {
+        let payload = JobPayload::new(&SendEmail { to: "a@b.c".into() }, 3, 5).unwrap();
+
+        assert_eq!(payload.name(), "test::send_email");
+        assert_eq!(payload.queue(), "default");
+        assert_eq!(payload.attempts(), 0);
+        assert!(payload.is_available());
+        assert!(!payload.is_reserved());
+        assert_eq!(payload.to_job::<SendEmail>().unwrap().to, "a@b.c");
+    }
+
+    #[test]
+    fn test_payload_backoff() {
+        let mut payload = JobPayload::new(&SendEmail { to: "a@b.c".into() }, 3, 5).unwrap();
+
+        payload.reserve(60);
+        assert_eq!(payload.next_backoff(), 5);
+        payload.reserve(60);
+        assert_eq!(payload.next_backoff(), 10);
+        payload.reserve(60);
+        assert_eq!(payload.next_backoff(), 20);
+        assert!(!payload.has_attempts_left());
+    }
+}
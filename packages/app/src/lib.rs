@@ -16,6 +16,7 @@ pub use dirtybase_db as db;
 pub use dirtybase_db_macro as db_macro;
 pub use dirtybase_helper as helper;
 pub use dirtybase_mail as mail;
+pub use dirtybase_queue as queue;
 pub use orsomafo;
 
 use dirtybase_contract::cli_contract::setup_cli_command_manager;
@@ -55,6 +56,7 @@ pub async fn setup_using(config: &core::Config) -> anyhow::Result<AppService> {
     app.register(dirtybase_entry::Extension).await;
     app.register(dirtybase_cache::Extension).await;
     app.register(dirtybase_cron::Extension).await;
+    app.register(dirtybase_queue::Extension).await;
     Ok(app)
 }
 
@@ -100,7 +100,8 @@ impl AuthUserStorage for AuthUserDatabaseStorage {
             .delete(AUTH_USER_TABLE, |q| {
                 q.is_eq("id", id);
             })
-            .await
+            .await?;
+        Ok(())
     }
 }
 
@@ -1,16 +1,29 @@
 pub mod aggregate;
 pub mod column;
 pub mod connection;
+pub mod cte_builder;
 pub mod cursor_builder;
+pub mod full_text;
+pub mod group_by_builder;
 pub mod helper;
 pub mod index;
+pub mod introspection;
 pub mod join_builder;
+pub mod json_path;
+pub mod lazy_stream;
 pub mod manager;
 pub mod order_by_builder;
+pub mod page_result;
 pub mod query;
 pub mod query_conditions;
 pub mod query_join_types;
+pub mod query_log;
 pub mod query_operators;
+pub mod replica;
 pub mod schema;
+pub mod schema_diff;
+pub mod stale_entity;
 pub mod table;
+pub mod union_builder;
 pub mod where_join_operators;
+pub mod window_function;
@@ -72,6 +72,50 @@ pub enum ColumnType {
     Uuid,
 }
 
+impl ColumnType {
+    /// Maps a type reported by the database back to a column type.
+    /// Unknown types are treated as text
+    pub fn from_sql_type(data_type: &str) -> Self {
+        let data_type = data_type.trim().to_ascii_lowercase();
+        let (name, args) = match data_type.split_once('(') {
+            Some((name, args)) => (
+                name.trim(),
+                args.split_once(')').map(|(a, _)| a).unwrap_or(args),
+            ),
+            None => (data_type.as_str(), ""),
+        };
+        let length = args.trim().parse::<usize>().ok();
+
+        match name {
+            "tinyint" if length == Some(1) => Self::Boolean,
+            "bool" | "boolean" => Self::Boolean,
+            "int" | "integer" | "tinyint" | "smallint" | "mediumint" | "bigint" | "int2"
+            | "int4" | "int8" => Self::Integer,
+            "char" | "character" | "bpchar" => Self::Char(length.unwrap_or(1)),
+            "varchar" | "character varying" | "nvarchar" => Self::String(length.unwrap_or(255)),
+            "text" | "tinytext" | "mediumtext" | "longtext" | "clob" => Self::Text,
+            "datetime" => Self::Datetime,
+            "date" => Self::Date,
+            "real" | "float" | "float4" | "float8" | "double" | "double precision" | "decimal"
+            | "numeric" => Self::Float,
+            "json" | "jsonb" => Self::Json,
+            "uuid" => Self::Uuid,
+            "binary" if length == Some(16) => Self::Uuid,
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "binary" | "varbinary" => {
+                Self::Binary
+            }
+            "enum" => Self::Enum(
+                args.split(',')
+                    .map(|option| option.trim().trim_matches('\'').to_string())
+                    .filter(|option| !option.is_empty())
+                    .collect(),
+            ),
+            name if name.starts_with("timestamp") => Self::Timestamp,
+            _ => Self::Text,
+        }
+    }
+}
+
 impl ColumnBlueprint {
     pub fn new(name: &str, column_type: ColumnType) -> Self {
         Self {
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
 
 use super::schema::{DatabaseKind, SchemaManagerTrait};
 use async_trait::async_trait;
@@ -9,4 +9,29 @@ pub trait ConnectionPoolTrait: Debug + Send + Sync {
     fn schema_manger(&self) -> Box<dyn SchemaManagerTrait + Send + Sync>;
     async fn close(&self);
     fn id(&self) -> DatabaseKind;
+
+    /// Checks that the database can be reached
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// How far behind its primary the database is. `None` when it is not a replica
+    async fn replica_lag(&self) -> Result<Option<Duration>, anyhow::Error> {
+        Ok(None)
+    }
+
+    /// Number of connections currently checked out of the pool
+    fn in_use(&self) -> u32 {
+        0
+    }
+
+    /// False when the pool should not be used, the writer is used instead
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// A schema manager when the pool can be used. `None` tells the caller to use the writer
+    fn available_schema_manger(&self) -> Option<Box<dyn SchemaManagerTrait + Send + Sync>> {
+        self.is_available().then(|| self.schema_manger())
+    }
 }
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use super::query::QueryBuilder;
+
+/// A named subquery (common table expression) that the main query can
+/// select from or join on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CteBuilder {
+    name: String,
+    recursive: bool,
+    query: Box<QueryBuilder>,
+}
+
+impl CteBuilder {
+    pub fn new(name: &str, query: QueryBuilder) -> Self {
+        Self {
+            name: name.to_string(),
+            recursive: false,
+            query: Box::new(query),
+        }
+    }
+
+    /// A recursive CTE is made of an anchor query, combined with
+    /// a query that references the CTE itself
+    pub fn new_recursive(name: &str, mut anchor: QueryBuilder, recursive: QueryBuilder) -> Self {
+        anchor.union_all(recursive);
+        Self {
+            name: name.to_string(),
+            recursive: true,
+            query: Box::new(anchor),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_recursive(&self) -> bool {
+        self.recursive
+    }
+
+    pub fn query(&self) -> &QueryBuilder {
+        &self.query
+    }
+}
@@ -1,19 +1,25 @@
-use std::fmt::{Debug, Display, write};
+use std::fmt::Debug;
 
 use serde::{Deserialize, Serialize};
 
 use crate::db::{
     base::{
-        helper,
-        order_by_builder::{LimitBuilder, OrderByBuilder},
+        order_by_builder::{Direction, LimitBuilder, OrderByBuilder},
+        query::QueryBuilder,
     },
     field_values::FieldValue,
+    types::StructuredColumnAndValue,
 };
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct CursorBuilder {
     col: String,
     last: Option<FieldValue>,
+    /// Tie breaker columns for when `col` is not unique
+    #[serde(default)]
+    then_by: Vec<String>,
+    #[serde(default)]
+    then_last: Vec<FieldValue>,
     limit: LimitBuilder,
     order: OrderByBuilder,
 }
@@ -28,14 +34,20 @@ impl CursorBuilder {
         }
     }
 
+    /// Adds a tie breaker column. Rows with the same value in the previous
+    /// columns are ordered by this column
+    pub fn then_by(&mut self, column: &str) -> &mut Self {
+        self.then_by.push(column.to_string());
+        self.then_last.clear();
+        self.set_direction(self.direction())
+    }
+
     pub fn set_desc(&mut self) -> &mut Self {
-        self.order = OrderByBuilder::new_desc(self.column());
-        self
+        self.set_direction(Direction::DESC)
     }
 
     pub fn set_asc(&mut self) -> &mut Self {
-        self.order = OrderByBuilder::new_asc(self.column());
-        self
+        self.set_direction(Direction::ASC)
     }
 
     pub fn set_last(&mut self, last: FieldValue) -> &mut Self {
@@ -43,6 +55,18 @@ impl CursorBuilder {
         self
     }
 
+    /// Sets the last values of the cursor's column and tie breaker columns
+    pub fn set_last_values(&mut self, mut values: Vec<FieldValue>) -> &mut Self {
+        if values.is_empty() {
+            self.last = None;
+            self.then_last.clear();
+        } else {
+            self.last = Some(values.remove(0));
+            self.then_last = values;
+        }
+        self
+    }
+
     pub fn set_limit(&mut self, limit: usize) -> &mut Self {
         self.limit.limit = limit;
         self
@@ -60,6 +84,13 @@ impl CursorBuilder {
         &self.col
     }
 
+    /// The cursor's column followed by the tie breaker columns
+    pub fn columns(&self) -> Vec<&str> {
+        std::iter::once(self.col.as_str())
+            .chain(self.then_by.iter().map(String::as_str))
+            .collect()
+    }
+
     pub fn order(&self) -> &OrderByBuilder {
         &self.order
     }
@@ -67,7 +98,7 @@ impl CursorBuilder {
     /// Encodes the instance to a base64 string
     pub fn encode(&self) -> String {
         let data = serde_json::to_string(self).expect("could not stringify cursor builder");
-        return dirtybase_helper::base64::url_encode(data.as_bytes());
+        dirtybase_helper::base64::url_encode(data.as_bytes())
     }
 
     /// Tries to decode the base64 string to an instance
@@ -77,6 +108,75 @@ impl CursorBuilder {
             Err(e) => Err(anyhow::anyhow!(e)),
         }
     }
+
+    /// Adds the condition that skips the rows up to the last values.
+    /// For the columns `a, b` that is `a > ? OR (a = ? AND b > ?)`
+    pub(crate) fn apply_to(&self, query: &mut QueryBuilder) {
+        let Some(last) = self.last.clone() else {
+            return;
+        };
+
+        let mut pairs = vec![(self.col.clone(), last)];
+        // tie breakers are only used when all their values are known
+        if self.then_last.len() == self.then_by.len() {
+            pairs.extend(
+                self.then_by
+                    .iter()
+                    .cloned()
+                    .zip(self.then_last.iter().cloned()),
+            );
+        }
+
+        let is_desc = self.direction() == Direction::DESC;
+        query.and_where(|q| {
+            for index in 0..pairs.len() {
+                q.or_where(|q| {
+                    for (column, value) in &pairs[..index] {
+                        q.is_eq(column, value.clone());
+                    }
+                    let (column, value) = pairs[index].clone();
+                    if is_desc {
+                        q.le(column, value);
+                    } else {
+                        q.gt(column, value);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Moves the cursor to the given row
+    pub(crate) fn set_last_row(&mut self, row: &StructuredColumnAndValue) {
+        let values = self
+            .columns()
+            .into_iter()
+            .map_while(|column| row.get(column).cloned())
+            .collect::<Vec<FieldValue>>();
+
+        if !values.is_empty() {
+            self.set_last_values(values);
+        }
+    }
+
+    fn direction(&self) -> Direction {
+        self.order
+            .orders
+            .first()
+            .map(|(_, direction)| direction.clone())
+            .unwrap_or(Direction::ASC)
+    }
+
+    fn set_direction(&mut self, direction: Direction) -> &mut Self {
+        let mut order = OrderByBuilder::new();
+        for column in self.columns() {
+            match direction {
+                Direction::ASC => order.asc(column),
+                Direction::DESC => order.desc(column),
+            };
+        }
+        self.order = order;
+        self
+    }
 }
 
 impl Default for CursorBuilder {
@@ -84,6 +184,8 @@ impl Default for CursorBuilder {
         Self {
             col: "id".to_string(),
             last: None,
+            then_by: Vec::new(),
+            then_last: Vec::new(),
             limit: LimitBuilder { limit: 25 },
             order: OrderByBuilder::new_asc("id"),
         }
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::index::IndexProp;
+
+/// How a full-text search term is interpreted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// Rows containing any of the words
+    #[default]
+    Natural,
+    /// The search syntax of the database, e.g. `+rust -go` on MySQL
+    Boolean,
+    /// The words next to each other in the given order
+    Phrase,
+}
+
+/// A full-text search over the columns of a `fulltext_index`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FullTextMatch {
+    table: String,
+    columns: Vec<String>,
+    term: String,
+    mode: MatchMode,
+}
+
+impl FullTextMatch {
+    pub fn new(table: &str, columns: &[&str], term: &str, mode: MatchMode) -> Self {
+        let mut columns = columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        columns.sort();
+
+        Self {
+            table: table.to_string(),
+            columns,
+            term: term.to_string(),
+            mode,
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Sorted, in the same order as the columns of the index
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn term(&self) -> &str {
+        &self.term
+    }
+
+    pub fn mode(&self) -> MatchMode {
+        self.mode
+    }
+
+    /// The name of the SQLite FTS5 table that shadows the index
+    pub fn shadow_table(&self) -> String {
+        let columns = self.columns.iter().map(String::as_str).collect::<Vec<_>>();
+        shadow_table_name(&self.table, &IndexProp::new(&columns, false))
+    }
+
+    /// The words of the term, without any search syntax
+    pub fn words(&self) -> Vec<String> {
+        self.term
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// The name of the SQLite FTS5 table that shadows a full-text index
+pub fn shadow_table_name(table: &str, index: &IndexProp) -> String {
+    format!("{table}_{}_fts", index.name())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_full_text_match() {
+        let search = FullTextMatch::new(
+            "posts",
+            &["title", "body"],
+            "rust's -async",
+            MatchMode::Natural,
+        );
+        assert_eq!(search.columns(), ["body", "title"]);
+        assert_eq!(search.shadow_table(), "posts_bodytitle_fts");
+        assert_eq!(search.words(), vec!["rust", "s", "async"]);
+    }
+}
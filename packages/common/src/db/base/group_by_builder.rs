@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupByBuilder {
+    pub(crate) columns: Vec<String>,
+}
+
+impl GroupByBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<C: ToString>(&mut self, column: C) -> &mut Self {
+        let column = column.to_string();
+        if !self.columns.contains(&column) {
+            self.columns.push(column);
+        }
+        self
+    }
+
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn as_clause(&self) -> String {
+        format!("GROUP BY {}", self.columns.join(","))
+    }
+}
+
+impl Display for GroupByBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_clause())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_group_by_a_field() {
+        let mut group = GroupByBuilder::new();
+        group.add("a");
+
+        assert_eq!(group.to_string(), "GROUP BY a");
+    }
+
+    #[test]
+    fn test_group_by_multiple_fields() {
+        let mut group = GroupByBuilder::new();
+        group.add("a").add("b").add("a");
+
+        assert_eq!(group.to_string(), "GROUP BY a,b");
+    }
+}
@@ -6,6 +6,9 @@ use serde::{Deserialize, Serialize};
 pub struct IndexProp {
     columns: Vec<String>,
     to_delete: bool,
+    /// The name of an existing index, when it is not derived from the columns
+    #[serde(default)]
+    name: Option<String>,
 }
 
 impl IndexProp {
@@ -19,9 +22,21 @@ impl IndexProp {
         Self {
             to_delete,
             columns: col,
+            name: None,
         }
     }
+
+    pub fn named(name: &str, columns: &[&str], to_delete: bool) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ..Self::new(columns, to_delete)
+        }
+    }
+
     pub fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
         let mut name = self.columns.join("").to_ascii_lowercase();
         name.truncate(64);
         name
@@ -45,6 +60,7 @@ pub enum IndexType {
     Primary(IndexProp),
     Unique(IndexProp),
     Index(IndexProp),
+    FullText(IndexProp),
 }
 
 impl Display for IndexType {
@@ -69,6 +85,18 @@ impl Display for IndexType {
                     write!(f, "ADD INDEX {} ({})", index.name(), index.concat_columns())
                 }
             }
+            Self::FullText(index) => {
+                if index.delete_index() {
+                    write!(f, "DROP INDEX {}", index.name())
+                } else {
+                    write!(
+                        f,
+                        "ADD FULLTEXT INDEX {} ({})",
+                        index.name(),
+                        index.concat_columns()
+                    )
+                }
+            }
             Self::Unique(index) => {
                 if index.delete_index() {
                     write!(f, "DROP UNIQUE INDEX {}", index.name())
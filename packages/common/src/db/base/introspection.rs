@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    column::{ColumnBlueprint, ColumnDefault, ColumnType, ForeignKey},
+    index::{IndexProp, IndexType},
+    table::TableBlueprint,
+};
+
+/// The structure of a table as it exists in the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDescription {
+    pub name: String,
+    pub columns: Vec<ColumnDescription>,
+    pub indexes: Vec<IndexDescription>,
+    pub foreign_keys: Vec<ForeignKeyDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnDescription {
+    pub name: String,
+    /// The type as reported by the database, e.g. `varchar(255)`
+    pub data_type: String,
+    pub column_type: ColumnType,
+    pub is_nullable: bool,
+    pub is_primary: bool,
+    pub auto_increment: bool,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexDescription {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+    pub is_primary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKeyDescription {
+    pub column: String,
+    pub foreign_table: String,
+    pub foreign_column: String,
+    pub on_delete: Option<String>,
+}
+
+impl TableDescription {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            columns: Vec::new(),
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+        }
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnDescription> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// Adds a column to the named index, creating the index on its first column
+    pub fn push_index_column(&mut self, name: &str, column: &str, unique: bool, primary: bool) {
+        match self.indexes.iter_mut().find(|index| index.name == name) {
+            Some(index) => index.columns.push(column.to_string()),
+            None => self.indexes.push(IndexDescription {
+                name: name.to_string(),
+                columns: vec![column.to_string()],
+                is_unique: unique || primary,
+                is_primary: primary,
+            }),
+        }
+    }
+
+    /// Converts the description into the blueprint that would create this table
+    pub fn to_blueprint(&self) -> TableBlueprint {
+        let mut table = TableBlueprint::new(&self.name);
+        table.set_is_new(false);
+
+        for column in &self.columns {
+            let mut blueprint = ColumnBlueprint::new(
+                &column.name,
+                if column.auto_increment {
+                    ColumnType::AutoIncrementId
+                } else {
+                    column.column_type.clone()
+                },
+            );
+            blueprint.set_is_nullable(column.is_nullable);
+            blueprint.is_primary = column.is_primary;
+            blueprint.default = column.default.clone().map(ColumnDefault::Custom);
+            blueprint.is_unique = self.indexes.iter().any(|index| {
+                index.is_unique && !index.is_primary && index.columns == [column.name.clone()]
+            });
+            blueprint.relationship = self
+                .foreign_keys
+                .iter()
+                .find(|fk| fk.column == column.name)
+                .map(|fk| {
+                    ForeignKey::new(
+                        &fk.foreign_table,
+                        &fk.foreign_column,
+                        fk.on_delete
+                            .as_ref()
+                            .is_some_and(|rule| rule.eq_ignore_ascii_case("cascade")),
+                    )
+                });
+            table.columns.push(blueprint);
+        }
+
+        let indexes = self
+            .indexes
+            .iter()
+            .filter(|index| !index.is_primary && (!index.is_unique || index.columns.len() > 1))
+            .map(|index| {
+                let columns = index.columns.iter().map(String::as_str).collect::<Vec<_>>();
+                if index.is_unique {
+                    IndexType::Unique(IndexProp::new(&columns, false))
+                } else {
+                    IndexType::Index(IndexProp::new(&columns, false))
+                }
+            })
+            .collect::<Vec<_>>();
+        if !indexes.is_empty() {
+            table.indexes = Some(indexes);
+        }
+
+        table
+    }
+}
+
+/// The structure and size of the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseDescription {
+    pub kind: String,
+    /// Size in bytes when the database reports it
+    pub size: Option<u64>,
+    /// Table names along with their number of rows
+    pub tables: Vec<(String, u64)>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_sql_type() {
+        assert_eq!(
+            ColumnType::from_sql_type("VARCHAR(80)"),
+            ColumnType::String(80)
+        );
+        assert_eq!(
+            ColumnType::from_sql_type("character varying(255)"),
+            ColumnType::String(255)
+        );
+        assert_eq!(ColumnType::from_sql_type("bigint(20)"), ColumnType::Integer);
+        assert_eq!(ColumnType::from_sql_type("tinyint(1)"), ColumnType::Boolean);
+        assert_eq!(
+            ColumnType::from_sql_type("timestamp with time zone"),
+            ColumnType::Timestamp
+        );
+        assert_eq!(ColumnType::from_sql_type("binary(16)"), ColumnType::Uuid);
+        assert_eq!(
+            ColumnType::from_sql_type("enum('draft','published')"),
+            ColumnType::Enum(vec!["draft".to_string(), "published".to_string()])
+        );
+        assert_eq!(ColumnType::from_sql_type("geometry"), ColumnType::Text);
+    }
+
+    #[test]
+    fn test_to_blueprint() {
+        let mut table = TableDescription::new("posts");
+        table.columns.push(ColumnDescription {
+            name: "id".to_string(),
+            data_type: "INTEGER".to_string(),
+            column_type: ColumnType::Integer,
+            is_nullable: false,
+            is_primary: true,
+            auto_increment: true,
+            default: None,
+        });
+        table.columns.push(ColumnDescription {
+            name: "slug".to_string(),
+            data_type: "VARCHAR(255)".to_string(),
+            column_type: ColumnType::String(255),
+            is_nullable: true,
+            is_primary: false,
+            auto_increment: false,
+            default: None,
+        });
+        table.push_index_column("posts_slug", "slug", true, false);
+
+        let blueprint = table.to_blueprint();
+        assert_eq!(
+            blueprint.columns[0].column_type,
+            ColumnType::AutoIncrementId
+        );
+        assert!(blueprint.columns[1].is_unique);
+        assert_eq!(blueprint.columns[1].is_nullable, Some(true));
+        assert!(blueprint.indexes.is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JsonPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A path into a JSON document such as `$.settings.theme` or `$.tags[0]`.
+/// The path is written into the query, keys are limited to letters, digits, `_` and `-`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonPath {
+    segments: Vec<JsonPathSegment>,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, anyhow::Error> {
+        let invalid = || anyhow!("invalid JSON path: {path}");
+        let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+        let mut segments = Vec::new();
+
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix('.') {
+                let end = tail.find(['.', '[']).unwrap_or(tail.len());
+                let key = &tail[..end];
+                if key.is_empty()
+                    || !key
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    return Err(invalid());
+                }
+                segments.push(JsonPathSegment::Key(key.to_string()));
+                rest = &tail[end..];
+            } else if let Some(tail) = rest.strip_prefix('[') {
+                let end = tail.find(']').ok_or_else(invalid)?;
+                let index = tail[..end].parse::<usize>().map_err(|_| invalid())?;
+                segments.push(JsonPathSegment::Index(index));
+                rest = &tail[end + 1..];
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn segments(&self) -> &[JsonPathSegment] {
+        &self.segments
+    }
+
+    /// Postgres `->` steps applied to the column. The last step is `->>` when `as_text` is set
+    pub fn to_arrows(&self, column: &str, as_text: bool) -> String {
+        if self.segments.is_empty() {
+            return if as_text {
+                format!("{column} #>> '{{}}'")
+            } else {
+                column.to_string()
+            };
+        }
+
+        let mut sql = column.to_string();
+        for (index, segment) in self.segments.iter().enumerate() {
+            let arrow = if as_text && index == self.segments.len() - 1 {
+                "->>"
+            } else {
+                "->"
+            };
+            match segment {
+                JsonPathSegment::Key(key) => sql.push_str(&format!(" {arrow} '{key}'")),
+                JsonPathSegment::Index(i) => sql.push_str(&format!(" {arrow} {i}")),
+            }
+        }
+        sql
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.segments {
+            match segment {
+                JsonPathSegment::Key(key) => write!(f, ".{key}")?,
+                JsonPathSegment::Index(i) => write!(f, "[{i}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let path = JsonPath::parse("$.settings.tags[1].name").unwrap();
+        assert_eq!(path.segments().len(), 4);
+        assert_eq!(path.segments()[2], JsonPathSegment::Index(1));
+        assert_eq!(path.to_string(), "$.settings.tags[1].name");
+        assert_eq!(
+            path.to_arrows("data", true),
+            "data -> 'settings' -> 'tags' -> 1 ->> 'name'"
+        );
+        assert_eq!(
+            JsonPath::parse("$").unwrap().to_arrows("data", false),
+            "data"
+        );
+
+        assert!(JsonPath::parse("settings").is_err());
+        assert!(JsonPath::parse("$.theme' OR 1=1").is_err());
+        assert!(JsonPath::parse("$.tags[x]").is_err());
+        assert!(JsonPath::parse("$..theme").is_err());
+    }
+}
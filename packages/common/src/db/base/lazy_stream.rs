@@ -0,0 +1,21 @@
+use std::future::Future;
+
+/// A stream of entities fetched in chunks by a background task
+pub type LazyStream<T> = tokio_stream::wrappers::ReceiverStream<Result<T, anyhow::Error>>;
+
+/// The sending half handed to the producer of a `LazyStream`
+pub type LazySender<T> = tokio::sync::mpsc::Sender<Result<T, anyhow::Error>>;
+
+/// Spawns `producer` and returns a stream of the items it sends.
+/// The producer should stop when sending fails, the stream has been dropped
+pub fn lazy_stream<T, F, Fut>(buffer: usize, producer: F) -> LazyStream<T>
+where
+    T: Send + 'static,
+    F: FnOnce(LazySender<T>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (sender, receiver) = tokio::sync::mpsc::channel(buffer.max(1));
+    tokio::spawn(producer(sender));
+
+    tokio_stream::wrappers::ReceiverStream::new(receiver)
+}
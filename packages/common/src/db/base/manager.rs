@@ -1,6 +1,8 @@
 use std::{
+    collections::HashMap,
     fmt::Debug,
     sync::{Arc, atomic::AtomicI64},
+    time::Duration,
 };
 
 use crate::db::{
@@ -12,13 +14,23 @@ use crate::db::{
 };
 
 use super::{
+    introspection::{DatabaseDescription, TableDescription},
     query::QueryBuilder,
-    schema::{DatabaseKind, SchemaManagerTrait, SchemaQuery},
+    query_log::{LoggedSchemaManager, QueryLogEntry, QueryLogger, QueryStats},
+    schema::{
+        ClientType, DatabaseKind, ExecuteResult, PretendLog, SchemaManagerTrait, SchemaQuery,
+    },
     table::TableBlueprint,
 };
 use crate::db::{TableModel, field_values::FieldValue};
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use futures::future::BoxFuture;
 use orsomafo::Dispatchable;
+use tokio::sync::RwLock;
+
+/// Builds the manager of a named connection set
+pub type ConnectionResolver =
+    Arc<dyn Fn(String) -> BoxFuture<'static, Result<Manager>> + Send + Sync>;
 
 #[derive(Clone)]
 pub struct Manager {
@@ -29,6 +41,12 @@ pub struct Manager {
     is_writable: bool,
     last_write_ts: Arc<AtomicI64>,
     in_trans: bool,
+    pretend: Option<PretendLog>,
+    logger: QueryLogger,
+    connection_name: Arc<String>,
+    resolver: Option<ConnectionResolver>,
+    named: Arc<RwLock<HashMap<String, Manager>>>,
+    use_connection: Option<Arc<String>>,
 }
 
 impl Debug for Manager {
@@ -53,16 +71,135 @@ impl Manager {
             is_writable,
             last_write_ts: Arc::default(),
             in_trans: false,
+            pretend: None,
+            logger: QueryLogger::default(),
+            connection_name: Arc::default(),
+            resolver: None,
+            named: Arc::default(),
+            use_connection: None,
         }
     }
 
+    /// Names this manager's connection set and sets how the other sets are built
+    pub fn set_connection_resolver(&mut self, name: &str, resolver: ConnectionResolver) {
+        self.connection_name = Arc::new(name.to_string());
+        self.resolver = Some(resolver);
+    }
+
+    /// The name of the connection set in database.toml
+    pub fn connection_name(&self) -> &str {
+        self.connection_name.as_str()
+    }
+
+    /// Returns the manager of a named connection set. Pools are created the first time
+    /// the set is used and are shared with every copy of this manager
+    pub async fn connection(&self, name: &str) -> Result<Self> {
+        if name == self.connection_name.as_str() {
+            let mut manager = self.clone();
+            manager.use_connection = None;
+            return Ok(manager);
+        }
+
+        let cached = self.named.read().await.get(name).cloned();
+        let mut manager = match cached {
+            Some(manager) => manager,
+            None => {
+                let resolver = self
+                    .resolver
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("database connection `{}` is not configured", name))?;
+                let mut manager = (resolver)(name.to_string()).await?;
+                // the set this manager belongs to is not cached by itself
+                if !Arc::ptr_eq(&manager.named, &self.named) {
+                    manager.connection_name = Arc::new(name.to_string());
+                    manager.resolver = self.resolver.clone();
+                    manager.named = self.named.clone();
+                    self.named
+                        .write()
+                        .await
+                        .entry(name.to_string())
+                        .or_insert_with(|| manager.clone());
+                }
+                manager
+            }
+        };
+
+        manager.in_trans = self.in_trans;
+        manager.pretend = self.pretend.clone();
+        if let Some(stats) = self.logger.stats() {
+            manager.logger.set_stats(Some(stats.clone()));
+        }
+
+        Ok(manager)
+    }
+
+    /// Returns a copy of this manager that runs its queries on the named connection set.
+    /// The set is resolved when the first query runs
+    pub fn on_connection(&self, name: &str) -> Self {
+        let mut manager = self.clone();
+        manager.use_connection = if name == self.connection_name.as_str() {
+            None
+        } else {
+            Some(Arc::new(name.to_string()))
+        };
+        manager
+    }
+
+    /// Returns a copy of this manager that records the write statements instead of running them
+    pub fn pretend(&self) -> Self {
+        let mut manager = self.clone();
+        manager.pretend = Some(PretendLog::default());
+        manager
+    }
+
+    pub fn is_pretending(&self) -> bool {
+        self.pretend.is_some()
+    }
+
+    /// True for the manager passed to a `transaction` callback
+    pub fn is_in_transaction(&self) -> bool {
+        self.in_trans
+    }
+
+    /// The statements recorded while pretending
+    pub fn pretended_statements(&self) -> Vec<String> {
+        self.pretend
+            .as_ref()
+            .map(PretendLog::statements)
+            .unwrap_or_default()
+    }
+
+    /// Calls the hook after every query run by this manager and its copies
+    pub fn on_query(&self, hook: impl Fn(&QueryLogEntry) + Send + Sync + 'static) {
+        self.logger.add_hook(Arc::new(hook));
+    }
+
+    /// Queries that take at least this long are logged as warnings
+    pub fn set_slow_query_threshold(&mut self, threshold: Option<Duration>) {
+        self.logger.set_slow_query_threshold(threshold);
+    }
+
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.logger.slow_query_threshold()
+    }
+
+    /// Returns a copy of this manager that counts its queries in `stats`
+    pub fn with_query_stats(&self, stats: QueryStats) -> Self {
+        let mut manager = self.clone();
+        manager.logger.set_stats(Some(stats));
+        manager
+    }
+
+    pub fn query_stats(&self) -> Option<&QueryStats> {
+        self.logger.stats()
+    }
+
     // Get a table or view for querying
     pub fn select_from_table<F>(&self, table: &str, callback: F) -> SchemaQuery
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder =
-            QueryBuilder::new(table, super::query::QueryAction::Query { columns: None });
+        let mut query_builder = QueryBuilder::new(table, super::query::QueryAction::query());
         callback(&mut query_builder);
 
         SchemaQuery::new(query_builder, self.clone())
@@ -76,7 +213,7 @@ impl Manager {
     }
 
     pub fn table(&self, table: &str) -> QueryBuilder {
-        QueryBuilder::new(table, super::query::QueryAction::Query { columns: None })
+        QueryBuilder::new(table, super::query::QueryAction::query())
     }
 
     pub fn select_table<T>(&self) -> QueryBuilder
@@ -101,7 +238,7 @@ impl Manager {
             table.set_is_new(true);
 
             callback(&mut table);
-            self.write_connection().await.apply(table).await?;
+            self.write_connection().await?.apply(table).await?;
             self.dispatch_written_event();
 
             return Ok(());
@@ -109,6 +246,14 @@ impl Manager {
         Err(anyhow::anyhow!("{} already exist", name))
     }
 
+    /// Creates the model's table from its blueprint
+    pub async fn create_table_for<T: TableModel>(&self) -> Result<(), anyhow::Error> {
+        self.create_table_schema(T::table_name(), |table| {
+            *table = T::blueprint();
+        })
+        .await
+    }
+
     // Get an existing table for updating
     pub async fn update_table_schema(
         &self,
@@ -120,7 +265,7 @@ impl Manager {
             table.set_is_new(false);
 
             callback(&mut table);
-            self.write_connection().await.apply(table).await?;
+            self.write_connection().await?.apply(table).await?;
             self.dispatch_written_event();
             return Ok(());
         }
@@ -134,24 +279,25 @@ impl Manager {
         from_table: &str,
         callback: impl FnOnce(&mut QueryBuilder),
     ) -> Result<(), anyhow::Error> {
-        let mut query = QueryBuilder::new(
-            from_table,
-            super::query::QueryAction::Query { columns: None },
-        );
+        let mut query = QueryBuilder::new(from_table, super::query::QueryAction::query());
         callback(&mut query);
         let mut table = TableBlueprint::new(name);
 
         table.view_query = Some(query);
-        self.write_connection().await.apply(table).await?;
+        self.write_connection().await?.apply(table).await?;
         self.dispatch_written_event();
         Ok(())
     }
 
-    pub async fn insert<CV: ToColumnAndValue>(&self, table_name: &str, record: CV) -> Result<()> {
+    pub async fn insert<CV: ToColumnAndValue>(
+        &self,
+        table_name: &str,
+        record: CV,
+    ) -> Result<ExecuteResult> {
         self.insert_multi(table_name, vec![record]).await
     }
 
-    pub async fn insert_into<T>(&self, record: impl ToColumnAndValue) -> Result<()>
+    pub async fn insert_into<T>(&self, record: impl ToColumnAndValue) -> Result<ExecuteResult>
     where
         T: TableModel,
     {
@@ -162,7 +308,7 @@ impl Manager {
         &self,
         table_name: &str,
         record: &CV,
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         self.insert_multi(table_name, vec![record.to_column_value()?])
             .await
     }
@@ -171,12 +317,16 @@ impl Manager {
         &self,
         table_name: &str,
         rows: R,
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         self.create_insert_query(table_name, rows, false).await
     }
 
     /// Insert row gracefully ignore insert duplicates
-    pub async fn soft_insert<I: ToColumnAndValue>(&self, table_name: &str, row: I) -> Result<()> {
+    pub async fn soft_insert<I: ToColumnAndValue>(
+        &self,
+        table_name: &str,
+        row: I,
+    ) -> Result<ExecuteResult> {
         self.create_insert_query(table_name, vec![row], true).await
     }
 
@@ -185,7 +335,7 @@ impl Manager {
         &self,
         table_name: &str,
         rows: R,
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         self.create_insert_query(table_name, rows, true).await
     }
 
@@ -200,7 +350,7 @@ impl Manager {
         row: I,
         update: &[&str],
         unique: &[&str],
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         self.upsert_multi(table_name, vec![row], update, unique)
             .await
     }
@@ -211,7 +361,7 @@ impl Manager {
         rows: R,
         update: &[&str],
         unique: &[&str],
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         let query = QueryBuilder::new(
             table_name,
             super::query::QueryAction::Upsert {
@@ -227,9 +377,9 @@ impl Manager {
             },
         );
 
-        self.write_connection().await.execute(query).await?;
+        let result = self.write_connection().await?.execute(query).await?;
         self.dispatch_written_event();
-        Ok(())
+        Ok(result)
     }
 
     pub async fn update<R: ToColumnAndValue>(
@@ -237,23 +387,23 @@ impl Manager {
         table_name: &str,
         row: R,
         callback: impl FnOnce(&mut QueryBuilder),
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         let mut query = QueryBuilder::new(
             table_name,
             super::query::QueryAction::Update(row.to_column_value()?),
         );
         callback(&mut query);
 
-        self.write_connection().await.execute(query).await?;
+        let result = self.write_connection().await?.execute(query).await?;
         self.dispatch_written_event();
-        Ok(())
+        Ok(result)
     }
 
     pub async fn update_table<T: TableModel>(
         &self,
         row: impl ToColumnAndValue,
         callback: impl FnOnce(&mut QueryBuilder),
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         self.update(T::table_name(), row, callback).await
     }
 
@@ -261,15 +411,18 @@ impl Manager {
         &self,
         table_name: &str,
         callback: impl FnOnce(&mut QueryBuilder),
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         let mut query = QueryBuilder::new(table_name, super::query::QueryAction::Delete);
         callback(&mut query);
-        self.write_connection().await.execute(query).await?;
+        let result = self.write_connection().await?.execute(query).await?;
         self.dispatch_written_event();
-        Ok(())
+        Ok(result)
     }
 
-    pub async fn delete_from_table<T>(&self, callback: impl FnOnce(&mut QueryBuilder)) -> Result<()>
+    pub async fn delete_from_table<T>(
+        &self,
+        callback: impl FnOnce(&mut QueryBuilder),
+    ) -> Result<ExecuteResult>
     where
         T: TableModel,
     {
@@ -289,22 +442,55 @@ impl Manager {
     }
 
     pub async fn has_table(&self, name: &str) -> Result<bool, anyhow::Error> {
-        self.read_connection().await.has_table(name).await
+        self.read_connection().await?.has_table(name).await
+    }
+
+    /// Names of the tables in the database
+    pub async fn tables(&self) -> Result<Vec<String>> {
+        self.read_connection().await?.tables().await
+    }
+
+    /// Describes the columns, indexes and foreign keys of a table as it exists in the database
+    pub async fn describe_table(&self, name: &str) -> Result<Option<TableDescription>> {
+        self.read_connection().await?.describe_table(name).await
+    }
+
+    /// Size of the database in bytes, when the database reports it
+    pub async fn database_size(&self) -> Result<Option<u64>> {
+        self.read_connection().await?.database_size().await
+    }
+
+    /// Describes the database along with the number of rows of each table
+    pub async fn describe_database(&self) -> Result<DatabaseDescription> {
+        let mut tables = Vec::new();
+        for name in self.tables().await? {
+            let rows = self.select_from_table(&name, |_| {}).count().await?;
+            tables.push((name, rows));
+        }
+
+        Ok(DatabaseDescription {
+            kind: self.kind.to_string(),
+            size: self.database_size().await?,
+            tables,
+        })
     }
 
     pub async fn drop_table(&self, table_name: &str) -> Result<(), anyhow::Error> {
-        self.write_connection().await.drop_table(table_name).await
+        self.write_connection().await?.drop_table(table_name).await
     }
 
     pub async fn rename_table(&self, old: &str, new: &str) -> Result<()> {
-        self.write_connection().await.rename_table(old, new).await?;
+        self.write_connection()
+            .await?
+            .rename_table(old, new)
+            .await?;
         self.dispatch_written_event();
         Ok(())
     }
 
     pub async fn drop_column(&self, table: &str, column: &str) -> Result<()> {
         self.write_connection()
-            .await
+            .await?
             .drop_column(table, column)
             .await?;
         self.dispatch_written_event();
@@ -313,18 +499,18 @@ impl Manager {
 
     pub async fn rename_column(&self, table: &str, old: &str, new: &str) -> Result<()> {
         self.write_connection()
-            .await
+            .await?
             .rename_column(table, old, new)
             .await?;
         self.dispatch_written_event();
         Ok(())
     }
 
-    pub async fn read_connection(&self) -> Box<dyn SchemaManagerTrait + Send> {
+    pub async fn read_connection(&self) -> Result<Box<dyn SchemaManagerTrait + Send>> {
         self.create_schema_manager(false).await
     }
 
-    pub async fn write_connection(&self) -> Box<dyn SchemaManagerTrait + Send> {
+    pub async fn write_connection(&self) -> Result<Box<dyn SchemaManagerTrait + Send>> {
         self.create_schema_manager(true).await
     }
 
@@ -333,7 +519,7 @@ impl Manager {
         table_name: &str,
         rows: R,
         do_soft_insert: bool,
-    ) -> Result<()> {
+    ) -> Result<ExecuteResult> {
         let query = QueryBuilder::new(
             table_name,
             super::query::QueryAction::Create {
@@ -342,13 +528,13 @@ impl Manager {
             },
         );
 
-        self.write_connection().await.execute(query).await?;
+        let result = self.write_connection().await?.execute(query).await?;
         self.dispatch_written_event();
-        Ok(())
+        Ok(result)
     }
 
     pub async fn raw_insert(&self, sql: &str) -> Result<bool, anyhow::Error> {
-        let result = self.write_connection().await.raw_insert(sql).await;
+        let result = self.write_connection().await?.raw_insert(sql).await;
         if result.is_ok() {
             self.dispatch_written_event();
         }
@@ -362,7 +548,7 @@ impl Manager {
     ) -> Result<u64, anyhow::Error> {
         let result = self
             .write_connection()
-            .await
+            .await?
             .raw_update(sql, params.into_iter().map(|v| v.into()).collect())
             .await;
         if result.is_ok() {
@@ -379,7 +565,7 @@ impl Manager {
     ) -> Result<u64, anyhow::Error> {
         let result = self
             .write_connection()
-            .await
+            .await?
             .raw_delete(sql, params.into_iter().map(|v| v.into()).collect())
             .await;
         if result.is_ok() {
@@ -395,13 +581,13 @@ impl Manager {
         params: Vec<P>,
     ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
         self.read_connection()
-            .await
+            .await?
             .raw_select(sql, params.into_iter().map(|v| v.into()).collect())
             .await
     }
 
     pub async fn raw_statement(&self, sql: &str) -> Result<bool, anyhow::Error> {
-        let result = self.write_connection().await.raw_statement(sql).await;
+        let result = self.write_connection().await?.raw_statement(sql).await;
 
         if result.is_ok() {
             self.dispatch_written_event();
@@ -415,6 +601,17 @@ impl Manager {
     }
 
     pub async fn close(self) {
+        let named = self
+            .named
+            .write()
+            .await
+            .drain()
+            .map(|(_, manager)| manager)
+            .collect::<Vec<_>>();
+        for manager in named {
+            Box::pin(manager.close()).await;
+        }
+
         for (_, collection) in self.connections.iter() {
             for pool in collection.values() {
                 pool.close().await;
@@ -429,14 +626,22 @@ impl Manager {
     async fn create_schema_manager(
         &self,
         mut for_write: bool,
-    ) -> Box<dyn SchemaManagerTrait + Send> {
+    ) -> Result<Box<dyn SchemaManagerTrait + Send>> {
+        if let Some(name) = &self.use_connection {
+            let manager = self.connection(name).await.map_err(|e| {
+                log::error!(target: "dirtybase_db", "could not use connection {}: {}", name, e);
+                anyhow::anyhow!("could not use connection {}: {}", name, e)
+            })?;
+            return Box::pin(async move { manager.create_schema_manager(for_write).await }).await;
+        }
+
         if self.in_trans {
             for_write = true;
         }
         let mut manager = match self.connections.get(&self.kind) {
             Some(pool) => {
                 if for_write {
-                    if let Some(write_pool) = pool.get(&super::schema::ClientType::Write) {
+                    if let Some(write_pool) = pool.get(&ClientType::Write) {
                         log::trace!("Using {:?}'s write pool for next query", &self.kind);
                         write_pool.schema_manger()
                     } else {
@@ -456,9 +661,13 @@ impl Manager {
                         return Box::pin(async { self.create_schema_manager(true).await }).await;
                     }
 
-                    if let Some(read_pool) = pool.get(&super::schema::ClientType::Read) {
+                    // replicas that are down or lagging behind fall back to the writer
+                    if let Some(read_manager) = pool
+                        .get(&ClientType::Read)
+                        .and_then(|read_pool| read_pool.available_schema_manger())
+                    {
                         log::trace!("Using {:?}'s read pool for next query", &self.kind);
-                        read_pool.schema_manger()
+                        read_manager
                     } else {
                         log::trace!("Using {:?}'s write pool for next read query", &self.kind);
                         return Box::pin(async { self.create_schema_manager(true).await }).await;
@@ -471,15 +680,33 @@ impl Manager {
             }
         };
 
+        if let Some(log) = &self.pretend {
+            manager.pretend(log.clone());
+            return Ok(manager);
+        }
+
+        if self.logger.is_active() {
+            let client_type = if for_write {
+                ClientType::Write
+            } else {
+                ClientType::Read
+            };
+            manager = Box::new(LoggedSchemaManager::new(
+                manager,
+                client_type,
+                self.kind.clone(),
+                self.logger.clone(),
+            ));
+        }
+
         if self.in_trans {
-            let result = manager.begin().await;
-            if result.is_ok() {
-                return result.unwrap();
+            match manager.begin().await {
+                Ok(trans) => return Ok(trans),
+                Err(e) => tracing::error!("could not start db transaction: {}", e),
             }
-            tracing::error!("could not start db transaction: {}", result.err().unwrap());
         }
 
-        manager
+        Ok(manager)
     }
 
     fn dispatch_written_event(&self) {
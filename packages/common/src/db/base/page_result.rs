@@ -0,0 +1,90 @@
+use serde::Serialize;
+
+/// A page of rows returned by `paginate`
+#[derive(Debug, Clone, Serialize)]
+pub struct PageResult<T> {
+    data: Vec<T>,
+    total: u64,
+    per_page: usize,
+    current_page: usize,
+    last_page: usize,
+}
+
+impl<T> PageResult<T> {
+    /// `current_page` starts at 1
+    pub fn new(data: Vec<T>, total: u64, current_page: usize, per_page: usize) -> Self {
+        let per_page = per_page.max(1);
+        let last_page = (total as usize).div_ceil(per_page).max(1);
+
+        Self {
+            data,
+            total,
+            per_page,
+            current_page: current_page.max(1),
+            last_page,
+        }
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Total number of rows across all the pages
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn per_page(&self) -> usize {
+        self.per_page
+    }
+
+    pub fn current_page(&self) -> usize {
+        self.current_page
+    }
+
+    pub fn last_page(&self) -> usize {
+        self.last_page
+    }
+
+    pub fn has_more_pages(&self) -> bool {
+        self.current_page < self.last_page
+    }
+
+    /// The number of rows to skip to get to the given page
+    pub fn offset_for(page: usize, per_page: usize) -> usize {
+        (page.max(1) - 1) * per_page.max(1)
+    }
+
+    /// Transforms the rows while keeping the page information
+    pub fn map<U, F: FnMut(T) -> U>(self, callback: F) -> PageResult<U> {
+        PageResult {
+            data: self.data.into_iter().map(callback).collect(),
+            total: self.total,
+            per_page: self.per_page,
+            current_page: self.current_page,
+            last_page: self.last_page,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_page_numbers() {
+        let page = PageResult::new(vec![1, 2], 12, 2, 5);
+        assert_eq!(page.last_page(), 3);
+        assert!(page.has_more_pages());
+        assert_eq!(PageResult::<i32>::offset_for(2, 5), 5);
+
+        let empty = PageResult::<i32>::new(Vec::new(), 0, 0, 0);
+        assert_eq!(empty.current_page(), 1);
+        assert_eq!(empty.last_page(), 1);
+        assert_eq!(PageResult::<i32>::offset_for(0, 10), 0);
+    }
+}
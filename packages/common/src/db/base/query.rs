@@ -11,12 +11,16 @@ use crate::db::{
 
 use super::{
     aggregate::Aggregate,
+    cte_builder::CteBuilder,
+    full_text::{FullTextMatch, MatchMode},
+    group_by_builder::GroupByBuilder,
     join_builder::JoinQueryBuilder,
     order_by_builder::{LimitBuilder, OffsetBuilder, OrderByBuilder},
     query_conditions::Condition,
     query_join_types::JoinType,
     query_operators::Operator,
     table::DELETED_AT_FIELD,
+    union_builder::{Distinct, UnionBuilder, UnionType},
     where_join_operators::WhereJoinOperator,
 };
 use std::{collections::BTreeMap, fmt::Display};
@@ -31,6 +35,10 @@ pub enum WhereJoin {
 pub enum QueryAction {
     Query {
         columns: Option<Vec<QueryColumn>>,
+        #[serde(default)]
+        distinct: Option<Distinct>,
+        #[serde(default)]
+        unions: Vec<UnionBuilder>,
     },
     Create {
         rows: Vec<ColumnAndValue>,
@@ -56,12 +64,18 @@ pub enum QueryAction {
 impl QueryAction {
     /// Create a new Query QueryAction
     pub fn query() -> Self {
-        Self::Query { columns: None }
+        Self::Query {
+            columns: None,
+            distinct: None,
+            unions: Vec::new(),
+        }
     }
 
     pub fn query_with(columns: Vec<QueryColumn>) -> Self {
         Self::Query {
             columns: Some(columns),
+            distinct: None,
+            unions: Vec::new(),
         }
     }
 }
@@ -81,7 +95,7 @@ impl Display for QueryAction {
                     unique: _,
                     to_update: _,
                 } => "Upsert",
-                QueryAction::Query { columns: _ } => "Query",
+                QueryAction::Query { .. } => "Query",
                 QueryAction::Update(_) => "Update",
                 QueryAction::Delete => "Delete",
                 QueryAction::DropTable => "DropTable",
@@ -96,11 +110,16 @@ impl Display for QueryAction {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct QueryBuilder {
+    ctes: Vec<CteBuilder>,
     where_clauses: Vec<WhereJoinOperator>,
     table: String,
+    #[serde(default)]
+    table_alias: Option<String>,
     joins: Option<BTreeMap<String, JoinQueryBuilder>>,
     action: QueryAction,
     order_by: Option<OrderByBuilder>,
+    group_by: Option<GroupByBuilder>,
+    having_clauses: Vec<WhereJoinOperator>,
     limit: Option<LimitBuilder>,
     offset: Option<OffsetBuilder>,
     cursor: Option<CursorBuilder>,
@@ -110,11 +129,15 @@ pub struct QueryBuilder {
 impl QueryBuilder {
     pub fn new(table: &str, action: QueryAction) -> Self {
         Self {
+            ctes: Vec::new(),
             where_clauses: Vec::new(),
             table: table.to_string(),
+            table_alias: None,
             joins: None,
             action,
             order_by: None,
+            group_by: None,
+            having_clauses: Vec::new(),
             limit: None,
             offset: None,
             cursor: None,
@@ -134,9 +157,66 @@ impl QueryBuilder {
         &self.table
     }
 
+    /// Sets the table to select from
+    pub fn from(&mut self, table: &str) -> &mut Self {
+        self.table = table.to_string();
+        self
+    }
+
+    /// Selects from the table under another name
+    pub fn alias(&mut self, alias: &str) -> &mut Self {
+        self.table_alias = Some(alias.to_string());
+        self
+    }
+
+    pub fn table_alias(&self) -> Option<&String> {
+        self.table_alias.as_ref()
+    }
+
+    /// Returns the CTEs defined for this query
+    pub fn ctes(&self) -> &[CteBuilder] {
+        &self.ctes
+    }
+
+    /// Defines a named subquery that can be used as a table in this query
+    pub fn with<F>(&mut self, name: &str, callback: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder),
+    {
+        let mut query_builder = Self::new("", QueryAction::query());
+        callback(&mut query_builder);
+
+        self.ctes.push(CteBuilder::new(name, query_builder));
+        self
+    }
+
+    /// Defines a named subquery that references itself
+    ///
+    /// The rows returned by the anchor are combined with the rows returned by the
+    /// recursive query, which is repeated until it returns no new rows.
+    /// The recursive query should join on the CTE using its name.
+    pub fn with_recursive<A, R>(&mut self, name: &str, anchor: A, recursive: R) -> &mut Self
+    where
+        A: FnOnce(&mut QueryBuilder),
+        R: FnOnce(&mut QueryBuilder),
+    {
+        let mut anchor_builder = Self::new("", QueryAction::query());
+        anchor(&mut anchor_builder);
+
+        let mut recursive_builder = Self::new("", QueryAction::query());
+        recursive(&mut recursive_builder);
+
+        self.ctes.push(CteBuilder::new_recursive(
+            name,
+            anchor_builder,
+            recursive_builder,
+        ));
+        self
+    }
+
     pub fn all_columns(&self) -> bool {
         match &self.action {
-            QueryAction::Query { columns } => columns.is_some(),
+            QueryAction::Query { columns, .. } => columns.is_some(),
             _ => false,
         }
     }
@@ -147,9 +227,8 @@ impl QueryBuilder {
     }
 
     pub fn lock_for_update(&mut self) -> &mut Self {
-        match &mut self.action {
-            QueryAction::Query { columns: _ } => self.lock_for_update = true,
-            _ => (),
+        if let QueryAction::Query { .. } = &self.action {
+            self.lock_for_update = true;
         }
         self
     }
@@ -158,7 +237,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
 
@@ -177,6 +256,21 @@ impl QueryBuilder {
         self
     }
 
+    /// Returns a query that counts the rows returned by this query.
+    /// The order, limit, offset and cursor of this query are ignored
+    pub fn count_query(&self, as_name: &str) -> QueryBuilder {
+        let mut inner = self.clone();
+        inner.order_by = None;
+        inner.limit = None;
+        inner.offset = None;
+        inner.cursor = None;
+
+        let mut query = Self::new_query("_counted");
+        query.ctes.push(CteBuilder::new("_counted", inner));
+        query.count_as("*", as_name);
+        query
+    }
+
     pub fn max(&mut self, column: &str) -> &mut Self {
         let as_name = format!("max_{column}",);
         self.max_as(column, &as_name)
@@ -234,6 +328,16 @@ impl QueryBuilder {
         self.joins.as_ref()
     }
 
+    /// Returns the `group by` columns
+    pub fn grouped_by(&self) -> Option<&GroupByBuilder> {
+        self.group_by.as_ref()
+    }
+
+    /// Returns a reference to the `having` clauses vec
+    pub fn having_clauses(&self) -> &Vec<WhereJoinOperator> {
+        &self.having_clauses
+    }
+
     pub fn order_by(&self) -> Option<&OrderByBuilder> {
         self.order_by.as_ref()
     }
@@ -297,7 +401,7 @@ impl QueryBuilder {
 
     /// Adds a column that should be selected
     pub fn select<T: Into<QueryColumn>>(&mut self, column: T) -> &mut Self {
-        if let QueryAction::Query { columns } = &mut self.action {
+        if let QueryAction::Query { columns, .. } = &mut self.action {
             let col: QueryColumn = column.into();
             if let Some(list) = columns {
                 list.push(col)
@@ -312,7 +416,7 @@ impl QueryBuilder {
     /// Adds a column that should be selected as alias
     pub fn select_as<T: Into<QueryColumn>>(&mut self, column: T, alias: &str) -> &mut Self {
         let table = self.table.clone();
-        if let QueryAction::Query { columns } = &mut self.action {
+        if let QueryAction::Query { columns, .. } = &mut self.action {
             let mut col: QueryColumn = column.into();
             col.set_table(&table);
             col.set_alias(alias);
@@ -331,7 +435,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut builder = QueryBuilder::new(table, QueryAction::Query { columns: None });
+        let mut builder = QueryBuilder::new(table, QueryAction::query());
         callback(&mut builder);
 
         let col = QueryColumn::new(
@@ -343,6 +447,31 @@ impl QueryBuilder {
         self.select(col)
     }
 
+    /// Selects the value at a JSON path of the column as alias
+    pub fn select_json<C: ToString>(&mut self, column: C, path: &str, alias: &str) -> &mut Self {
+        let col = QueryColumn::new(
+            QueryColumnName::Json(column.to_string(), path.to_string()),
+            None,
+            Some(alias),
+        );
+
+        self.select(col)
+    }
+
+    /// Selects the relevance of a full-text search as alias, order by it to get the best matches first
+    pub fn select_relevance(
+        &mut self,
+        columns: &[&str],
+        term: &str,
+        mode: MatchMode,
+        alias: &str,
+    ) -> &mut Self {
+        let search = FullTextMatch::new(&self.table, columns, term, mode);
+        let col = QueryColumn::new(QueryColumnName::Relevance(search), None, Some(alias));
+
+        self.select(col)
+    }
+
     /// Adds a table to the list of tables to select from
     pub fn select_table<T: TableModel>(&mut self) -> &mut Self {
         self.select_multiple(T::table_column_full_names())
@@ -353,7 +482,7 @@ impl QueryBuilder {
         &mut self,
         columns_to_select: C,
     ) -> &mut Self {
-        if let QueryAction::Query { columns } = &mut self.action {
+        if let QueryAction::Query { columns, .. } = &mut self.action {
             if let Some(list) = columns {
                 list.extend(columns_to_select.into_iter().map(|c| c.into()))
             } else {
@@ -391,6 +520,120 @@ impl QueryBuilder {
         self
     }
 
+    /// Groups the result by the specified columns
+    pub fn group_by<C: ToString, I: IntoIterator<Item = C>>(&mut self, columns: I) -> &mut Self {
+        let group = self.group_by.get_or_insert_with(GroupByBuilder::new);
+        for a_column in columns {
+            group.add(a_column);
+        }
+        self
+    }
+
+    /// Adds a `having` clause
+    ///
+    /// The conditions are built the same way `where` clauses are. Aggregates
+    /// should be referenced by their expression, e.g. `COUNT(id)`
+    pub fn having<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder),
+    {
+        self.having_operator(callback, WhereJoin::And)
+    }
+
+    /// Adds a `having` clause that is joined to the previous ones with `OR`
+    pub fn or_having<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder),
+    {
+        self.having_operator(callback, WhereJoin::Or)
+    }
+
+    fn having_operator<F>(&mut self, callback: F, and_or: WhereJoin) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder),
+    {
+        let mut query_builder = Self::new("", QueryAction::query());
+        callback(&mut query_builder);
+
+        if query_builder.where_clauses.is_empty() {
+            return self;
+        }
+
+        let condition = Condition::new(
+            "",
+            Operator::Clause,
+            QueryValue::Clause(Box::new(query_builder)),
+        );
+
+        self.having_clauses.push(if self.having_clauses.is_empty() {
+            WhereJoinOperator::None(condition)
+        } else {
+            match and_or {
+                WhereJoin::And => WhereJoinOperator::And(condition),
+                WhereJoin::Or => WhereJoinOperator::Or(condition),
+            }
+        });
+
+        self
+    }
+
+    /// Only returns distinct rows
+    pub fn distinct(&mut self) -> &mut Self {
+        if let QueryAction::Query { distinct, .. } = &mut self.action {
+            *distinct = Some(Distinct::Rows);
+        }
+        self
+    }
+
+    /// Only returns the first row of each set of rows where the columns are equal
+    ///
+    /// Postgres only
+    pub fn distinct_on<C: ToString, I: IntoIterator<Item = C>>(&mut self, columns: I) -> &mut Self {
+        if let QueryAction::Query { distinct, .. } = &mut self.action {
+            *distinct = Some(Distinct::On(
+                columns.into_iter().map(|c| c.to_string()).collect(),
+            ));
+        }
+        self
+    }
+
+    /// Returns the `distinct` modifier of the query
+    pub fn distinct_by(&self) -> Option<&Distinct> {
+        match &self.action {
+            QueryAction::Query { distinct, .. } => distinct.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Combines the result of the other query with this one, removing duplicate rows
+    ///
+    /// The ordering, limit and offset of this query apply to the combined result
+    pub fn union(&mut self, other: QueryBuilder) -> &mut Self {
+        self.add_union(UnionType::Union, other)
+    }
+
+    /// Combines the result of the other query with this one, keeping duplicate rows
+    ///
+    /// The ordering, limit and offset of this query apply to the combined result
+    pub fn union_all(&mut self, other: QueryBuilder) -> &mut Self {
+        self.add_union(UnionType::UnionAll, other)
+    }
+
+    /// Returns the queries combined with this one
+    pub fn unions(&self) -> &[UnionBuilder] {
+        match &self.action {
+            QueryAction::Query { unions, .. } => unions,
+            _ => &[],
+        }
+    }
+
+    fn add_union(&mut self, union_type: UnionType, other: QueryBuilder) -> &mut Self {
+        if let QueryAction::Query { unions, .. } = &mut self.action {
+            unions.push(UnionBuilder::new(union_type, other));
+        }
+        self
+    }
+
     pub fn limit(&mut self, limit: usize) -> &mut Self {
         self.limit = Some(LimitBuilder { limit });
         self
@@ -742,7 +985,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -753,6 +996,54 @@ impl QueryBuilder {
         )
     }
 
+    /// Compares a column to another column, e.g. `children.family_id = families.id`
+    pub fn is_eq_column<C: ToString, O: ToString>(&mut self, column: C, other: O) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::Equal,
+            QueryValue::ColumnName(other.to_string()),
+            None,
+        )
+    }
+
+    /// Adds an `EXISTS (sub query)` condition
+    pub fn exists(&mut self, query: QueryBuilder) -> &mut Self {
+        self.where_operator(
+            "",
+            Operator::Exists,
+            QueryValue::SubQuery(Box::new(query)),
+            None,
+        )
+    }
+
+    pub fn or_exists(&mut self, query: QueryBuilder) -> &mut Self {
+        self.where_operator(
+            "",
+            Operator::Exists,
+            QueryValue::SubQuery(Box::new(query)),
+            Some(WhereJoin::Or),
+        )
+    }
+
+    /// Adds a `NOT EXISTS (sub query)` condition
+    pub fn not_exists(&mut self, query: QueryBuilder) -> &mut Self {
+        self.where_operator(
+            "",
+            Operator::NotExists,
+            QueryValue::SubQuery(Box::new(query)),
+            None,
+        )
+    }
+
+    pub fn or_not_exists(&mut self, query: QueryBuilder) -> &mut Self {
+        self.where_operator(
+            "",
+            Operator::NotExists,
+            QueryValue::SubQuery(Box::new(query)),
+            Some(WhereJoin::Or),
+        )
+    }
+
     pub fn is_in_query<C: ToString>(&mut self, column: C, query: QueryBuilder) -> &mut Self {
         self.where_operator(
             column,
@@ -779,7 +1070,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -802,7 +1093,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -830,7 +1121,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -858,7 +1149,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -886,7 +1177,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new(table, QueryAction::Query { columns: None });
+        let mut query_builder = Self::new(table, QueryAction::query());
 
         callback(&mut query_builder);
         self.where_operator(
@@ -957,6 +1248,118 @@ impl QueryBuilder {
             .and_not_le_or_eq(column, last)
     }
 
+    /// Compares the value at a JSON path of the column, e.g. `$.settings.theme`
+    pub fn where_json<T: Into<QueryValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        operator: Operator,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::Json(path.to_string(), Box::new(operator)),
+            value,
+            None,
+        )
+    }
+
+    pub fn or_where_json<T: Into<QueryValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        operator: Operator,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::Json(path.to_string(), Box::new(operator)),
+            value,
+            Some(WhereJoin::Or),
+        )
+    }
+
+    /// The JSON array at the path of the column contains the value. Use `$` for the column itself
+    pub fn json_contains<T: Into<FieldValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::JsonContains(path.to_string()),
+            json_value(value),
+            None,
+        )
+    }
+
+    pub fn or_json_contains<T: Into<FieldValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::JsonContains(path.to_string()),
+            json_value(value),
+            Some(WhereJoin::Or),
+        )
+    }
+
+    /// Compares the number of items in the JSON array at the path of the column
+    pub fn json_length<T: Into<QueryValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        operator: Operator,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::JsonLength(path.to_string(), Box::new(operator)),
+            value,
+            None,
+        )
+    }
+
+    pub fn or_json_length<T: Into<QueryValue>, C: ToString>(
+        &mut self,
+        column: C,
+        path: &str,
+        operator: Operator,
+        value: T,
+    ) -> &mut Self {
+        self.where_operator(
+            column,
+            Operator::JsonLength(path.to_string(), Box::new(operator)),
+            value,
+            Some(WhereJoin::Or),
+        )
+    }
+
+    /// Full-text search over the columns of a `fulltext_index` of this table
+    pub fn match_against(&mut self, columns: &[&str], term: &str, mode: MatchMode) -> &mut Self {
+        let search = FullTextMatch::new(&self.table, columns, term, mode);
+        self.where_operator(
+            search.columns().join(","),
+            Operator::Match(search),
+            QueryValue::Null,
+            None,
+        )
+    }
+
+    pub fn or_match_against(&mut self, columns: &[&str], term: &str, mode: MatchMode) -> &mut Self {
+        let search = FullTextMatch::new(&self.table, columns, term, mode);
+        self.where_operator(
+            search.columns().join(","),
+            Operator::Match(search),
+            QueryValue::Null,
+            Some(WhereJoin::Or),
+        )
+    }
+
     fn where_(&mut self, where_clause: WhereJoinOperator) -> &mut Self {
         self.where_clauses.push(where_clause);
         self
@@ -1007,7 +1410,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new("", QueryAction::Query { columns: None });
+        let mut query_builder = Self::new("", QueryAction::query());
 
         callback(&mut query_builder);
 
@@ -1022,7 +1425,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new("", QueryAction::Query { columns: None });
+        let mut query_builder = Self::new("", QueryAction::query());
 
         callback(&mut query_builder);
 
@@ -1038,7 +1441,7 @@ impl QueryBuilder {
     where
         F: FnOnce(&mut QueryBuilder),
     {
-        let mut query_builder = Self::new("", QueryAction::Query { columns: None });
+        let mut query_builder = Self::new("", QueryAction::query());
 
         callback(&mut query_builder);
 
@@ -1269,3 +1672,8 @@ impl QueryBuilder {
         )
     }
 }
+
+/// The value as JSON text, bound in place of a JSON document
+fn json_value<T: Into<FieldValue>>(value: T) -> FieldValue {
+    FieldValue::String(serde_json::to_string(&value.into()).unwrap_or_default())
+}
@@ -0,0 +1,480 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{
+    introspection::TableDescription,
+    query::QueryBuilder,
+    schema::{ClientType, DatabaseKind, ExecuteResult, PretendLog, SchemaManagerTrait},
+    table::TableBlueprint,
+};
+use crate::db::{field_values::FieldValue, types::ColumnAndValue};
+
+const LOG_TARGET: &str = "dirtybase_db";
+
+/// A call made on a schema manager
+#[derive(Debug, Clone)]
+pub struct QueryLogEntry {
+    /// The `SchemaManagerTrait` method that was called, e.g. `fetch_all`
+    pub operation: &'static str,
+    /// The statements that ran, schema changes may run more than one
+    pub sql: Vec<String>,
+    pub params: Vec<FieldValue>,
+    pub duration: Duration,
+    pub client_type: ClientType,
+    pub kind: DatabaseKind,
+}
+
+impl QueryLogEntry {
+    pub fn is_read(&self) -> bool {
+        matches!(
+            self.operation,
+            "fetch_all" | "fetch_one" | "stream_result" | "raw_select"
+        )
+    }
+}
+
+pub type QueryHook = Arc<dyn Fn(&QueryLogEntry) + Send + Sync>;
+
+/// Collects the statements and bound values of the call being logged.
+/// Connectors feed it while building and running their statements
+#[derive(Debug, Clone, Default)]
+pub struct StatementRecorder(Arc<Mutex<(Vec<String>, Vec<FieldValue>)>>);
+
+impl StatementRecorder {
+    pub fn sql<S: ToString>(&self, sql: S) {
+        if let Ok(mut lock) = self.0.lock() {
+            lock.0.push(sql.to_string());
+        }
+    }
+
+    pub fn bind(&self, value: &FieldValue) {
+        if let Ok(mut lock) = self.0.lock() {
+            lock.1.push(value.clone());
+        }
+    }
+
+    /// Returns and clears what has been recorded so far
+    pub fn take(&self) -> (Vec<String>, Vec<FieldValue>) {
+        self.0
+            .lock()
+            .map(|mut lock| std::mem::take(&mut *lock))
+            .unwrap_or_default()
+    }
+}
+
+/// Counts the queries run for a context and spots the same read running over and over
+#[derive(Debug, Clone, Default)]
+pub struct QueryStats {
+    count: Arc<AtomicU64>,
+    n_plus_one_threshold: Option<u32>,
+    reads: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl QueryStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Warns when the same read statement runs `threshold` times, the sign of an N+1 query
+    pub fn with_n_plus_one_detection(threshold: u32) -> Self {
+        Self {
+            n_plus_one_threshold: Some(threshold.max(2)),
+            ..Self::default()
+        }
+    }
+
+    /// Number of calls made so far
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Read statements that reached the N+1 threshold along with the number of times they ran
+    pub fn repeated(&self) -> Vec<(String, u32)> {
+        let Some(threshold) = self.n_plus_one_threshold else {
+            return Vec::new();
+        };
+        self.reads
+            .lock()
+            .map(|reads| {
+                reads
+                    .iter()
+                    .filter(|(_, total)| **total >= threshold)
+                    .map(|(sql, total)| (sql.clone(), *total))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn record(&self, entry: &QueryLogEntry) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+
+        let Some(threshold) = self.n_plus_one_threshold else {
+            return;
+        };
+        if !entry.is_read() || entry.sql.is_empty() {
+            return;
+        }
+        let sql = entry.sql.join(";\n");
+        if let Ok(mut reads) = self.reads.lock() {
+            let total = reads.entry(sql).or_default();
+            *total += 1;
+            if *total == threshold {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "possible N+1 query, ran {} times: {}",
+                    total,
+                    entry.sql.join(";\n")
+                );
+            }
+        }
+    }
+}
+
+/// Feeds the calls made by a manager to the hooks, the slow query log and the query stats
+#[derive(Clone, Default)]
+pub struct QueryLogger {
+    hooks: Arc<RwLock<Vec<QueryHook>>>,
+    slow_query_threshold: Option<Duration>,
+    stats: Option<QueryStats>,
+}
+
+impl Debug for QueryLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryLogger")
+            .field("slow_query_threshold", &self.slow_query_threshold)
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl QueryLogger {
+    pub fn add_hook(&self, hook: QueryHook) {
+        if let Ok(mut hooks) = self.hooks.write() {
+            hooks.push(hook);
+        }
+    }
+
+    pub fn set_slow_query_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_query_threshold = threshold;
+    }
+
+    pub fn slow_query_threshold(&self) -> Option<Duration> {
+        self.slow_query_threshold
+    }
+
+    pub fn set_stats(&mut self, stats: Option<QueryStats>) {
+        self.stats = stats;
+    }
+
+    pub fn stats(&self) -> Option<&QueryStats> {
+        self.stats.as_ref()
+    }
+
+    /// True when something is interested in the calls
+    pub fn is_active(&self) -> bool {
+        self.slow_query_threshold.is_some()
+            || self.stats.is_some()
+            || self.hooks.read().is_ok_and(|hooks| !hooks.is_empty())
+    }
+
+    pub fn log(&self, entry: QueryLogEntry) {
+        if let Some(stats) = &self.stats {
+            stats.record(&entry);
+        }
+
+        if let Some(threshold) = self.slow_query_threshold
+            && entry.duration >= threshold
+        {
+            log::warn!(
+                target: LOG_TARGET,
+                "slow query, {} took {} ms on the {:?} connection: {}",
+                entry.operation,
+                entry.duration.as_millis(),
+                entry.client_type,
+                entry.sql.join(";\n")
+            );
+        }
+
+        let hooks = self
+            .hooks
+            .read()
+            .map(|hooks| hooks.clone())
+            .unwrap_or_default();
+        for hook in hooks {
+            hook(&entry);
+        }
+    }
+}
+
+/// Wraps a schema manager and logs every call made on it
+pub struct LoggedSchemaManager {
+    inner: Box<dyn SchemaManagerTrait + Send>,
+    recorder: StatementRecorder,
+    client_type: ClientType,
+    kind: DatabaseKind,
+    logger: QueryLogger,
+}
+
+impl LoggedSchemaManager {
+    pub fn new(
+        mut inner: Box<dyn SchemaManagerTrait + Send>,
+        client_type: ClientType,
+        kind: DatabaseKind,
+        logger: QueryLogger,
+    ) -> Self {
+        let recorder = StatementRecorder::default();
+        inner.record_statements(recorder.clone());
+
+        Self {
+            inner,
+            recorder,
+            client_type,
+            kind,
+            logger,
+        }
+    }
+
+    fn log(&self, operation: &'static str, started: Instant) {
+        let (sql, params) = self.recorder.take();
+        self.logger.log(QueryLogEntry {
+            operation,
+            sql,
+            params,
+            duration: started.elapsed(),
+            client_type: self.client_type,
+            kind: self.kind.clone(),
+        });
+    }
+}
+
+#[async_trait]
+impl SchemaManagerTrait for LoggedSchemaManager {
+    async fn apply(&mut self, table: TableBlueprint) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.apply(table).await;
+        self.log("apply", started);
+        result
+    }
+
+    async fn execute(&mut self, query_builder: QueryBuilder) -> Result<ExecuteResult> {
+        let started = Instant::now();
+        let result = self.inner.execute(query_builder).await;
+        self.log("execute", started);
+        result
+    }
+
+    async fn begin(&mut self) -> Result<Box<dyn SchemaManagerTrait>, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.begin().await;
+        self.log("begin", started);
+
+        Ok(Box::new(Self::new(
+            result?,
+            self.client_type,
+            self.kind.clone(),
+            self.logger.clone(),
+        )))
+    }
+
+    async fn commit(&mut self) -> Result<(), anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.commit().await;
+        self.log("commit", started);
+        result
+    }
+
+    async fn rollback(&mut self) -> Result<(), anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.rollback().await;
+        self.log("rollback", started);
+        result
+    }
+
+    async fn fetch_all(
+        &mut self,
+        query_builder: &QueryBuilder,
+    ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.fetch_all(query_builder).await;
+        self.log("fetch_all", started);
+        result
+    }
+
+    async fn stream_result(
+        &mut self,
+        query_builder: &QueryBuilder,
+        sender: tokio::sync::mpsc::Sender<ColumnAndValue>,
+    ) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.stream_result(query_builder, sender).await;
+        self.log("stream_result", started);
+        result
+    }
+
+    async fn fetch_one(
+        &mut self,
+        query_builder: &QueryBuilder,
+    ) -> Result<Option<ColumnAndValue>, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.fetch_one(query_builder).await;
+        self.log("fetch_one", started);
+        result
+    }
+
+    async fn has_table(&mut self, name: &str) -> Result<bool, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.has_table(name).await;
+        self.log("has_table", started);
+        result
+    }
+
+    async fn tables(&mut self) -> Result<Vec<String>> {
+        let started = Instant::now();
+        let result = self.inner.tables().await;
+        self.log("tables", started);
+        result
+    }
+
+    async fn describe_table(&mut self, name: &str) -> Result<Option<TableDescription>> {
+        let started = Instant::now();
+        let result = self.inner.describe_table(name).await;
+        self.log("describe_table", started);
+        result
+    }
+
+    async fn database_size(&mut self) -> Result<Option<u64>> {
+        let started = Instant::now();
+        let result = self.inner.database_size().await;
+        self.log("database_size", started);
+        result
+    }
+
+    async fn drop_table(&mut self, name: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.drop_table(name).await;
+        self.log("drop_table", started);
+        result
+    }
+
+    async fn rename_table(&mut self, old: &str, new: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.rename_table(old, new).await;
+        self.log("rename_table", started);
+        result
+    }
+
+    async fn drop_column(&mut self, table: &str, column: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.drop_column(table, column).await;
+        self.log("drop_column", started);
+        result
+    }
+
+    async fn rename_column(&mut self, table: &str, old: &str, new: &str) -> Result<()> {
+        let started = Instant::now();
+        let result = self.inner.rename_column(table, old, new).await;
+        self.log("rename_column", started);
+        result
+    }
+
+    async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.raw_insert(sql).await;
+        self.log("raw_insert", started);
+        result
+    }
+
+    async fn raw_update(
+        &mut self,
+        sql: &str,
+        params: Vec<FieldValue>,
+    ) -> Result<u64, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.raw_update(sql, params).await;
+        self.log("raw_update", started);
+        result
+    }
+
+    async fn raw_delete(
+        &mut self,
+        sql: &str,
+        values: Vec<FieldValue>,
+    ) -> Result<u64, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.raw_delete(sql, values).await;
+        self.log("raw_delete", started);
+        result
+    }
+
+    async fn raw_select(
+        &mut self,
+        sql: &str,
+        params: Vec<FieldValue>,
+    ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.raw_select(sql, params).await;
+        self.log("raw_select", started);
+        result
+    }
+
+    async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        let started = Instant::now();
+        let result = self.inner.raw_statement(sql).await;
+        self.log("raw_statement", started);
+        result
+    }
+
+    fn pretend(&mut self, log: PretendLog) {
+        self.inner.pretend(log);
+    }
+
+    fn record_statements(&mut self, recorder: StatementRecorder) {
+        self.inner.record_statements(recorder);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(sql: &str) -> QueryLogEntry {
+        QueryLogEntry {
+            operation: "fetch_all",
+            sql: vec![sql.to_string()],
+            params: vec![FieldValue::I64(1)],
+            duration: Duration::from_millis(3),
+            client_type: ClientType::Read,
+            kind: "sqlite".into(),
+        }
+    }
+
+    #[test]
+    fn test_query_stats() {
+        let stats = QueryStats::with_n_plus_one_detection(3);
+        let mut logger = QueryLogger::default();
+        assert!(!logger.is_active());
+        logger.set_stats(Some(stats.clone()));
+        assert!(logger.is_active());
+
+        for _ in 0..3 {
+            logger.log(entry("SELECT * FROM books WHERE author_id = ?"));
+        }
+        logger.log(entry("SELECT * FROM authors"));
+
+        assert_eq!(stats.count(), 4);
+        assert_eq!(
+            stats.repeated(),
+            vec![("SELECT * FROM books WHERE author_id = ?".to_string(), 3)]
+        );
+    }
+}
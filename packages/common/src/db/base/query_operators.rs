@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::full_text::FullTextMatch;
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Operator {
     Equal,
@@ -19,9 +21,29 @@ pub enum Operator {
     In,
     NotIn,
     Clause,
+    Exists,
+    NotExists,
+    /// Compares the value at a JSON path of the column, e.g. `$.settings.theme`
+    Json(String, Box<Operator>),
+    /// The JSON array at the path contains the value
+    JsonContains(String),
+    /// Compares the number of items in the JSON array at the path
+    JsonLength(String, Box<Operator>),
+    /// A full-text search, the term is bound by the connector
+    Match(FullTextMatch),
 }
 
 impl Operator {
+    /// The placeholder holds a list of values
+    pub fn is_list(&self) -> bool {
+        match self {
+            Self::In | Self::NotIn => true,
+            Self::Json(_, operator) | Self::JsonLength(_, operator) => operator.is_list(),
+            _ => false,
+        }
+    }
+
+    /// The JSON and full-text operators are rendered by each connector
     pub fn as_clause(&self, column: &str, placeholder: &str) -> String {
         match &self {
             Self::Equal => format!("{column} = {placeholder}"),
@@ -41,6 +63,11 @@ impl Operator {
             Self::In => format!("{column} IN ({placeholder})"),
             Self::NotIn => format!("{column} NOT IN ({placeholder})"),
             Self::Clause => format!("({placeholder})"),
+            Self::Exists => format!("EXISTS ({placeholder})"),
+            Self::NotExists => format!("NOT EXISTS ({placeholder})"),
+            Self::Json(..) | Self::JsonContains(_) | Self::JsonLength(..) | Self::Match(_) => {
+                unreachable!("{self:?} is rendered by the connector")
+            }
         }
     }
 }
@@ -0,0 +1,685 @@
+use std::{
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    connection::ConnectionPoolTrait,
+    introspection::TableDescription,
+    query::QueryBuilder,
+    query_log::StatementRecorder,
+    schema::{DatabaseKind, ExecuteResult, PretendLog, SchemaManagerTrait},
+    table::TableBlueprint,
+};
+use crate::db::{field_values::FieldValue, types::ColumnAndValue};
+
+const LOG_TARGET: &str = "dirtybase_db";
+
+/// How the next read replica is picked
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicaBalance {
+    #[default]
+    RoundRobin,
+    /// The replica with the fewest connections in use
+    LeastConnections,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplicaOptions {
+    pub balance: ReplicaBalance,
+    /// Consecutive connection errors before a replica is taken out
+    pub eject_after: u32,
+    /// How long an ejected replica stays out when it is not health checked
+    pub eject_duration: Duration,
+    /// Replicas further behind their primary are not read from
+    pub max_lag: Option<Duration>,
+    pub health_check_interval: Duration,
+}
+
+impl Default for ReplicaOptions {
+    fn default() -> Self {
+        Self {
+            balance: ReplicaBalance::RoundRobin,
+            eject_after: 3,
+            eject_duration: Duration::from_secs(30),
+            max_lag: None,
+            health_check_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplicaHealth {
+    failures: AtomicU32,
+    ejected_until: AtomicI64,
+    lagging: AtomicBool,
+}
+
+impl ReplicaHealth {
+    fn is_available(&self) -> bool {
+        !self.lagging.load(Ordering::Relaxed) && self.ejected_until.load(Ordering::Relaxed) <= now()
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Returns true when the replica has just been ejected
+    fn record_failure(&self, options: &ReplicaOptions) -> bool {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= options.eject_after.max(1) {
+            self.eject(options);
+            return true;
+        }
+        false
+    }
+
+    fn eject(&self, options: &ReplicaOptions) {
+        self.failures.store(0, Ordering::Relaxed);
+        self.ejected_until.store(
+            now() + options.eject_duration.as_secs() as i64,
+            Ordering::Relaxed,
+        );
+    }
+
+    fn readmit(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        self.ejected_until.store(0, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct Replica {
+    pool: Box<dyn ConnectionPoolTrait>,
+    health: ReplicaHealth,
+}
+
+/// The read pools of a connection set. Replicas that fail or lag behind are skipped,
+/// the manager falls back to the writer when none is left
+#[derive(Debug)]
+pub struct ReplicaPool {
+    replicas: Arc<Vec<Replica>>,
+    options: ReplicaOptions,
+    next: AtomicUsize,
+    closed: Arc<AtomicBool>,
+}
+
+impl ReplicaPool {
+    pub fn new(pools: Vec<Box<dyn ConnectionPoolTrait>>, options: ReplicaOptions) -> Self {
+        let replicas = pools
+            .into_iter()
+            .map(|pool| Replica {
+                pool,
+                health: ReplicaHealth::default(),
+            })
+            .collect();
+
+        Self {
+            replicas: Arc::new(replicas),
+            options,
+            next: AtomicUsize::new(0),
+            closed: Arc::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.replicas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replicas.is_empty()
+    }
+
+    /// Index of the replica the next read goes to
+    pub fn pick(&self) -> Option<usize> {
+        let available = (0..self.replicas.len())
+            .filter(|index| self.replicas[*index].health.is_available())
+            .collect::<Vec<_>>();
+        if available.is_empty() {
+            return None;
+        }
+
+        match self.options.balance {
+            ReplicaBalance::RoundRobin => {
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                Some(available[next % available.len()])
+            }
+            ReplicaBalance::LeastConnections => available
+                .into_iter()
+                .min_by_key(|index| self.replicas[*index].pool.in_use()),
+        }
+    }
+
+    fn schema_manager_for(
+        &self,
+        index: Option<usize>,
+    ) -> Box<dyn SchemaManagerTrait + Send + Sync> {
+        Box::new(ReplicaSchemaManager {
+            inner: index.map(|index| self.replicas[index].pool.schema_manger()),
+            replicas: self.replicas.clone(),
+            index,
+            options: self.options.clone(),
+        })
+    }
+
+    /// Takes the replica out until a health check finds it reachable again
+    pub fn eject(&self, index: usize) {
+        if let Some(replica) = self.replicas.get(index) {
+            replica.health.eject(&self.options);
+        }
+    }
+
+    /// Pings every replica and measures its lag. Ejected replicas that answer are put back
+    pub async fn check_health(&self) {
+        check_replicas(&self.replicas, &self.options).await;
+    }
+
+    /// Checks the replicas in the background until the pool is closed or dropped
+    pub fn start_health_checks(&self) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let replicas = Arc::downgrade(&self.replicas);
+        let closed = self.closed.clone();
+        let options = self.options.clone();
+
+        handle.spawn(async move {
+            loop {
+                tokio::time::sleep(options.health_check_interval).await;
+                let Some(replicas) = Weak::upgrade(&replicas) else {
+                    break;
+                };
+                if closed.load(Ordering::Relaxed) {
+                    break;
+                }
+                check_replicas(&replicas, &options).await;
+            }
+        });
+    }
+}
+
+async fn check_replicas(replicas: &[Replica], options: &ReplicaOptions) {
+    for (index, replica) in replicas.iter().enumerate() {
+        if let Err(e) = replica.pool.ping().await {
+            if replica.health.is_available() {
+                log::warn!(target: LOG_TARGET, "read replica {} is not reachable: {}", index, e);
+                replica.health.record_failure(options);
+            }
+            continue;
+        }
+        if !replica.health.is_available() && !replica.health.lagging.load(Ordering::Relaxed) {
+            log::info!(target: LOG_TARGET, "read replica {} is back", index);
+        }
+        replica.health.readmit();
+
+        let Some(max_lag) = options.max_lag else {
+            continue;
+        };
+        let lagging = match replica.pool.replica_lag().await {
+            Ok(Some(lag)) => lag > max_lag,
+            Ok(None) => false,
+            Err(e) => {
+                log::warn!(target: LOG_TARGET, "could not get the lag of read replica {}: {}", index, e);
+                true
+            }
+        };
+        if lagging && !replica.health.lagging.load(Ordering::Relaxed) {
+            log::warn!(target: LOG_TARGET, "read replica {} is lagging behind", index);
+        }
+        replica.health.lagging.store(lagging, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl ConnectionPoolTrait for ReplicaPool {
+    /// The queries fail when no replica is available, the manager checks
+    /// `is_available` first and reads from the writer instead
+    fn schema_manger(&self) -> Box<dyn SchemaManagerTrait + Send + Sync> {
+        self.schema_manager_for(self.pick())
+    }
+
+    async fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        for replica in self.replicas.iter() {
+            replica.pool.close().await;
+        }
+    }
+
+    fn id(&self) -> DatabaseKind {
+        self.replicas
+            .first()
+            .map(|replica| replica.pool.id())
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        let mut result = Err(anyhow::anyhow!("there are no read replicas"));
+        for replica in self.replicas.iter() {
+            result = replica.pool.ping().await;
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    fn in_use(&self) -> u32 {
+        self.replicas
+            .iter()
+            .map(|replica| replica.pool.in_use())
+            .sum()
+    }
+
+    fn is_available(&self) -> bool {
+        self.replicas
+            .iter()
+            .any(|replica| replica.health.is_available())
+    }
+
+    fn available_schema_manger(&self) -> Option<Box<dyn SchemaManagerTrait + Send + Sync>> {
+        self.pick()
+            .map(|index| self.schema_manager_for(Some(index)))
+    }
+}
+
+/// Reports the connection errors of a replica's schema manager
+struct ReplicaSchemaManager {
+    inner: Option<Box<dyn SchemaManagerTrait + Send + Sync>>,
+    replicas: Arc<Vec<Replica>>,
+    index: Option<usize>,
+    options: ReplicaOptions,
+}
+
+impl ReplicaSchemaManager {
+    fn inner(&mut self) -> Result<&mut Box<dyn SchemaManagerTrait + Send + Sync>> {
+        self.inner
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("no read replica is available"))
+    }
+
+    /// An error counts against the replica when it can not be reached anymore
+    async fn observe<T>(&self, result: Result<T>) -> Result<T> {
+        let Some(index) = self.index else {
+            return result;
+        };
+        let replica = &self.replicas[index];
+        match &result {
+            Ok(_) => replica.health.record_success(),
+            Err(_) => {
+                if let Err(e) = replica.pool.ping().await
+                    && replica.health.record_failure(&self.options)
+                {
+                    log::warn!(target: LOG_TARGET, "read replica {} ejected: {}", index, e);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl SchemaManagerTrait for ReplicaSchemaManager {
+    async fn apply(&mut self, table: TableBlueprint) -> Result<()> {
+        self.inner()?.apply(table).await
+    }
+
+    async fn execute(&mut self, query_builder: QueryBuilder) -> Result<ExecuteResult> {
+        self.inner()?.execute(query_builder).await
+    }
+
+    async fn begin(&mut self) -> Result<Box<dyn SchemaManagerTrait>, anyhow::Error> {
+        self.inner()?.begin().await
+    }
+
+    async fn commit(&mut self) -> Result<(), anyhow::Error> {
+        self.inner()?.commit().await
+    }
+
+    async fn rollback(&mut self) -> Result<(), anyhow::Error> {
+        self.inner()?.rollback().await
+    }
+
+    async fn fetch_all(
+        &mut self,
+        query_builder: &QueryBuilder,
+    ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+        let result = self.inner()?.fetch_all(query_builder).await;
+        self.observe(result).await
+    }
+
+    async fn stream_result(
+        &mut self,
+        query_builder: &QueryBuilder,
+        sender: tokio::sync::mpsc::Sender<ColumnAndValue>,
+    ) -> Result<()> {
+        let result = self.inner()?.stream_result(query_builder, sender).await;
+        self.observe(result).await
+    }
+
+    async fn fetch_one(
+        &mut self,
+        query_builder: &QueryBuilder,
+    ) -> Result<Option<ColumnAndValue>, anyhow::Error> {
+        let result = self.inner()?.fetch_one(query_builder).await;
+        self.observe(result).await
+    }
+
+    async fn has_table(&mut self, name: &str) -> Result<bool, anyhow::Error> {
+        let result = self.inner()?.has_table(name).await;
+        self.observe(result).await
+    }
+
+    async fn tables(&mut self) -> Result<Vec<String>> {
+        let result = self.inner()?.tables().await;
+        self.observe(result).await
+    }
+
+    async fn describe_table(&mut self, name: &str) -> Result<Option<TableDescription>> {
+        let result = self.inner()?.describe_table(name).await;
+        self.observe(result).await
+    }
+
+    async fn database_size(&mut self) -> Result<Option<u64>> {
+        let result = self.inner()?.database_size().await;
+        self.observe(result).await
+    }
+
+    async fn drop_table(&mut self, name: &str) -> Result<()> {
+        self.inner()?.drop_table(name).await
+    }
+
+    async fn rename_table(&mut self, old: &str, new: &str) -> Result<()> {
+        self.inner()?.rename_table(old, new).await
+    }
+
+    async fn drop_column(&mut self, table: &str, column: &str) -> Result<()> {
+        self.inner()?.drop_column(table, column).await
+    }
+
+    async fn rename_column(&mut self, table: &str, old: &str, new: &str) -> Result<()> {
+        self.inner()?.rename_column(table, old, new).await
+    }
+
+    async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        self.inner()?.raw_insert(sql).await
+    }
+
+    async fn raw_update(
+        &mut self,
+        sql: &str,
+        params: Vec<FieldValue>,
+    ) -> Result<u64, anyhow::Error> {
+        self.inner()?.raw_update(sql, params).await
+    }
+
+    async fn raw_delete(
+        &mut self,
+        sql: &str,
+        values: Vec<FieldValue>,
+    ) -> Result<u64, anyhow::Error> {
+        self.inner()?.raw_delete(sql, values).await
+    }
+
+    async fn raw_select(
+        &mut self,
+        sql: &str,
+        params: Vec<FieldValue>,
+    ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+        let result = self.inner()?.raw_select(sql, params).await;
+        self.observe(result).await
+    }
+
+    async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        self.inner()?.raw_statement(sql).await
+    }
+
+    fn pretend(&mut self, log: PretendLog) {
+        if let Some(inner) = &mut self.inner {
+            inner.pretend(log);
+        }
+    }
+
+    fn record_statements(&mut self, recorder: StatementRecorder) {
+        if let Some(inner) = &mut self.inner {
+            inner.record_statements(recorder);
+        }
+    }
+}
+
+fn now() -> i64 {
+    std::time::UNIX_EPOCH.elapsed().unwrap().as_secs() as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct FakePool {
+        down: Arc<AtomicBool>,
+        in_use: u32,
+        lag: u64,
+    }
+
+    /// Answers every query with an empty result
+    struct StubSchemaManager;
+
+    #[async_trait]
+    impl SchemaManagerTrait for StubSchemaManager {
+        async fn apply(&mut self, _table: TableBlueprint) -> Result<()> {
+            Ok(())
+        }
+
+        async fn execute(&mut self, _query_builder: QueryBuilder) -> Result<ExecuteResult> {
+            Ok(ExecuteResult::default())
+        }
+
+        async fn begin(&mut self) -> Result<Box<dyn SchemaManagerTrait>, anyhow::Error> {
+            Ok(Box::new(StubSchemaManager))
+        }
+
+        async fn commit(&mut self) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        async fn rollback(&mut self) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        async fn fetch_all(
+            &mut self,
+            _query_builder: &QueryBuilder,
+        ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+            Ok(Vec::new())
+        }
+
+        async fn stream_result(
+            &mut self,
+            _query_builder: &QueryBuilder,
+            _sender: tokio::sync::mpsc::Sender<ColumnAndValue>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn fetch_one(
+            &mut self,
+            _query_builder: &QueryBuilder,
+        ) -> Result<Option<ColumnAndValue>, anyhow::Error> {
+            Ok(None)
+        }
+
+        async fn has_table(&mut self, _name: &str) -> Result<bool, anyhow::Error> {
+            Ok(false)
+        }
+
+        async fn drop_table(&mut self, _name: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn raw_insert(&mut self, _sql: &str) -> Result<bool, anyhow::Error> {
+            Ok(true)
+        }
+
+        async fn raw_update(
+            &mut self,
+            _sql: &str,
+            _params: Vec<FieldValue>,
+        ) -> Result<u64, anyhow::Error> {
+            Ok(0)
+        }
+
+        async fn raw_delete(
+            &mut self,
+            _sql: &str,
+            _values: Vec<FieldValue>,
+        ) -> Result<u64, anyhow::Error> {
+            Ok(0)
+        }
+
+        async fn raw_select(
+            &mut self,
+            _sql: &str,
+            _params: Vec<FieldValue>,
+        ) -> Result<Vec<ColumnAndValue>, anyhow::Error> {
+            Ok(Vec::new())
+        }
+
+        async fn raw_statement(&mut self, _sql: &str) -> Result<bool, anyhow::Error> {
+            Ok(true)
+        }
+    }
+
+    #[async_trait]
+    impl ConnectionPoolTrait for FakePool {
+        fn schema_manger(&self) -> Box<dyn SchemaManagerTrait + Send + Sync> {
+            Box::new(StubSchemaManager)
+        }
+
+        async fn close(&self) {}
+
+        fn id(&self) -> DatabaseKind {
+            "fake".into()
+        }
+
+        async fn ping(&self) -> Result<(), anyhow::Error> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(anyhow::anyhow!("connection refused"));
+            }
+            Ok(())
+        }
+
+        async fn replica_lag(&self) -> Result<Option<Duration>, anyhow::Error> {
+            Ok(Some(Duration::from_secs(self.lag)))
+        }
+
+        fn in_use(&self) -> u32 {
+            self.in_use
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replica_pool() {
+        let down = Arc::new(AtomicBool::new(false));
+        let pool = ReplicaPool::new(
+            vec![
+                Box::new(FakePool {
+                    down: down.clone(),
+                    in_use: 4,
+                    ..FakePool::default()
+                }),
+                Box::new(FakePool {
+                    in_use: 1,
+                    lag: 60,
+                    ..FakePool::default()
+                }),
+            ],
+            ReplicaOptions {
+                eject_after: 2,
+                max_lag: Some(Duration::from_secs(10)),
+                ..ReplicaOptions::default()
+            },
+        );
+        assert_eq!(
+            (0..4).filter_map(|_| pool.pick()).collect::<Vec<_>>(),
+            vec![0, 1, 0, 1]
+        );
+
+        // the second replica is too far behind
+        pool.check_health().await;
+        assert_eq!(pool.pick(), Some(0));
+
+        down.store(true, Ordering::Relaxed);
+        pool.check_health().await;
+        assert_eq!(pool.pick(), Some(0));
+        pool.check_health().await;
+        assert_eq!(pool.pick(), None);
+        assert!(!pool.is_available());
+
+        down.store(false, Ordering::Relaxed);
+        pool.check_health().await;
+        assert!(pool.is_available());
+
+        let pool = ReplicaPool::new(
+            vec![
+                Box::new(FakePool {
+                    in_use: 4,
+                    ..FakePool::default()
+                }),
+                Box::new(FakePool {
+                    in_use: 1,
+                    ..FakePool::default()
+                }),
+            ],
+            ReplicaOptions {
+                balance: ReplicaBalance::LeastConnections,
+                ..ReplicaOptions::default()
+            },
+        );
+        assert_eq!(pool.pick(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_replicas() {
+        let query = QueryBuilder::new_query("users");
+        let pool = ReplicaPool::new(Vec::new(), ReplicaOptions::default());
+        assert!(!pool.is_available());
+        assert!(pool.schema_manger().fetch_all(&query).await.is_err());
+
+        let pool = ReplicaPool::new(
+            vec![Box::new(FakePool::default())],
+            ReplicaOptions::default(),
+        );
+        pool.eject(0);
+        assert_eq!(pool.pick(), None);
+        assert!(pool.schema_manger().fetch_all(&query).await.is_err());
+
+        // a replica that answers again is put back
+        pool.check_health().await;
+        assert_eq!(pool.pick(), Some(0));
+        assert!(pool.schema_manger().fetch_all(&query).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_available_schema_manager() {
+        let query = QueryBuilder::new_query("users");
+        let pool = ReplicaPool::new(
+            vec![Box::new(FakePool::default())],
+            ReplicaOptions::default(),
+        );
+
+        let mut manager = pool.available_schema_manger().unwrap();
+        assert!(manager.fetch_all(&query).await.is_ok());
+
+        // no replica to pick, the caller uses the writer
+        pool.eject(0);
+        assert!(pool.available_schema_manger().is_none());
+    }
+}
@@ -1,5 +1,7 @@
 use super::{
+    introspection::TableDescription,
     query::{QueryAction, QueryBuilder},
+    query_log::StatementRecorder,
     table::TableBlueprint,
 };
 use crate::db::{
@@ -9,7 +11,7 @@ use crate::db::{
 use crate::db::{
     base::{
         cursor_builder::{CursorBuilder, CursorResult},
-        order_by_builder::Direction,
+        page_result::PageResult,
     },
     field_values::FieldValue,
 };
@@ -17,7 +19,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use std::{
     fmt::{Debug, Display},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 #[derive(
@@ -85,6 +87,59 @@ pub enum ClientType {
     Write,
 }
 
+/// The outcome of a statement that writes to the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExecuteResult {
+    rows_affected: u64,
+    last_insert_id: Option<u64>,
+}
+
+impl ExecuteResult {
+    pub fn new(rows_affected: u64, last_insert_id: Option<u64>) -> Self {
+        Self {
+            rows_affected,
+            last_insert_id,
+        }
+    }
+
+    /// Number of rows inserted, updated or deleted
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+
+    /// The auto increment ID generated by the insert.
+    ///
+    /// For a single row insert it is the row's ID on every database. For a
+    /// multi-row insert it depends on the database:
+    /// - sqlite: the ID of the last inserted row
+    /// - mysql and mariadb: the ID of the first inserted row
+    /// - postgres: `None`
+    pub fn last_insert_id(&self) -> Option<u64> {
+        self.last_insert_id
+    }
+
+    /// Returns true when the statement did not change any row
+    pub fn is_unchanged(&self) -> bool {
+        self.rows_affected == 0
+    }
+}
+
+/// Collects the statements that a manager in pretend mode would have written
+#[derive(Debug, Clone, Default)]
+pub struct PretendLog(Arc<Mutex<Vec<String>>>);
+
+impl PretendLog {
+    pub fn record<S: ToString>(&self, sql: S) {
+        if let Ok(mut list) = self.0.lock() {
+            list.push(sql.to_string());
+        }
+    }
+
+    pub fn statements(&self) -> Vec<String> {
+        self.0.lock().map(|list| list.clone()).unwrap_or_default()
+    }
+}
+
 #[async_trait]
 pub trait RelationalDbTrait: SchemaManagerTrait {
     fn kind(&self) -> DatabaseKind;
@@ -95,7 +150,7 @@ pub trait SchemaManagerTrait: Send + Sync {
     // commit schema changes
     async fn apply(&mut self, table: TableBlueprint) -> Result<()>;
 
-    async fn execute(&mut self, query_builder: QueryBuilder) -> Result<()>;
+    async fn execute(&mut self, query_builder: QueryBuilder) -> Result<ExecuteResult>;
 
     async fn begin(&mut self) -> Result<Box<dyn SchemaManagerTrait>, anyhow::Error>;
 
@@ -157,6 +212,23 @@ pub trait SchemaManagerTrait: Send + Sync {
     // Checks if a table exist in the database
     async fn has_table(&mut self, name: &str) -> Result<bool, anyhow::Error>;
 
+    /// Names of the tables in the database
+    async fn tables(&mut self) -> Result<Vec<String>> {
+        Err(anyhow::anyhow!("listing the tables is unsupported"))
+    }
+
+    /// Describes a table as it exists in the database. `None` when the table does not exist
+    async fn describe_table(&mut self, _name: &str) -> Result<Option<TableDescription>> {
+        Err(anyhow::anyhow!("describing a table is unsupported"))
+    }
+
+    /// Size of the database in bytes, when the database reports it
+    async fn database_size(&mut self) -> Result<Option<u64>> {
+        Err(anyhow::anyhow!(
+            "reporting the database size is unsupported"
+        ))
+    }
+
     async fn drop_table(&mut self, name: &str) -> Result<()>;
 
     async fn rename_table(&mut self, old: &str, new: &str) -> Result<()> {
@@ -164,7 +236,8 @@ pub trait SchemaManagerTrait: Send + Sync {
             old,
             QueryAction::RenameTable(new.to_string()),
         ))
-        .await
+        .await?;
+        Ok(())
     }
 
     async fn drop_column(&mut self, table: &str, column: &str) -> Result<()> {
@@ -172,7 +245,8 @@ pub trait SchemaManagerTrait: Send + Sync {
             table,
             QueryAction::DropColumn(column.to_string()),
         ))
-        .await
+        .await?;
+        Ok(())
     }
 
     async fn rename_column(&mut self, table: &str, old: &str, new: &str) -> Result<()> {
@@ -183,7 +257,8 @@ pub trait SchemaManagerTrait: Send + Sync {
                 new: new.to_string(),
             },
         ))
-        .await
+        .await?;
+        Ok(())
     }
 
     async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error>;
@@ -207,6 +282,13 @@ pub trait SchemaManagerTrait: Send + Sync {
     ) -> Result<Vec<ColumnAndValue>, anyhow::Error>;
 
     async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error>;
+
+    /// Records the write statements in the log instead of running them.
+    /// Does nothing by default, a manager that does not implement it runs the statements
+    fn pretend(&mut self, _log: PretendLog) {}
+
+    /// Reports the statements that run and the values bound to them, for the query log
+    fn record_statements(&mut self, _recorder: StatementRecorder) {}
 }
 
 pub struct SchemaQuery {
@@ -226,7 +308,7 @@ impl SchemaQuery {
         let results = self
             .manager
             .read_connection()
-            .await
+            .await?
             .fetch_all(&self.query_builder)
             .await;
         if let Ok(records) = results {
@@ -252,7 +334,7 @@ impl SchemaQuery {
         let result = self
             .manager
             .read_connection()
-            .await
+            .await?
             .fetch_one(&self.query_builder)
             .await;
 
@@ -298,26 +380,14 @@ impl SchemaQuery {
         Self: Sized,
     {
         let mut cursor_two = cursor.clone();
-        if let Some(field_value) = cursor.last().cloned() {
-            self.query_builder.and_where(|q| {
-                if let Some((col, dir)) = cursor.order().orders.first().cloned() {
-                    if dir == Direction::ASC {
-                        q.gt(col, field_value);
-                    } else {
-                        q.le(col, field_value);
-                    }
-                }
-            });
-        }
+        cursor.apply_to(&mut self.query_builder);
         self.query_builder.cursor(cursor);
 
         let result = self.fetch_all().await;
-        if let Ok(rows) = &result {
-            if let Some(last) = rows.last()
-                && let Some(value) = last.get(cursor_two.column()).cloned()
-            {
-                cursor_two.set_last(value);
-            }
+        if let Ok(rows) = &result
+            && let Some(last) = rows.last()
+        {
+            cursor_two.set_last_row(last);
         }
 
         CursorResult::new(cursor_two, result)
@@ -329,25 +399,13 @@ impl SchemaQuery {
         T: FromColumnAndValue + Debug,
     {
         let mut cursor_two = cursor.clone();
-        if let Some(field_value) = cursor.last().cloned() {
-            self.query_builder.and_where(|q| {
-                if let Some((col, dir)) = cursor.order().orders.first().cloned() {
-                    if dir == Direction::ASC {
-                        q.gt(col, field_value);
-                    } else {
-                        q.le(col, field_value);
-                    }
-                }
-            });
-        }
+        cursor.apply_to(&mut self.query_builder);
         self.query_builder.cursor(cursor);
 
         let result = self.fetch_all().await;
         let data = if let Ok(rows) = result {
-            if let Some(last) = rows.last()
-                && let Some(value) = last.get(cursor_two.column()).cloned()
-            {
-                cursor_two.set_last(value);
+            if let Some(last) = rows.last() {
+                cursor_two.set_last_row(last);
             }
             Ok(rows
                 .into_iter()
@@ -360,6 +418,52 @@ impl SchemaQuery {
         CursorResult::new(cursor_two, data)
     }
 
+    /// Counts the rows the query returns. The order, limit and offset are ignored
+    pub async fn count(self) -> Result<u64, anyhow::Error> {
+        let query = self.query_builder.count_query("_total");
+        let row = Self::new(query, self.manager).fetch_one().await?;
+
+        Ok(row
+            .and_then(|r| r.get("_total").map(i64::from))
+            .unwrap_or_default()
+            .max(0) as u64)
+    }
+
+    /// Fetches a page of rows along with the total number of rows.
+    /// `page` starts at 1
+    pub async fn paginate(
+        mut self,
+        page: usize,
+        per_page: usize,
+    ) -> Result<PageResult<StructuredColumnAndValue>, anyhow::Error> {
+        let total = Self::new(self.query_builder.clone(), self.manager.clone())
+            .count()
+            .await?;
+
+        self.query_builder
+            .limit(per_page.max(1))
+            .offset(PageResult::<()>::offset_for(page, per_page));
+        let rows = self.fetch_all().await?;
+
+        Ok(PageResult::new(rows, total, page, per_page))
+    }
+
+    pub async fn paginate_to<T: FromColumnAndValue>(
+        self,
+        page: usize,
+        per_page: usize,
+    ) -> Result<PageResult<T>, anyhow::Error> {
+        let result = self.paginate(page, per_page).await?;
+        let total = result.total();
+        let rows = result
+            .into_data()
+            .into_iter()
+            .map(|row| T::from_column_value(row.fields()))
+            .collect::<Result<Vec<T>, _>>()?;
+
+        Ok(PageResult::new(rows, total, page, per_page))
+    }
+
     pub async fn fetch_all_to<T>(self) -> Result<Vec<T>, anyhow::Error>
     where
         Self: Sized,
@@ -380,12 +484,12 @@ impl SchemaQuery {
         let (sender, receiver) = tokio::sync::mpsc::channel::<ColumnAndValue>(100);
 
         tokio::spawn(async move {
-            _ = self
-                .manager
-                .read_connection()
-                .await
-                .stream_result(&self.query_builder, sender)
-                .await;
+            match self.manager.read_connection().await {
+                Ok(mut connection) => {
+                    _ = connection.stream_result(&self.query_builder, sender).await;
+                }
+                Err(e) => tracing::error!("could not stream the query result: {}", e),
+            }
         });
 
         tokio_stream::wrappers::ReceiverStream::new(receiver)
@@ -418,12 +522,14 @@ impl SchemaQuery {
         });
 
         tokio::spawn(async move {
-            _ = self
-                .manager
-                .read_connection()
-                .await
-                .stream_result(&self.query_builder, inner_sender)
-                .await;
+            match self.manager.read_connection().await {
+                Ok(mut connection) => {
+                    _ = connection
+                        .stream_result(&self.query_builder, inner_sender)
+                        .await;
+                }
+                Err(e) => tracing::error!("could not stream the query result: {}", e),
+            }
         });
 
         tokio_stream::wrappers::ReceiverStream::new(outer_receiver)
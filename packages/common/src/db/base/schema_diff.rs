@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    column::{ColumnBlueprint, ColumnType},
+    index::IndexType,
+    introspection::TableDescription,
+    table::TableBlueprint,
+};
+
+/// The changes needed to bring a table in line with the blueprint of its entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub table: String,
+    /// The table does not exist yet, `added` holds all its columns
+    pub is_new: bool,
+    pub added: Vec<ColumnBlueprint>,
+    /// Columns that only exist in the database
+    pub dropped: Vec<ColumnBlueprint>,
+    /// `(old name, new name)` pairs, guessed from the added and dropped columns
+    pub renamed: Vec<(String, String)>,
+    /// Columns whose type or nullability differ. There is no way to alter a column yet,
+    /// so these are reported only
+    pub changed: Vec<ColumnChange>,
+    pub added_indexes: Vec<IndexChange>,
+    pub dropped_indexes: Vec<IndexChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexChange {
+    /// Sorted column names
+    pub columns: Vec<String>,
+    pub is_unique: bool,
+    /// The name of the index in the database, blueprint indexes have none
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl IndexChange {
+    /// True when both cover the same columns, whatever their names are
+    pub fn same_as(&self, other: &Self) -> bool {
+        self.columns == other.columns && self.is_unique == other.is_unique
+    }
+}
+
+impl TableDiff {
+    /// Compares the blueprint of an entity with the table in the database.
+    ///
+    /// With `guess_renames`, a dropped and an added column of the same kind are treated
+    /// as a rename when neither of them could be paired with another column
+    pub fn between(
+        blueprint: &TableBlueprint,
+        live: Option<&TableDescription>,
+        guess_renames: bool,
+    ) -> Self {
+        let mut diff = Self {
+            table: blueprint.name.clone(),
+            ..Self::default()
+        };
+
+        let Some(live) = live else {
+            diff.is_new = true;
+            diff.added = blueprint.columns.clone();
+            diff.added_indexes = blueprint_indexes(blueprint, false);
+            return diff;
+        };
+        let live_blueprint = live.to_blueprint();
+
+        let mut added = blueprint
+            .columns
+            .iter()
+            .filter(|c| !live_blueprint.columns.iter().any(|l| l.name == c.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut dropped = live_blueprint
+            .columns
+            .iter()
+            .filter(|l| !blueprint.columns.iter().any(|c| c.name == l.name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let matches = |a: &ColumnBlueprint, b: &ColumnBlueprint| {
+            same_kind(&a.column_type, &b.column_type)
+                && is_nullable(a) == is_nullable(b)
+                && a.is_unique == b.is_unique
+                && a.relationship == b.relationship
+        };
+        let renamed = if !guess_renames {
+            Vec::new()
+        } else {
+            added
+                .iter()
+                .filter_map(|new| {
+                    let mut candidates = dropped.iter().filter(|old| matches(old, new));
+                    let old = candidates.next()?;
+                    let unique = candidates.next().is_none()
+                        && added.iter().filter(|other| matches(old, other)).count() == 1;
+                    unique.then(|| (old.name.clone(), new.name.clone()))
+                })
+                .collect::<Vec<_>>()
+        };
+        added.retain(|c| !renamed.iter().any(|(_, new)| *new == c.name));
+        dropped.retain(|c| !renamed.iter().any(|(old, _)| *old == c.name));
+
+        for column in &blueprint.columns {
+            let Some(current) = live.column(&column.name) else {
+                continue;
+            };
+            let current_type = if current.auto_increment {
+                ColumnType::AutoIncrementId
+            } else {
+                current.column_type.clone()
+            };
+            let nullable_changed =
+                !current.is_primary && current.is_nullable != is_nullable(column);
+            if !same_kind(&current_type, &column.column_type) || nullable_changed {
+                diff.changed.push(ColumnChange {
+                    name: column.name.clone(),
+                    from: describe(&current.data_type, current.is_nullable),
+                    to: describe(&format!("{:?}", column.column_type), is_nullable(column)),
+                });
+            }
+        }
+
+        // unique columns that are added carry their own constraint
+        let wanted = blueprint_indexes(blueprint, true)
+            .into_iter()
+            .filter(|index| {
+                !(index.is_unique
+                    && index.columns.len() == 1
+                    && added
+                        .iter()
+                        .any(|c| c.name == index.columns[0] && c.is_unique))
+            })
+            .collect::<Vec<_>>();
+        let existing = live
+            .indexes
+            .iter()
+            .filter(|index| !index.is_primary)
+            .map(|index| {
+                let mut columns = index.columns.clone();
+                columns.sort();
+                IndexChange {
+                    columns,
+                    is_unique: index.is_unique,
+                    name: Some(index.name.clone()),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        diff.added_indexes = wanted
+            .iter()
+            .filter(|index| !existing.iter().any(|e| e.same_as(index)))
+            .cloned()
+            .collect();
+        // indexes of dropped columns are dropped as well, SQLite can not drop an indexed column
+        diff.dropped_indexes = existing
+            .into_iter()
+            .filter(|index| !wanted.iter().any(|w| w.same_as(index)))
+            .collect();
+
+        diff.added = added;
+        diff.dropped = dropped;
+        diff.renamed = renamed;
+        diff
+    }
+
+    /// True when the table matches the blueprint
+    pub fn is_empty(&self) -> bool {
+        !self.is_new
+            && self.added.is_empty()
+            && self.dropped.is_empty()
+            && self.renamed.is_empty()
+            && self.changed.is_empty()
+            && self.added_indexes.is_empty()
+            && self.dropped_indexes.is_empty()
+    }
+}
+
+/// The indexes declared by a blueprint. Unique columns are included when `with_unique_columns` is set
+fn blueprint_indexes(blueprint: &TableBlueprint, with_unique_columns: bool) -> Vec<IndexChange> {
+    let mut list = Vec::new();
+    if with_unique_columns {
+        for column in blueprint
+            .columns
+            .iter()
+            .filter(|c| c.is_unique && !c.is_primary)
+        {
+            list.push(IndexChange {
+                columns: vec![column.name.clone()],
+                is_unique: true,
+                name: None,
+            });
+        }
+    }
+
+    for index in blueprint.indexes.iter().flatten() {
+        let (prop, is_unique) = match index {
+            IndexType::Unique(prop) => (prop, true),
+            IndexType::Index(prop) => (prop, false),
+            // full-text indexes are not reported the same way by every database
+            IndexType::Primary(_) | IndexType::FullText(_) => continue,
+        };
+        if prop.delete_index() {
+            continue;
+        }
+        let change = IndexChange {
+            columns: prop.columns().clone(),
+            is_unique,
+            name: None,
+        };
+        if !list.contains(&change) {
+            list.push(change);
+        }
+    }
+
+    list
+}
+
+fn is_nullable(column: &ColumnBlueprint) -> bool {
+    column.is_nullable.unwrap_or_default()
+}
+
+fn describe(data_type: &str, nullable: bool) -> String {
+    if nullable {
+        format!("{data_type} NULL")
+    } else {
+        format!("{data_type} NOT NULL")
+    }
+}
+
+/// Column types that are stored the same way by at least one of the databases
+fn same_kind(a: &ColumnType, b: &ColumnType) -> bool {
+    fn kind(column_type: &ColumnType) -> &'static str {
+        match column_type {
+            ColumnType::AutoIncrementId | ColumnType::Integer => "integer",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Float | ColumnType::Number => "float",
+            ColumnType::Datetime | ColumnType::Timestamp => "datetime",
+            ColumnType::Date => "date",
+            ColumnType::Json => "json",
+            ColumnType::Binary | ColumnType::Uuid => "binary",
+            ColumnType::Char(_)
+            | ColumnType::String(_)
+            | ColumnType::Text
+            | ColumnType::Enum(_) => "text",
+        }
+    }
+
+    kind(a) == kind(b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::base::introspection::{ColumnDescription, IndexDescription};
+
+    fn live_column(name: &str, data_type: &str, nullable: bool) -> ColumnDescription {
+        ColumnDescription {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            column_type: ColumnType::from_sql_type(data_type),
+            is_nullable: nullable,
+            is_primary: name == "id",
+            auto_increment: name == "id",
+            default: None,
+        }
+    }
+
+    #[test]
+    fn test_new_table() {
+        let mut blueprint = TableBlueprint::new("posts");
+        blueprint.id(None);
+        blueprint.string("title");
+        blueprint.index(&["title"]);
+
+        let diff = TableDiff::between(&blueprint, None, false);
+        assert!(diff.is_new);
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(diff.added_indexes[0].columns, vec!["title"]);
+    }
+
+    #[test]
+    fn test_existing_table() {
+        let mut blueprint = TableBlueprint::new("posts");
+        blueprint.id(None);
+        blueprint.string("headline");
+        blueprint.text("body").set_is_nullable(true);
+        blueprint.boolean("published");
+        blueprint
+            .string("slug")
+            .set_is_unique(true)
+            .set_is_nullable(true);
+
+        let mut live = TableDescription::new("posts");
+        live.columns.push(live_column("id", "INTEGER", false));
+        live.columns
+            .push(live_column("title", "VARCHAR(255)", false));
+        live.columns.push(live_column("body", "TEXT", false));
+        live.columns.push(live_column("views", "INTEGER", false));
+        live.indexes.push(IndexDescription {
+            name: "views".to_string(),
+            columns: vec!["views".to_string()],
+            is_unique: false,
+            is_primary: false,
+        });
+        live.push_index_column("title", "title", false, false);
+
+        let diff = TableDiff::between(&blueprint, Some(&live), false);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.added[0].name, "headline");
+        assert_eq!(diff.dropped[0].name, "title");
+
+        let diff = TableDiff::between(&blueprint, Some(&live), true);
+        assert!(!diff.is_new);
+        assert_eq!(
+            diff.renamed,
+            vec![("title".to_string(), "headline".to_string())]
+        );
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["published", "slug"]
+        );
+        assert_eq!(diff.dropped[0].name, "views");
+        assert_eq!(diff.changed[0].name, "body");
+        assert!(diff.added_indexes.is_empty());
+        assert_eq!(diff.dropped_indexes.len(), 2);
+        assert_eq!(diff.dropped_indexes[0].name.as_deref(), Some("views"));
+
+        let same = TableDiff::between(&live.to_blueprint(), Some(&live), true);
+        assert!(same.is_empty());
+    }
+}
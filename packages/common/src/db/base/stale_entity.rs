@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+/// Returned when an update does not change any row because the entity's
+/// version column no longer matches, i.e. the row was changed or deleted since
+/// the entity was fetched.
+///
+/// Check for it with `error.downcast_ref::<StaleEntity>()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleEntity {
+    table: String,
+    id: String,
+}
+
+impl StaleEntity {
+    pub fn new<T: ToString, I: ToString>(table: T, id: I) -> Self {
+        Self {
+            table: table.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Display for StaleEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stale entity: `{}` with ID `{}` was changed by someone else",
+            self.table, self.id
+        )
+    }
+}
+
+impl std::error::Error for StaleEntity {}
@@ -421,6 +421,57 @@ impl TableBlueprint {
         self
     }
 
+    /// Drops the index that was created for the same columns
+    pub fn drop_index(&mut self, columns: &[&str]) -> &mut Self {
+        if self.indexes.is_none() {
+            self.indexes = Some(Vec::new());
+        }
+
+        if let Some(indexes) = &mut self.indexes {
+            indexes.push(IndexType::Index(IndexProp::new(columns, true)));
+        }
+
+        self
+    }
+
+    /// Drops an existing index by its name in the database
+    pub fn drop_index_named(&mut self, name: &str) -> &mut Self {
+        if self.indexes.is_none() {
+            self.indexes = Some(Vec::new());
+        }
+
+        if let Some(indexes) = &mut self.indexes {
+            indexes.push(IndexType::Index(IndexProp::named(name, &[], true)));
+        }
+
+        self
+    }
+
+    /// An index for `match_against` searches over the text columns
+    pub fn fulltext_index(&mut self, columns: &[&str]) -> &mut Self {
+        if self.indexes.is_none() {
+            self.indexes = Some(Vec::new());
+        }
+
+        if let Some(indexes) = &mut self.indexes {
+            indexes.push(IndexType::FullText(IndexProp::new(columns, false)));
+        }
+
+        self
+    }
+
+    pub fn drop_fulltext_index(&mut self, columns: &[&str]) -> &mut Self {
+        if self.indexes.is_none() {
+            self.indexes = Some(Vec::new());
+        }
+
+        if let Some(indexes) = &mut self.indexes {
+            indexes.push(IndexType::FullText(IndexProp::new(columns, true)));
+        }
+
+        self
+    }
+
     pub fn primary_index(&mut self, columns: &[&str]) -> &mut Self {
         if self.indexes.is_none() {
             self.indexes = Some(Vec::new());
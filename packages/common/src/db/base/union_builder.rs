@@ -0,0 +1,86 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::query::QueryBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnionType {
+    Union,
+    UnionAll,
+}
+
+impl Display for UnionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Union => write!(f, "UNION"),
+            Self::UnionAll => write!(f, "UNION ALL"),
+        }
+    }
+}
+
+/// A query whose result is combined with the parent query's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnionBuilder {
+    union_type: UnionType,
+    query: Box<QueryBuilder>,
+}
+
+impl UnionBuilder {
+    pub fn new(union_type: UnionType, query: QueryBuilder) -> Self {
+        Self {
+            union_type,
+            query: Box::new(query),
+        }
+    }
+
+    pub fn union_type(&self) -> UnionType {
+        self.union_type
+    }
+
+    pub fn query(&self) -> &QueryBuilder {
+        &self.query
+    }
+
+    /// Ordering and limiting a combined query requires wrapping it
+    /// in a subquery
+    pub fn needs_wrapping(&self) -> bool {
+        self.query.order_by().is_some()
+            || self.query.limit_by().is_some()
+            || self.query.offset_by().is_some()
+    }
+}
+
+/// The `DISTINCT` modifier of a select query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Distinct {
+    Rows,
+    /// `DISTINCT ON (...)` is only supported by Postgres
+    On(Vec<String>),
+}
+
+impl Display for Distinct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rows => write!(f, "DISTINCT"),
+            Self::On(columns) => write!(f, "DISTINCT ON ({})", columns.join(",")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_union_type_clause() {
+        assert_eq!(UnionType::Union.to_string(), "UNION");
+        assert_eq!(UnionType::UnionAll.to_string(), "UNION ALL");
+    }
+
+    #[test]
+    fn test_distinct_on_clause() {
+        let distinct = Distinct::On(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(distinct.to_string(), "DISTINCT ON (a,b)");
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use super::{aggregate::Aggregate, order_by_builder::OrderByBuilder};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WindowFunctionType {
+    RowNumber,
+    Rank,
+    DenseRank,
+    /// The column's value `offset` rows before the current row
+    Lag {
+        column: String,
+        offset: u64,
+    },
+    /// The column's value `offset` rows after the current row
+    Lead {
+        column: String,
+        offset: u64,
+    },
+    Aggregate {
+        aggregate: Aggregate,
+        column: String,
+    },
+}
+
+/// A function computed over a set of rows related to the current row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowFunction {
+    function: WindowFunctionType,
+    partition_by: Vec<String>,
+    order_by: Option<OrderByBuilder>,
+}
+
+impl WindowFunction {
+    pub fn new(function: WindowFunctionType) -> Self {
+        Self {
+            function,
+            partition_by: Vec::new(),
+            order_by: None,
+        }
+    }
+
+    /// `ROW_NUMBER()`
+    pub fn row_number() -> Self {
+        Self::new(WindowFunctionType::RowNumber)
+    }
+
+    /// `RANK()`
+    pub fn rank() -> Self {
+        Self::new(WindowFunctionType::Rank)
+    }
+
+    /// `DENSE_RANK()`
+    pub fn dense_rank() -> Self {
+        Self::new(WindowFunctionType::DenseRank)
+    }
+
+    /// `LAG(column, offset)`
+    pub fn lag<C: ToString>(column: C, offset: u64) -> Self {
+        Self::new(WindowFunctionType::Lag {
+            column: column.to_string(),
+            offset,
+        })
+    }
+
+    /// `LEAD(column, offset)`
+    pub fn lead<C: ToString>(column: C, offset: u64) -> Self {
+        Self::new(WindowFunctionType::Lead {
+            column: column.to_string(),
+            offset,
+        })
+    }
+
+    /// An aggregate such as `SUM(column)` computed over the window
+    pub fn aggregate<C: ToString>(aggregate: Aggregate, column: C) -> Self {
+        Self::new(WindowFunctionType::Aggregate {
+            aggregate,
+            column: column.to_string(),
+        })
+    }
+
+    pub fn sum<C: ToString>(column: C) -> Self {
+        Self::aggregate(Aggregate::Sum, column)
+    }
+
+    pub fn count<C: ToString>(column: C) -> Self {
+        Self::aggregate(Aggregate::Count, column)
+    }
+
+    pub fn avg<C: ToString>(column: C) -> Self {
+        Self::aggregate(Aggregate::Avg, column)
+    }
+
+    pub fn partition_by<C: ToString, I: IntoIterator<Item = C>>(mut self, columns: I) -> Self {
+        self.partition_by
+            .extend(columns.into_iter().map(|c| c.to_string()));
+        self
+    }
+
+    pub fn asc<C: ToString>(mut self, column: C) -> Self {
+        self.order_by
+            .get_or_insert_with(OrderByBuilder::new)
+            .asc(column);
+        self
+    }
+
+    pub fn desc<C: ToString>(mut self, column: C) -> Self {
+        self.order_by
+            .get_or_insert_with(OrderByBuilder::new)
+            .desc(column);
+        self
+    }
+
+    pub fn function(&self) -> &WindowFunctionType {
+        &self.function
+    }
+
+    pub fn partitions(&self) -> &Vec<String> {
+        &self.partition_by
+    }
+
+    pub fn order_by(&self) -> Option<&OrderByBuilder> {
+        self.order_by.as_ref()
+    }
+
+    pub fn as_clause(&self) -> String {
+        let function = match &self.function {
+            WindowFunctionType::RowNumber => "ROW_NUMBER()".to_string(),
+            WindowFunctionType::Rank => "RANK()".to_string(),
+            WindowFunctionType::DenseRank => "DENSE_RANK()".to_string(),
+            WindowFunctionType::Lag { column, offset } => format!("LAG({column}, {offset})"),
+            WindowFunctionType::Lead { column, offset } => format!("LEAD({column}, {offset})"),
+            WindowFunctionType::Aggregate { aggregate, column } => {
+                let name = match aggregate {
+                    Aggregate::Avg => "AVG",
+                    Aggregate::Count => "COUNT",
+                    Aggregate::Max => "MAX",
+                    Aggregate::Min => "MIN",
+                    Aggregate::Sum => "SUM",
+                };
+                format!("{name}({column})")
+            }
+        };
+
+        let mut window = Vec::new();
+        if !self.partition_by.is_empty() {
+            window.push(format!("PARTITION BY {}", self.partition_by.join(",")));
+        }
+        if let Some(order) = &self.order_by {
+            window.push(order.as_clause());
+        }
+
+        format!("{function} OVER ({})", window.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_row_number() {
+        let window = WindowFunction::row_number().desc("points");
+        assert_eq!(
+            window.as_clause(),
+            "ROW_NUMBER() OVER (ORDER BY points DESC)"
+        );
+    }
+
+    #[test]
+    fn test_running_total() {
+        let window = WindowFunction::sum("amount")
+            .partition_by(["account_id"])
+            .asc("created_at")
+            .asc("id");
+        assert_eq!(
+            window.as_clause(),
+            "SUM(amount) OVER (PARTITION BY account_id ORDER BY created_at ASC,id ASC)"
+        );
+    }
+
+    #[test]
+    fn test_lag_without_window() {
+        assert_eq!(
+            WindowFunction::lag("points", 1).as_clause(),
+            "LAG(points, 1) OVER ()"
+        );
+    }
+}
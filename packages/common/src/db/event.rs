@@ -1,5 +1,7 @@
+mod model_event;
 mod schema_wrote_event;
 mod user_created_event;
 
+pub use model_event::{ModelEvent, ModelEventKind};
 pub use schema_wrote_event::SchemeWroteEvent;
 pub use user_created_event::UserCreatedEvent;
@@ -0,0 +1,162 @@
+use crate::{app::observable::Observable, db::field_values::FieldValue};
+
+/// The stage of a model's lifecycle an event is dispatched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModelEventKind {
+    Creating,
+    Created,
+    Updating,
+    Updated,
+    Deleting,
+    Deleted,
+    Restoring,
+    Restored,
+}
+
+impl ModelEventKind {
+    /// `*ing` events are dispatched before the operation runs and can veto it
+    pub fn is_before(&self) -> bool {
+        matches!(
+            self,
+            Self::Creating | Self::Updating | Self::Deleting | Self::Restoring
+        )
+    }
+}
+
+/// Dispatched by the generated entity repositories around `insert`, `update`,
+/// `delete`, `destroy` and `restore`.
+///
+/// Observers are registered per entity with `ModelEvent::<Entity>::subscribe`.
+/// The model may be changed by a `*ing` observer before it is written.
+#[derive(Debug, Clone)]
+pub struct ModelEvent<T> {
+    kind: ModelEventKind,
+    id: Option<FieldValue>,
+    model: Option<T>,
+    vetoed: Option<String>,
+}
+
+impl<T> ModelEvent<T> {
+    pub fn new(kind: ModelEventKind, id: Option<FieldValue>, model: Option<T>) -> Self {
+        Self {
+            kind,
+            id,
+            model,
+            vetoed: None,
+        }
+    }
+
+    pub fn kind(&self) -> ModelEventKind {
+        self.kind
+    }
+
+    /// The ID of the model. `None` for the `creating` event
+    pub fn id(&self) -> Option<&FieldValue> {
+        self.id.as_ref()
+    }
+
+    /// The model is `None` when the operation was called with an ID only
+    pub fn model(&self) -> Option<&T> {
+        self.model.as_ref()
+    }
+
+    pub fn model_mut(&mut self) -> Option<&mut T> {
+        self.model.as_mut()
+    }
+
+    pub fn into_model(self) -> Option<T> {
+        self.model
+    }
+
+    /// Stops the operation. Only honored for `*ing` events
+    pub fn veto<R: ToString>(&mut self, reason: R) {
+        if self.kind.is_before() {
+            self.vetoed = Some(reason.to_string());
+        }
+    }
+
+    pub fn is_vetoed(&self) -> bool {
+        self.vetoed.is_some()
+    }
+
+    pub fn vetoed_reason(&self) -> Option<&str> {
+        self.vetoed.as_deref()
+    }
+
+    fn into_result(self) -> Result<Self, anyhow::Error> {
+        match &self.vetoed {
+            Some(reason) => Err(anyhow::anyhow!("{:?} vetoed: {}", self.kind, reason)),
+            None => Ok(self),
+        }
+    }
+}
+
+impl<T: Send + 'static> ModelEvent<T> {
+    /// Dispatches a `*ing` event and returns the model as left by the observers
+    pub async fn before(
+        kind: ModelEventKind,
+        id: Option<FieldValue>,
+        model: T,
+    ) -> Result<T, anyhow::Error> {
+        Self::new(kind, id, Some(model))
+            .dispatch()
+            .await
+            .into_result()?
+            .into_model()
+            .ok_or_else(|| anyhow::anyhow!("{:?} observer removed the model", kind))
+    }
+
+    /// Dispatches a `*ing` event for an operation that was called with an ID only
+    pub async fn before_id(kind: ModelEventKind, id: FieldValue) -> Result<(), anyhow::Error> {
+        Self::new(kind, Some(id), None)
+            .dispatch()
+            .await
+            .into_result()
+            .map(|_| ())
+    }
+
+    /// Dispatches a `*ed` event
+    pub async fn after(
+        kind: ModelEventKind,
+        id: Option<FieldValue>,
+        model: Option<T>,
+    ) -> Option<T> {
+        Self::new(kind, id, model).dispatch().await.into_model()
+    }
+}
+
+impl<T: Send + 'static> Observable for ModelEvent<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_veto() {
+        #[derive(Debug, PartialEq)]
+        struct Post(String);
+
+        ModelEvent::<Post>::subscribe(|mut event| async move {
+            if let Some(post) = event.model_mut() {
+                post.0 = post.0.to_lowercase();
+            }
+            if event.id() == Some(&FieldValue::from(1)) {
+                event.veto("locked");
+            }
+            event
+        })
+        .await;
+
+        let post = ModelEvent::before(ModelEventKind::Creating, None, Post("Hello".into())).await;
+        assert_eq!(post.unwrap(), Post("hello".into()));
+
+        let result =
+            ModelEvent::<Post>::before_id(ModelEventKind::Deleting, FieldValue::from(1)).await;
+        assert_eq!(result.unwrap_err().to_string(), "Deleting vetoed: locked");
+
+        let event =
+            ModelEvent::<Post>::after(ModelEventKind::Deleted, Some(FieldValue::from(1)), None)
+                .await;
+        assert!(event.is_none());
+    }
+}
@@ -1,11 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-use crate::db::base::{aggregate::Aggregate, query::QueryBuilder};
+use crate::db::base::{
+    aggregate::Aggregate, full_text::FullTextMatch, query::QueryBuilder,
+    window_function::WindowFunction,
+};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum QueryColumnName {
     Name(String),
     SubQuery(Box<QueryBuilder>),
+    Window(Box<WindowFunction>),
+    /// `(column, path)`, the value at a JSON path of the column
+    Json(String, String),
+    /// The relevance of a full-text search, higher is more relevant
+    Relevance(FullTextMatch),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -71,6 +79,18 @@ impl From<QueryBuilder> for QueryColumnName {
     }
 }
 
+impl From<WindowFunction> for QueryColumnName {
+    fn from(value: WindowFunction) -> Self {
+        Self::Window(Box::new(value))
+    }
+}
+
+impl From<WindowFunction> for QueryColumn {
+    fn from(value: WindowFunction) -> Self {
+        QueryColumnName::from(value).into()
+    }
+}
+
 impl From<QueryColumnName> for QueryColumn {
     fn from(name: QueryColumnName) -> Self {
         Self::new(name, None, None)
@@ -1,13 +1,16 @@
 use std::{
     collections::HashMap,
     fmt::{Debug, Display},
+    pin::Pin,
     sync::Arc,
 };
 
 use crate::db::{
-    base::{manager::Manager, query::QueryBuilder},
+    base::{manager::Manager, query::QueryBuilder, schema::ExecuteResult},
     field_values::FieldValue,
-    types::StructuredColumnAndValue,
+    query_column::QueryColumn,
+    table_model::TableModel,
+    types::{ColumnAndValue, StructuredColumnAndValue},
 };
 
 #[derive(Clone)]
@@ -40,6 +43,10 @@ pub enum RelationType {
         query: QueryBuilder,
         // pivot: QueryBuilder,
     },
+    /// Each morph type gets its own query. This one selects from the parent table
+    MorphTo {
+        query: QueryBuilder,
+    },
 }
 
 impl Display for RelationType {
@@ -53,6 +60,7 @@ impl Display for RelationType {
             Self::HasOne { query: _ } => "has_one",
             Self::MorphMany { query: _ } => "morph_many",
             Self::MorphOne { query: _ } => "morph_one",
+            Self::MorphTo { query: _ } => "morph_to",
         };
 
         write!(f, "{}", _type)
@@ -70,6 +78,7 @@ impl RelationType {
             Self::HasOne { query } => (query, None),
             Self::MorphMany { query } => (query, None),
             Self::MorphOne { query } => (query, None),
+            Self::MorphTo { query } => (query, None),
         }
     }
 
@@ -83,6 +92,7 @@ impl RelationType {
             Self::HasOne { query } => query,
             Self::MorphMany { query } => query,
             Self::MorphOne { query } => query,
+            Self::MorphTo { query } => query,
         }
     }
 
@@ -96,6 +106,7 @@ impl RelationType {
             Self::HasOne { query: _ } => None,
             Self::MorphMany { query: _ } => None,
             Self::MorphOne { query: _ } => None,
+            Self::MorphTo { query: _ } => None,
         }
     }
 }
@@ -196,62 +207,75 @@ impl<T> Relation<T> {
             .take()
             .expect("could not get relation processor");
 
-        let RelationProcessor {
+        let processor = (process)(self, rows, join_field_values);
+
+        for RelationProcessor {
             query,
             child_col_name,
             child_field_prefix,
             parent_col_name,
-        } = (process)(self, rows, join_field_values);
-
-        match manager.execute_query(query).all().await {
-            Ok(rel_list) => {
-                for a_row in rel_list {
-                    let mut belongs_to_hash = Vec::new();
-
-                    if let Some(FieldValue::Object(obj)) = a_row.get(&child_field_prefix) {
-                        if let Some(value) = obj.get(&child_col_name) {
-                            if let Some(kv) = join_field_values.get(&parent_col_name) {
-                                for (hash, val) in kv {
-                                    if val == value {
-                                        belongs_to_hash.push(*hash);
-                                    }
-                                }
-                            }
+            ..
+        } in processor.into_batches()
+        {
+            let Some(query) = query else {
+                continue;
+            };
+
+            let rel_list = manager.execute_query(query).all().await?;
+            for a_row in rel_list {
+                let mut belongs_to_hash = Vec::new();
+
+                if let Some(FieldValue::Object(obj)) = a_row.get(&child_field_prefix)
+                    && let Some(value) = obj.get(&child_col_name)
+                    && let Some(kv) = join_field_values.get(&parent_col_name)
+                {
+                    for (hash, val) in kv {
+                        if val == value {
+                            belongs_to_hash.push(*hash);
                         }
                     }
+                }
 
-                    if !belongs_to_hash.is_empty() {
-                        if rows_rel_map.get(name).is_none() {
-                            rows_rel_map
-                                .insert(name.to_string(), ::std::collections::HashMap::new());
-                        }
+                if !belongs_to_hash.is_empty() {
+                    if rows_rel_map.get(name).is_none() {
+                        rows_rel_map.insert(name.to_string(), ::std::collections::HashMap::new());
+                    }
 
-                        for hash in belongs_to_hash {
-                            if rows_rel_map.get(name).unwrap().get(&hash).is_none() {
-                                rows_rel_map.get_mut(name).unwrap().insert(hash, Vec::new());
-                            }
-                            rows_rel_map
-                                .get_mut(name)
-                                .unwrap()
-                                .get_mut(&hash)
-                                .unwrap()
-                                .push(a_row.clone());
+                    for hash in belongs_to_hash {
+                        if rows_rel_map.get(name).unwrap().get(&hash).is_none() {
+                            rows_rel_map.get_mut(name).unwrap().insert(hash, Vec::new());
                         }
+                        rows_rel_map
+                            .get_mut(name)
+                            .unwrap()
+                            .get_mut(&hash)
+                            .unwrap()
+                            .push(a_row.clone());
                     }
                 }
             }
-            Err(e) => return Err(e),
         }
 
         Ok(())
     }
 }
 
+/// Loads relations by name into fetched entities. Implemented by the `DirtyTable` derive
+pub trait EagerLoad: Sized {
+    /// `paths` are relation names. Nested relations are separated by a dot
+    fn eager_load<'a>(
+        manager: &'a Manager,
+        rows_map: &'a mut HashMap<u64, Self>,
+        paths: &'a [String],
+    ) -> Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send + 'a>>;
+}
+
 pub struct RelationProcessor {
     pub parent_col_name: String,
     pub child_field_prefix: String,
     pub child_col_name: String,
-    pub query: QueryBuilder,
+    pub query: Option<QueryBuilder>,
+    pub batches: Vec<RelationProcessor>,
 }
 
 impl RelationProcessor {
@@ -262,10 +286,292 @@ impl RelationProcessor {
         child_col_name: String,
     ) -> Self {
         Self {
-            query,
+            query: Some(query),
             parent_col_name,
             child_field_prefix,
             child_col_name,
+            batches: Vec::new(),
+        }
+    }
+
+    /// A processor that runs a query per batch. Used when the related rows
+    /// live in different tables
+    pub fn batched(batches: Vec<RelationProcessor>) -> Self {
+        Self {
+            query: None,
+            parent_col_name: String::new(),
+            child_field_prefix: String::new(),
+            child_col_name: String::new(),
+            batches,
+        }
+    }
+
+    fn into_batches(mut self) -> Vec<RelationProcessor> {
+        let mut list = std::mem::take(&mut self.batches);
+        list.insert(0, self);
+        list
+    }
+}
+
+/// The pivot table of a `belongs_to_many` relation
+#[derive(Debug, Clone)]
+pub struct PivotTable {
+    table: String,
+    parent_col: String,
+    related_col: String,
+}
+
+impl PivotTable {
+    /// `parent_col` references the parent entity and `related_col` the related entity
+    pub fn new(table: &str, parent_col: &str, related_col: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            parent_col: parent_col.to_string(),
+            related_col: related_col.to_string(),
+        }
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    pub fn parent_col(&self) -> &str {
+        &self.parent_col
+    }
+
+    pub fn related_col(&self) -> &str {
+        &self.related_col
+    }
+
+    /// The related keys currently attached to the parent
+    pub async fn attached(
+        &self,
+        manager: &Manager,
+        parent: FieldValue,
+    ) -> Result<Vec<FieldValue>, anyhow::Error> {
+        let rows = manager
+            .select_from_table(&self.table, |query| {
+                query.select(&self.related_col);
+                query.is_eq(&self.parent_col, parent);
+            })
+            .fetch_all()
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.get(&self.related_col).cloned())
+            .collect())
+    }
+
+    /// Inserts the pivot rows that do not exist yet
+    pub async fn attach(
+        &self,
+        manager: &Manager,
+        parent: FieldValue,
+        related: Vec<FieldValue>,
+    ) -> Result<ExecuteResult, anyhow::Error> {
+        let attached = self
+            .attached(manager, parent.clone())
+            .await?
+            .iter()
+            .map(FieldValue::to_string)
+            .collect::<Vec<String>>();
+
+        let mut rows = Vec::new();
+        for value in related {
+            let key = value.to_string();
+            if attached.contains(&key) || rows.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            let mut row = ColumnAndValue::new();
+            row.insert(self.parent_col.clone(), parent.clone());
+            row.insert(self.related_col.clone(), value);
+            rows.push((key, row));
+        }
+
+        if rows.is_empty() {
+            return Ok(ExecuteResult::default());
+        }
+
+        manager
+            .insert_multi(&self.table, rows.into_iter().map(|(_, row)| row))
+            .await
+    }
+
+    /// Deletes the pivot rows of the related keys
+    pub async fn detach(
+        &self,
+        manager: &Manager,
+        parent: FieldValue,
+        related: Vec<FieldValue>,
+    ) -> Result<ExecuteResult, anyhow::Error> {
+        if related.is_empty() {
+            return Ok(ExecuteResult::default());
+        }
+
+        manager
+            .delete(&self.table, |query| {
+                query.is_eq(&self.parent_col, parent);
+                query.is_in(&self.related_col, related);
+            })
+            .await
+    }
+
+    /// Makes the related keys the only ones attached to the parent
+    pub async fn sync(
+        &self,
+        manager: &Manager,
+        parent: FieldValue,
+        related: Vec<FieldValue>,
+    ) -> Result<(), anyhow::Error> {
+        let keys = related
+            .iter()
+            .map(FieldValue::to_string)
+            .collect::<Vec<String>>();
+
+        // the attached keys are read from the writer, a replica may be behind
+        manager
+            .transaction(|manager| async move {
+                let stale = self
+                    .attached(&manager, parent.clone())
+                    .await?
+                    .into_iter()
+                    .filter(|value| !keys.contains(&value.to_string()))
+                    .collect::<Vec<FieldValue>>();
+
+                self.detach(&manager, parent.clone(), stale).await?;
+                self.attach(&manager, parent, related).await?;
+
+                Ok(())
+            })
+            .await
+    }
+}
+
+type MorphBuilder<T> = Arc<dyn Fn(&StructuredColumnAndValue) -> Option<T> + Send + Sync>;
+
+/// Maps the morph type values of a `morph_to` relation to their entities
+pub trait MorphToRegistry: Sized + 'static {
+    fn register(registry: &mut MorphRegistry<Self>);
+
+    fn registry() -> MorphRegistry<Self> {
+        let mut registry = MorphRegistry::new();
+        Self::register(&mut registry);
+        registry
+    }
+}
+
+/// A morph type and the entity it maps to
+pub struct MorphEntry<T> {
+    name: String,
+    table: &'static str,
+    id_column: &'static str,
+    deleted_at_column: Option<&'static str>,
+    columns: Vec<QueryColumn>,
+    builder: MorphBuilder<T>,
+}
+
+impl<T> Clone for MorphEntry<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            table: self.table,
+            id_column: self.id_column,
+            deleted_at_column: self.deleted_at_column,
+            columns: self.columns.clone(),
+            builder: self.builder.clone(),
+        }
+    }
+}
+
+impl<T> MorphEntry<T> {
+    /// The value stored in the `*_type` column
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    pub fn id_column(&self) -> &'static str {
+        self.id_column
+    }
+
+    pub fn deleted_at_column(&self) -> Option<&'static str> {
+        self.deleted_at_column
+    }
+
+    /// A query that selects the entity's columns
+    pub fn query(&self) -> QueryBuilder {
+        let mut query = QueryBuilder::new_query(self.table);
+        query.select_multiple(self.columns.clone());
+        query
+    }
+
+    /// Builds the entity from a row fetched with this entry's query
+    pub fn build(&self, row: &StructuredColumnAndValue) -> Option<T> {
+        (self.builder)(row)
+    }
+}
+
+pub struct MorphRegistry<T> {
+    entries: Vec<MorphEntry<T>>,
+}
+
+impl<T> Clone for MorphRegistry<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
         }
     }
 }
+
+impl<T> Default for MorphRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MorphRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Maps the morph type `name` to the entity `M`. `wrap` turns the entity into `T`
+    pub fn add<M: TableModel + 'static>(
+        &mut self,
+        name: &str,
+        wrap: impl Fn(M) -> T + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.entries.push(MorphEntry {
+            name: name.to_string(),
+            table: M::table_name(),
+            id_column: M::id_column(),
+            deleted_at_column: M::deleted_at_column(),
+            columns: M::table_query_col_aliases(None),
+            builder: Arc::new(move |row| match row.get(M::table_name()) {
+                Some(FieldValue::Object(values)) => {
+                    M::from_column_value(values.clone()).ok().map(&wrap)
+                }
+                _ => None,
+            }),
+        });
+        self
+    }
+
+    pub fn entries(&self) -> &Vec<MorphEntry<T>> {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MorphEntry<T>> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Builds the entity of the morph type from a related row
+    pub fn build(&self, name: &str, row: &StructuredColumnAndValue) -> Option<T> {
+        self.get(name).and_then(|entry| entry.build(row))
+    }
+}
@@ -2,7 +2,7 @@ use std::hash::Hash;
 
 use crate::db::base::{
     query::QueryBuilder,
-    table::{CREATED_AT_FIELD, DELETED_AT_FIELD, UPDATED_AT_FIELD},
+    table::{CREATED_AT_FIELD, DELETED_AT_FIELD, TableBlueprint, UPDATED_AT_FIELD},
 };
 
 use super::{
@@ -17,6 +17,9 @@ pub trait TableModel: FromColumnAndValue + ToColumnAndValue {
     /// Tables table's column names without prefix
     fn table_columns() -> Vec<&'static str>;
 
+    /// The blueprint used to create the model's table
+    fn blueprint() -> TableBlueprint;
+
     fn id_field() -> &'static str {
         "id"
     }
@@ -59,6 +62,16 @@ pub trait TableModel: FromColumnAndValue + ToColumnAndValue {
         Some(DELETED_AT_FIELD)
     }
 
+    /// Returns the optimistic locking version column's name
+    fn version_column() -> Option<&'static str> {
+        None
+    }
+
+    /// The named connection set the model's table lives in. `None` for the default set
+    fn connection() -> Option<&'static str> {
+        None
+    }
+
     /// Prefixes the subject with the model's table name
     fn prefix_with_tbl<T: ToString>(subject: T) -> String {
         format!("{}.{}", Self::table_name(), subject.to_string())
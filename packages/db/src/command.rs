@@ -1,13 +1,22 @@
 mod migrator;
 use anyhow::anyhow;
 use dirtybase_contract::{
+    ExtensionManager,
+    app_contract::Context,
     cli_contract::{
         CliCommandManager,
         clap::{self, Arg, ArgAction, ArgMatches},
     },
-    db_contract::{SeederRegisterer, base::manager::Manager},
+    db_contract::{
+        SeederRegisterer,
+        base::{
+            introspection::{DatabaseDescription, TableDescription},
+            manager::Manager,
+            schema_diff::TableDiff,
+        },
+    },
 };
-use migrator::{MigrateAction, Migrator};
+use migrator::{MigrateAction, MigrationStatus, Migrator};
 
 #[derive(Debug, Clone)]
 pub(crate) enum Commands {
@@ -32,8 +41,26 @@ pub(crate) fn setup_commands(mut manager: CliCommandManager) -> CliCommandManage
     let migrate = clap::Command::new("migrate")
         .about("Execute migration")
         .arg_required_else_help(true)
-        .subcommand(clap::Command::new("up").about("Migrate up"))
-        .subcommand(clap::Command::new("down").about("Migrate down"))
+        .subcommand(
+            clap::Command::new("up").about("Migrate up").arg(
+                Arg::new("pretend")
+                    .long("pretend")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the SQL statements without running them"),
+            ),
+        )
+        .subcommand(
+            clap::Command::new("down")
+                .about("Migrate the last batch down")
+                .arg(
+                    Arg::new("step")
+                        .long("step")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Number of migrations to roll back instead of the last batch"),
+                ),
+        )
+        .subcommand(clap::Command::new("rollback").about("Migrate the last batch down"))
+        .subcommand(clap::Command::new("status").about("List the ran and pending migrations"))
         .subcommand(clap::Command::new("refresh").about("Resets and migrate all up"))
         .subcommand(clap::Command::new("reset").about("Migrate all down"));
 
@@ -46,8 +73,26 @@ pub(crate) fn setup_commands(mut manager: CliCommandManager) -> CliCommandManage
                     let migrator = Migrator::new(Some(context.clone())).await;
                     if let Ok(db_manager) = context.get::<Manager>().await {
                         match action {
-                            MigrateAction::Up => return migrator.up(&db_manager).await,
-                            MigrateAction::Down => return migrator.down(&db_manager).await,
+                            MigrateAction::Up { pretend: false } => {
+                                return migrator.up(&db_manager).await;
+                            }
+                            MigrateAction::Up { pretend: true } => {
+                                for (name, statements) in migrator.pretend(&db_manager).await? {
+                                    println!("-- {name}");
+                                    for sql in statements {
+                                        println!("{sql};");
+                                    }
+                                }
+                                Ok(())
+                            }
+                            MigrateAction::Down { step } => {
+                                return migrator.down(&db_manager, step).await;
+                            }
+                            MigrateAction::Rollback => return migrator.rollback(&db_manager).await,
+                            MigrateAction::Status => {
+                                print_status(&migrator.status(&db_manager).await?);
+                                Ok(())
+                            }
                             MigrateAction::Reset => return migrator.reset(&db_manager).await,
                             MigrateAction::Refresh => return migrator.refresh(&db_manager).await,
                             MigrateAction::Unknown => {
@@ -83,5 +128,269 @@ pub(crate) fn setup_commands(mut manager: CliCommandManager) -> CliCommandManage
         })
     });
 
+    // $ db:show
+    let show = clap::Command::new("db:show")
+        .about("Show the database size and the number of rows of each table");
+
+    manager.register(show, |_, _, context| {
+        Box::pin(async move {
+            let manager = context.get::<Manager>().await?;
+            print_database(&manager.describe_database().await?);
+            Ok(())
+        })
+    });
+
+    // $ db:table posts
+    let table = clap::Command::new("db:table")
+        .about("Show the columns, indexes and foreign keys of a table")
+        .arg_required_else_help(true)
+        .arg(Arg::new("name").required(true).help("Name of the table"));
+
+    manager.register(table, |_, matches, context| {
+        Box::pin(async move {
+            let name = matches
+                .get_one::<String>("name")
+                .ok_or_else(|| anyhow!("the table name is required"))?;
+            let manager = context.get::<Manager>().await?;
+            match manager.describe_table(name).await? {
+                Some(table) => {
+                    print_table(&table);
+                    Ok(())
+                }
+                None => Err(anyhow!("table `{name}` does not exist")),
+            }
+        })
+    });
+
+    // $ db:diff --json --guess-renames
+    let diff = clap::Command::new("db:diff")
+        .about("Compare the registered entities with the database schema")
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Print the differences as JSON"),
+        )
+        .arg(
+            Arg::new("guess-renames")
+                .long("guess-renames")
+                .action(ArgAction::SetTrue)
+                .help("Treat a dropped and an added column of the same kind as a rename"),
+        );
+
+    manager.register(diff, |_, matches, context| {
+        Box::pin(async move {
+            let manager = context.get::<Manager>().await?;
+            let diffs = schema_diff(&context, &manager, matches.get_flag("guess-renames")).await?;
+            if matches.get_flag("json") {
+                println!("{}", serde_json::to_string(&diffs)?);
+            } else {
+                print_diffs(&diffs);
+            }
+            Ok(())
+        })
+    });
+
     manager
 }
+
+/// Diffs the entities registered by the extensions against the database
+async fn schema_diff(
+    context: &Context,
+    manager: &Manager,
+    guess_renames: bool,
+) -> anyhow::Result<Vec<TableDiff>> {
+    let mut blueprints = Vec::new();
+    ExtensionManager::extensions(|ext| {
+        if let Some(list) = ext.entities(context) {
+            blueprints.extend(list);
+        }
+    })
+    .await;
+
+    let mut diffs = Vec::new();
+    for blueprint in blueprints {
+        let live = manager.describe_table(&blueprint.name).await?;
+        let diff = TableDiff::between(&blueprint, live.as_ref(), guess_renames);
+        if !diff.is_empty() {
+            diffs.push(diff);
+        }
+    }
+    Ok(diffs)
+}
+
+fn print_diffs(diffs: &[TableDiff]) {
+    if diffs.is_empty() {
+        println!("The database is in sync with the entities");
+        return;
+    }
+
+    for diff in diffs {
+        if diff.is_new {
+            println!("{}: new table", diff.table);
+            continue;
+        }
+        println!("{}:", diff.table);
+        for column in &diff.added {
+            println!("  + {}", column.name);
+        }
+        for column in &diff.dropped {
+            println!("  - {}", column.name);
+        }
+        for (old, new) in &diff.renamed {
+            println!("  ~ {old} -> {new} (guessed)");
+        }
+        for change in &diff.changed {
+            println!("  ! {}: {} -> {}", change.name, change.from, change.to);
+        }
+        for index in &diff.added_indexes {
+            println!("  + index ({})", index.columns.join(", "));
+        }
+        for index in &diff.dropped_indexes {
+            println!("  - index ({})", index.columns.join(", "));
+        }
+    }
+}
+
+fn print_database(database: &DatabaseDescription) {
+    let size = match database.size {
+        Some(size) => format_size(size),
+        None => "Unknown".to_string(),
+    };
+    println!("Database  {}", database.kind);
+    println!("Size      {size}");
+    println!("Tables    {}", database.tables.len());
+    println!();
+
+    let name_width = database
+        .tables
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(["Table".len()])
+        .max()
+        .unwrap_or_default();
+    println!("{:name_width$}  Rows", "Table");
+    for (name, rows) in &database.tables {
+        println!("{name:name_width$}  {rows}");
+    }
+}
+
+fn print_table(table: &TableDescription) {
+    println!("Table  {}", table.name);
+    println!();
+
+    let name_width = table
+        .columns
+        .iter()
+        .map(|c| c.name.len())
+        .chain(["Column".len()])
+        .max()
+        .unwrap_or_default();
+    let type_width = table
+        .columns
+        .iter()
+        .map(|c| c.data_type.len())
+        .chain(["Type".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:name_width$}  {:type_width$}  Attributes",
+        "Column", "Type"
+    );
+    for column in &table.columns {
+        let mut attributes = Vec::new();
+        if column.is_primary {
+            attributes.push("primary".to_string());
+        }
+        if column.auto_increment {
+            attributes.push("autoincrement".to_string());
+        }
+        if column.is_nullable {
+            attributes.push("nullable".to_string());
+        }
+        if let Some(default) = &column.default {
+            attributes.push(format!("default {default}"));
+        }
+        println!(
+            "{:name_width$}  {:type_width$}  {}",
+            column.name,
+            column.data_type,
+            attributes.join(", ")
+        );
+    }
+
+    if !table.indexes.is_empty() {
+        println!();
+        println!("Indexes");
+        for index in &table.indexes {
+            let kind = if index.is_primary {
+                "primary"
+            } else if index.is_unique {
+                "unique"
+            } else {
+                "index"
+            };
+            println!("  {} ({}) {kind}", index.name, index.columns.join(", "));
+        }
+    }
+
+    if !table.foreign_keys.is_empty() {
+        println!();
+        println!("Foreign keys");
+        for fk in &table.foreign_keys {
+            let on_delete = match &fk.on_delete {
+                Some(rule) => format!(" ON DELETE {rule}"),
+                None => String::new(),
+            };
+            println!(
+                "  {} -> {}.{}{on_delete}",
+                fk.column, fk.foreign_table, fk.foreign_column
+            );
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    for unit in ["KB", "MB", "GB"] {
+        if size < 1024.0 {
+            return format!("{size:.1} {unit}");
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} TB")
+}
+
+fn print_status(list: &[MigrationStatus]) {
+    let ext_width = list
+        .iter()
+        .map(|m| m.extension().len())
+        .chain(["Extension".len()])
+        .max()
+        .unwrap_or_default();
+    let name_width = list
+        .iter()
+        .map(|m| m.name().len())
+        .chain(["Migration".len()])
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{:ext_width$}  {:name_width$}  Batch",
+        "Extension", "Migration"
+    );
+    for entry in list {
+        let batch = match entry.batch() {
+            Some(batch) => batch.to_string(),
+            None => "Pending".to_string(),
+        };
+        println!(
+            "{:ext_width$}  {:name_width$}  {batch}",
+            entry.extension(),
+            entry.name()
+        );
+    }
+}
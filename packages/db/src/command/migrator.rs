@@ -6,17 +6,42 @@ use dirtybase_contract::{
     prelude::Context,
 };
 
-use crate::model::migration::{MigrationRepository, TABLE_NAME};
+use crate::model::migration::{MigrationEntity, MigrationRepository, TABLE_NAME};
 
 #[derive(Debug, Clone)]
 pub enum MigrateAction {
-    Up,
-    Down,
+    Up { pretend: bool },
+    Down { step: Option<usize> },
+    Rollback,
+    Status,
     Refresh,
     Reset,
     Unknown,
 }
 
+/// The state of a registered migration
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    extension: String,
+    name: String,
+    batch: Option<i64>,
+}
+
+impl MigrationStatus {
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The batch the migration ran in. `None` when the migration is pending
+    pub fn batch(&self) -> Option<i64> {
+        self.batch
+    }
+}
+
 pub struct Migrator {
     context: Context,
 }
@@ -32,90 +57,165 @@ impl Migrator {
         Self { context }
     }
 
+    /// Runs the pending migrations as a new batch
     pub async fn up(&self, manager: &Manager) -> Result<(), anyhow::Error> {
-        let batch = chrono::Utc::now().timestamp();
+        self.run_up(manager, self.migrations().await).await
+    }
+
+    /// Returns the statements each pending migration would run, without running them
+    pub async fn pretend(
+        &self,
+        manager: &Manager,
+    ) -> Result<Vec<(String, Vec<String>)>, anyhow::Error> {
+        self.run_pretend(manager, self.migrations().await).await
+    }
+
+    /// Rolls back the last batch or, when `step` is set, the last `step` migrations
+    pub async fn down(&self, manager: &Manager, step: Option<usize>) -> Result<(), anyhow::Error> {
         let repo = self.repo(manager).await;
+        let entries = match step {
+            Some(total) => repo.get_latest(total).await,
+            None => repo.get_last_batch().await,
+        };
+
+        self.run_down(manager, entries, self.migrations().await)
+            .await
+    }
 
-        let migrations = self.migrations().await;
+    /// Rolls back the last batch
+    pub async fn rollback(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+        self.down(manager, None).await
+    }
+
+    /// The ran and pending migrations of each extension
+    pub async fn status(&self, manager: &Manager) -> Result<Vec<MigrationStatus>, anyhow::Error> {
+        let repo = self.repo(manager).await;
+        let ran = repo.all().await;
+        let mut list = Vec::new();
+
+        for (extension, migrations) in self.migrations_by_extension().await {
+            for entry in migrations {
+                let name = entry.id();
+                let batch = ran.iter().find(|m| m.name() == name).map(|m| m.batch());
+                list.push(MigrationStatus {
+                    extension: extension.clone(),
+                    name,
+                    batch,
+                });
+            }
+        }
+
+        Ok(list)
+    }
+
+    pub async fn refresh(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+        self.down(manager, None).await?;
+        self.up(manager).await
+    }
+
+    pub async fn reset(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+        loop {
+            let repo = self.repo(manager).await;
+            if repo.get_last_batch().await.is_empty() {
+                break;
+            }
+            self.down(manager, None).await?;
+        }
+
+        manager.drop_table(TABLE_NAME).await
+    }
+
+    async fn run_up(
+        &self,
+        manager: &Manager,
+        migrations: Vec<Box<dyn Migration>>,
+    ) -> Result<(), anyhow::Error> {
+        let repo = self.repo(manager).await;
+        let batch = repo.next_batch().await;
 
         manager
             .transaction(|trans| async move {
+                let mut ran: Vec<&Box<dyn Migration>> = Vec::new();
                 for entry in &migrations {
                     let name = entry.id();
-                    if !repo.exist(&name).await {
-                        tracing::debug!("migrating {} up", &name);
-                        if let Err(e) = entry.up(&trans).await {
-                            tracing::debug!("reverting migration: {}", entry.id());
-                            entry.down(&trans).await?;
-                            let collection = repo.get_batch(batch).await;
-                            for name in collection.keys() {
-                                for entry in &migrations {
-                                    if entry.id() != name.as_str() {
-                                        tracing::debug!("reverting migration: {}", entry.id());
-                                        entry.down(&trans).await?
-                                    }
-                                }
-                            }
-                            repo.delete_batch(batch).await;
-                            return Err(e);
-                        }
+                    if repo.exist(&name).await {
+                        tracing::debug!("migration already exist: {:?}", &name);
+                        continue;
+                    }
 
-                        if let Err(e) = repo.create(&name, batch).await {
-                            tracing::error!("could not create migration entry: {:?}", &e);
-                            entry.down(&trans).await?;
-                            return Err(e);
+                    tracing::debug!("migrating {} up", &name);
+                    if let Err(e) = entry.up(&trans).await {
+                        tracing::debug!("reverting migration: {}", &name);
+                        entry.down(&trans).await?;
+                        for previous in ran.iter().rev() {
+                            tracing::debug!("reverting migration: {}", previous.id());
+                            previous.down(&trans).await?;
                         }
-                    } else {
-                        tracing::debug!("migration already exist: {:?}", &name);
+                        repo.delete_batch(batch).await;
+                        return Err(e);
+                    }
+
+                    if let Err(e) = repo.create(&name, batch).await {
+                        tracing::error!("could not create migration entry: {:?}", &e);
+                        entry.down(&trans).await?;
+                        return Err(e);
                     }
+                    ran.push(entry);
                 }
-                return Ok(());
+                Ok(())
             })
             .await
     }
 
-    pub async fn down(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+    async fn run_pretend(
+        &self,
+        manager: &Manager,
+        migrations: Vec<Box<dyn Migration>>,
+    ) -> Result<Vec<(String, Vec<String>)>, anyhow::Error> {
+        let repo = self.repo(manager).await;
+        let mut list = Vec::new();
+
+        for entry in &migrations {
+            let name = entry.id();
+            if repo.exist(&name).await {
+                continue;
+            }
+
+            let pretend = manager.pretend();
+            entry.up(&pretend).await?;
+            list.push((name, pretend.pretended_statements()));
+        }
+
+        Ok(list)
+    }
+
+    async fn run_down(
+        &self,
+        manager: &Manager,
+        entries: Vec<MigrationEntity>,
+        migrations: Vec<Box<dyn Migration>>,
+    ) -> Result<(), anyhow::Error> {
         let repo = self.repo(manager).await;
 
-        let collection = repo.get_last_batch().await;
-        let migrations = self.migrations().await;
         manager
             .transaction(|trans| async move {
-                for name in collection.keys() {
-                    for entry in &migrations {
-                        if entry.id() == name.as_str() {
+                for ran in &entries {
+                    match migrations.iter().find(|m| m.id() == ran.name()) {
+                        Some(entry) => {
                             tracing::debug!("migrating {} down", entry.id());
                             entry.down(&trans).await?;
                         }
+                        None => {
+                            tracing::warn!("migration {} is no longer registered", ran.name());
+                        }
                     }
-                }
-
-                if let Some((name, _)) = collection.iter().next() {
-                    _ = repo.delete(&name).await;
+                    repo.delete(ran.name()).await?;
                 }
                 Ok(())
             })
             .await
     }
 
-    pub async fn refresh(&self, manager: &Manager) -> Result<(), anyhow::Error> {
-        self.down(manager).await?;
-        self.up(manager).await
-    }
-
-    pub async fn reset(&self, manager: &Manager) -> Result<(), anyhow::Error> {
-        loop {
-            let repo = self.repo(manager).await;
-            let collection = repo.get_last_batch().await;
-            if collection.is_empty() {
-                break;
-            }
-            self.down(manager).await?;
-        }
-
-        manager.drop_table(TABLE_NAME).await
-    }
-
     async fn repo(&self, manager: &Manager) -> MigrationRepository {
         let repo = MigrationRepository::new(manager.clone());
         if let Err(e) = repo.init().await
@@ -129,10 +229,18 @@ impl Migrator {
     }
 
     async fn migrations(&self) -> Vec<Box<dyn Migration>> {
-        let mut migrations = Vec::with_capacity(100);
+        self.migrations_by_extension()
+            .await
+            .into_iter()
+            .flat_map(|(_, list)| list)
+            .collect()
+    }
+
+    async fn migrations_by_extension(&self) -> Vec<(String, Vec<Box<dyn Migration>>)> {
+        let mut migrations = Vec::new();
         ExtensionManager::extensions(|ext| {
             if let Some(m) = ext.migrations(&self.context) {
-                migrations.extend(m);
+                migrations.push((ext.id().to_string(), m));
             }
         })
         .await;
@@ -142,12 +250,110 @@ impl Migrator {
 
 impl From<(String, ArgMatches)> for MigrateAction {
     fn from(value: (String, ArgMatches)) -> Self {
-        match value.0.as_str() {
-            "up" => MigrateAction::Up,
-            "down" => MigrateAction::Down,
+        let (name, matches) = value;
+        match name.as_str() {
+            "up" => MigrateAction::Up {
+                pretend: matches.get_flag("pretend"),
+            },
+            "down" => MigrateAction::Down {
+                step: matches.get_one::<usize>("step").cloned(),
+            },
+            "rollback" => MigrateAction::Rollback,
+            "status" => MigrateAction::Status,
             "refresh" => MigrateAction::Refresh,
             "reset" => MigrateAction::Reset,
             _ => MigrateAction::Unknown,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::connector::sqlite::make_sqlite_in_memory_manager;
+
+    struct CreateTable(&'static str);
+
+    #[async_trait::async_trait]
+    impl Migration for CreateTable {
+        async fn up(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+            manager
+                .create_table_schema(self.0, |table| {
+                    table.id(None);
+                    table.string("name");
+                })
+                .await
+        }
+
+        async fn down(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+            manager.drop_table(self.0).await
+        }
+
+        fn id(&self) -> String {
+            format!("create_{}", self.0)
+        }
+    }
+
+    fn migrations(tables: &[&'static str]) -> Vec<Box<dyn Migration>> {
+        tables
+            .iter()
+            .map(|t| Box::new(CreateTable(t)) as Box<dyn Migration>)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_batches_and_steps() {
+        let manager = make_sqlite_in_memory_manager().await;
+        let migrator = Migrator::new(Some(Context::new().await)).await;
+        let repo = MigrationRepository::new(manager.clone());
+
+        migrator
+            .run_up(&manager, migrations(&["posts", "comments"]))
+            .await
+            .unwrap();
+        migrator
+            .run_up(&manager, migrations(&["posts", "comments", "tags"]))
+            .await
+            .unwrap();
+
+        let batches = repo
+            .all()
+            .await
+            .iter()
+            .map(|m| (m.name().to_string(), m.batch()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            batches,
+            vec![
+                ("create_posts".to_string(), 1),
+                ("create_comments".to_string(), 1),
+                ("create_tags".to_string(), 2)
+            ]
+        );
+
+        let pending = migrator
+            .run_pretend(&manager, migrations(&["posts", "likes"]))
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "create_likes");
+        assert!(pending[0].1[0].starts_with("CREATE TABLE `likes`"));
+        assert!(!manager.has_table("likes").await.unwrap());
+
+        let all = || migrations(&["posts", "comments", "tags"]);
+        migrator
+            .run_down(&manager, repo.get_latest(2).await, all())
+            .await
+            .unwrap();
+        assert!(manager.has_table("posts").await.unwrap());
+        assert!(!manager.has_table("comments").await.unwrap());
+        assert!(!manager.has_table("tags").await.unwrap());
+
+        migrator
+            .run_down(&manager, repo.get_last_batch().await, all())
+            .await
+            .unwrap();
+        assert!(!manager.has_table("posts").await.unwrap());
+        assert!(repo.all().await.is_empty());
+    }
+}
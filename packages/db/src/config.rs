@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::anyhow;
 use dirtybase_contract::{
@@ -8,7 +8,10 @@ use dirtybase_contract::{
 
 use crate::connector::sqlite::sqlite_connector::SQLITE_KIND;
 
-use super::base::schema::{ClientType, DatabaseKind};
+use super::base::{
+    replica::{ReplicaBalance, ReplicaOptions},
+    schema::{ClientType, DatabaseKind},
+};
 
 pub type ConfigSet = HashMap<ClientType, ConnectionConfig>;
 pub type ConfigCollection = HashMap<String, ConfigSet>;
@@ -58,6 +61,11 @@ impl DbConfig {
         self.collection.get(self.default.as_str()).cloned()
     }
 
+    /// Name of the default connection set
+    pub fn default_name(&self) -> &str {
+        self.default.as_str()
+    }
+
     pub fn get_set(&self, name: &str) -> Option<ConfigSet> {
         self.collection.get(name).cloned()
     }
@@ -71,11 +79,24 @@ pub struct ConnectionConfig {
     #[serde(default)]
     pub client_type: ClientType,
     pub url: String,
+    /// More read replicas, balanced along with `url`
+    pub urls: Option<Vec<String>>,
     pub max: u32,
     pub sticky: Option<bool>,
     pub sticky_duration: Option<i64>,
     pub foreign_key: Option<bool>,
     pub busy_timeout: Option<u64>,
+    /// Queries that take at least this many milliseconds are logged as slow
+    pub slow_query_ms: Option<u64>,
+    pub balance: Option<ReplicaBalance>,
+    /// Consecutive connection errors before a read replica is taken out
+    pub eject_after: Option<u32>,
+    /// Seconds an ejected read replica stays out
+    pub eject_duration: Option<u64>,
+    /// Seconds a read replica may lag behind before reads go to the writer
+    pub max_replica_lag: Option<u64>,
+    /// Seconds between two health checks of the read replicas
+    pub health_check_interval: Option<u64>,
     pub custom: Option<HashMap<String, String>>,
 }
 
@@ -88,11 +109,18 @@ impl Default for ConnectionConfig {
             kind: SQLITE_KIND.into(),
             client_type: ClientType::Write,
             url: "sqlite::memory:".to_string(),
+            urls: None,
             max: 2,
             sticky: Some(true),
             sticky_duration: Some(10),
             foreign_key: Some(true),
             busy_timeout: Some(60),
+            slow_query_ms: None,
+            balance: None,
+            eject_after: None,
+            eject_duration: None,
+            max_replica_lag: None,
+            health_check_interval: None,
             custom: None,
         }
     }
@@ -107,6 +135,30 @@ impl ConnectionConfig {
         &self.kind
     }
 
+    /// `url` followed by the extra replica urls
+    pub fn replica_urls(&self) -> Vec<String> {
+        let mut urls = vec![self.url.clone()];
+        urls.extend(self.urls.iter().flatten().cloned());
+        urls
+    }
+
+    pub fn replica_options(&self) -> ReplicaOptions {
+        let defaults = ReplicaOptions::default();
+        ReplicaOptions {
+            balance: self.balance.unwrap_or(defaults.balance),
+            eject_after: self.eject_after.unwrap_or(defaults.eject_after),
+            eject_duration: self
+                .eject_duration
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.eject_duration),
+            max_lag: self.max_replica_lag.map(Duration::from_secs),
+            health_check_interval: self
+                .health_check_interval
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.health_check_interval),
+        }
+    }
+
     pub async fn new_set() -> ConfigSet {
         return Self::set_from(&DirtyConfig::new()).await;
     }
@@ -2,3 +2,50 @@ pub mod mariadb;
 pub mod mysql;
 pub mod postgres;
 pub mod sqlite;
+
+/// Inserts a `FieldValue::Null` and checks that the database stored a NULL
+#[cfg(test)]
+pub(crate) async fn assert_null_round_trip(manager: &crate::base::manager::Manager) {
+    use crate::{column_value_builder::ColumnAndValueBuilder, field_values::FieldValue};
+
+    let table = "null_round_trip";
+    _ = manager.drop_table(table).await;
+    manager
+        .create_table_schema(table, |table| {
+            table.id(None);
+            table.string("title");
+            table.text("body").nullable();
+        })
+        .await
+        .unwrap();
+    manager
+        .insert(
+            table,
+            ColumnAndValueBuilder::new()
+                .add("title", "draft")
+                .add("body", FieldValue::Null)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let total = manager
+        .select_from_table(table, |q| {
+            q.is_null("body");
+        })
+        .count()
+        .await
+        .unwrap();
+    let row = manager
+        .select_from_table(table, |q| {
+            q.select("body");
+        })
+        .fetch_one()
+        .await
+        .unwrap()
+        .unwrap();
+    _ = manager.drop_table(table).await;
+
+    assert_eq!(total, 1);
+    assert_eq!(row.fields_ref().get("body"), Some(&FieldValue::Null));
+}
@@ -1,7 +1,7 @@
 use anyhow::anyhow;
 use async_trait::async_trait;
-use sqlx::{MySql, Pool, mysql::MySqlPoolOptions};
-use std::{collections::HashMap, sync::Arc};
+use sqlx::{MySql, Pool, Row, mysql::MySqlPoolOptions};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use crate::{
     base::{
@@ -10,6 +10,7 @@ use crate::{
     },
     config::{ConfigSet, ConnectionConfig},
     connector::mariadb::LOG_TARGET,
+    make_replica_pool,
     pool_manager_resolver::DbPoolManagerResolver,
 };
 
@@ -46,6 +47,32 @@ impl ConnectionPoolTrait for MariadbPoolManager {
         }
         tracing::trace!(target: LOG_TARGET,"mariadb connection closed: {}", self.db_pool.is_closed());
     }
+
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        sqlx::query("SELECT 1")
+            .execute(self.db_pool.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn replica_lag(&self) -> Result<Option<Duration>, anyhow::Error> {
+        let Some(row) = sqlx::query("SHOW SLAVE STATUS")
+            .fetch_optional(self.db_pool.as_ref())
+            .await?
+        else {
+            return Ok(None);
+        };
+        match row.try_get_unchecked::<Option<i64>, _>("Seconds_Behind_Master")? {
+            Some(seconds) => Ok(Some(Duration::from_secs(seconds.max(0) as u64))),
+            None => Err(anyhow!("replication is not running")),
+        }
+    }
+
+    fn in_use(&self) -> u32 {
+        self.db_pool
+            .size()
+            .saturating_sub(self.db_pool.num_idle() as u32)
+    }
 }
 
 pub async fn resolve(
@@ -54,6 +81,24 @@ pub async fn resolve(
     let mut pools: HashMap<ClientType, Box<dyn ConnectionPoolTrait>> = HashMap::new();
     for (client_type, config) in config_set.iter() {
         if MARIADB_KIND == config.kind_ref().as_str() && config.enable {
+            if *client_type == ClientType::Read {
+                let replicas = make_replica_pool(config, |config, lazy| async move {
+                    let db_pool = if lazy {
+                        db_connect_lazy(&config)?
+                    } else {
+                        db_connect(&config).await?
+                    };
+                    Ok(Box::new(MariadbPoolManager {
+                        db_pool: Arc::new(db_pool),
+                    }) as Box<dyn ConnectionPoolTrait>)
+                })
+                .await;
+                if let Some(pool) = replicas {
+                    pools.insert(ClientType::Read, pool);
+                }
+                continue;
+            }
+
             match db_connect(config).await {
                 Ok(db_pool) => {
                     pools.insert(
@@ -77,6 +122,13 @@ pub async fn resolve(
     }
 }
 
+/// Creates the pool without connecting, the connections are made when they are used
+pub fn db_connect_lazy(config: &ConnectionConfig) -> anyhow::Result<Pool<MySql>> {
+    Ok(MySqlPoolOptions::new()
+        .max_connections(config.max)
+        .connect_lazy(&config.url)?)
+}
+
 pub async fn db_connect(config: &ConnectionConfig) -> anyhow::Result<Pool<MySql>> {
     match MySqlPoolOptions::new()
         .max_connections(config.max)
@@ -15,11 +15,17 @@ use std::{collections::HashMap, sync::Arc};
 use crate::{
     base::{
         column::{ColumnBlueprint, ColumnDefault, ColumnType},
+        full_text::{FullTextMatch, MatchMode},
+        introspection::{ColumnDescription, ForeignKeyDescription, TableDescription},
+        json_path::JsonPath,
         query::{QueryAction, QueryBuilder},
         query_conditions::Condition,
+        query_log::StatementRecorder,
         query_operators::Operator,
-        schema::{DatabaseKind, RelationalDbTrait, SchemaManagerTrait},
+        schema::{DatabaseKind, ExecuteResult, PretendLog, RelationalDbTrait, SchemaManagerTrait},
         table::TableBlueprint,
+        union_builder::Distinct,
+        window_function::WindowFunction,
     },
     field_values::FieldValue,
     query_values::QueryValue,
@@ -32,6 +38,8 @@ pub const MYSQL_KIND: &str = "mysql";
 pub struct MySqlSchemaManager {
     db_pool: Arc<Pool<MySql>>,
     trans: Option<MySqlTransaction<'static>>,
+    pretend: Option<PretendLog>,
+    recorder: Option<StatementRecorder>,
 }
 
 impl MySqlSchemaManager {
@@ -39,6 +47,8 @@ impl MySqlSchemaManager {
         Self {
             db_pool,
             trans: None,
+            pretend: None,
+            recorder: None,
         }
     }
 
@@ -46,8 +56,20 @@ impl MySqlSchemaManager {
         Self {
             db_pool,
             trans: Some(trans),
+            pretend: None,
+            recorder: None,
         }
     }
+
+    /// The name of the database the pool connects to
+    fn database_name(&self) -> String {
+        self.db_pool
+            .connect_options()
+            .as_ref()
+            .get_database()
+            .unwrap_or_default()
+            .to_string()
+    }
 }
 
 #[async_trait]
@@ -113,6 +135,99 @@ impl SchemaManagerTrait for MySqlSchemaManager {
         }
     }
 
+    async fn tables(&mut self) -> Result<Vec<String>, anyhow::Error> {
+        let query = "SELECT CAST(table_name AS CHAR) AS name FROM INFORMATION_SCHEMA.TABLES WHERE table_schema = ? AND table_type = 'BASE TABLE' ORDER BY table_name";
+
+        Ok(sqlx::query(query)
+            .bind(self.database_name())
+            .map(|row: MySqlRow| row.get::<String, _>("name"))
+            .fetch_all(self.db_pool.as_ref())
+            .await?)
+    }
+
+    async fn describe_table(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<TableDescription>, anyhow::Error> {
+        if !self.has_table(name).await? {
+            return Ok(None);
+        }
+        let database = self.database_name();
+        let mut table = TableDescription::new(name);
+
+        table.columns = sqlx::query(
+            "SELECT CAST(column_name AS CHAR) AS name, CAST(column_type AS CHAR) AS data_type, CAST(is_nullable AS CHAR) AS nullable, CAST(column_default AS CHAR) AS default_value, CAST(column_key AS CHAR) AS column_key, CAST(extra AS CHAR) AS extra FROM INFORMATION_SCHEMA.COLUMNS WHERE table_schema = ? AND table_name = ? ORDER BY ordinal_position",
+        )
+        .bind(&database)
+        .bind(name)
+        .map(|row: MySqlRow| {
+            let data_type = row.get::<String, _>("data_type");
+            ColumnDescription {
+                name: row.get("name"),
+                column_type: ColumnType::from_sql_type(&data_type),
+                data_type,
+                is_nullable: row.get::<String, _>("nullable") == "YES",
+                is_primary: row.get::<String, _>("column_key") == "PRI",
+                auto_increment: row.get::<String, _>("extra").contains("auto_increment"),
+                default: row.get("default_value"),
+            }
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let indexes = sqlx::query(
+            "SELECT CAST(index_name AS CHAR) AS name, CAST(column_name AS CHAR) AS column_name, CAST(non_unique AS SIGNED) AS non_unique FROM INFORMATION_SCHEMA.STATISTICS WHERE table_schema = ? AND table_name = ? AND index_type <> 'FULLTEXT' ORDER BY index_name, seq_in_index",
+        )
+        .bind(&database)
+        .bind(name)
+        .map(|row: MySqlRow| {
+            (
+                row.get::<String, _>("name"),
+                // functional key parts have no column
+                row.get::<Option<String>, _>("column_name"),
+                row.get::<i64, _>("non_unique") == 0,
+            )
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+        for (index, column, unique) in indexes {
+            let Some(column) = column else {
+                continue;
+            };
+            let primary = index == "PRIMARY";
+            table.push_index_column(&index, &column, unique, primary);
+        }
+
+        table.foreign_keys = sqlx::query(
+            "SELECT CAST(k.column_name AS CHAR) AS column_name, CAST(k.referenced_table_name AS CHAR) AS foreign_table, CAST(k.referenced_column_name AS CHAR) AS foreign_column, CAST(r.delete_rule AS CHAR) AS on_delete FROM INFORMATION_SCHEMA.KEY_COLUMN_USAGE k JOIN INFORMATION_SCHEMA.REFERENTIAL_CONSTRAINTS r ON r.constraint_schema = k.constraint_schema AND r.constraint_name = k.constraint_name WHERE k.table_schema = ? AND k.table_name = ? AND k.referenced_table_name IS NOT NULL ORDER BY k.constraint_name, k.ordinal_position",
+        )
+        .bind(&database)
+        .bind(name)
+        .map(|row: MySqlRow| ForeignKeyDescription {
+            column: row.get("column_name"),
+            foreign_table: row.get("foreign_table"),
+            foreign_column: row.get("foreign_column"),
+            on_delete: row.get("on_delete"),
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(Some(table))
+    }
+
+    async fn database_size(&mut self) -> Result<Option<u64>, anyhow::Error> {
+        let query = "SELECT CAST(SUM(data_length + index_length) AS SIGNED) AS size FROM INFORMATION_SCHEMA.TABLES WHERE table_schema = ?";
+
+        Ok(sqlx::query(query)
+            .bind(self.database_name())
+            .map(|row: MySqlRow| {
+                row.get::<Option<i64>, _>("size")
+                    .map(|size| size.max(0) as u64)
+            })
+            .fetch_one(self.db_pool.as_ref())
+            .await?)
+    }
+
     async fn stream_result(
         &mut self,
         query_builder: &QueryBuilder,
@@ -120,6 +235,7 @@ impl SchemaManagerTrait for MySqlSchemaManager {
     ) -> Result<(), anyhow::Error> {
         let mut params = MySqlArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
 
@@ -141,7 +257,7 @@ impl SchemaManagerTrait for MySqlSchemaManager {
     async fn drop_table(&mut self, name: &str) -> Result<(), anyhow::Error> {
         if self.has_table(name).await? {
             let query = QueryBuilder::new(name, QueryAction::DropTable);
-            return self.do_execute(query).await;
+            self.do_execute(query).await?;
         }
 
         Ok(())
@@ -151,7 +267,7 @@ impl SchemaManagerTrait for MySqlSchemaManager {
         self.do_apply(table).await
     }
 
-    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         self.do_execute(query).await
     }
 
@@ -163,6 +279,7 @@ impl SchemaManagerTrait for MySqlSchemaManager {
 
         let mut params = MySqlArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
         let mut rows = query.fetch(self.db_pool.as_ref());
@@ -192,6 +309,7 @@ impl SchemaManagerTrait for MySqlSchemaManager {
     ) -> Result<Option<ColumnAndValue>, anyhow::Error> {
         let mut params = MySqlArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
         return match query.fetch_optional(self.db_pool.as_ref()).await {
@@ -203,7 +321,19 @@ impl SchemaManagerTrait for MySqlSchemaManager {
         };
     }
 
+    fn pretend(&mut self, log: PretendLog) {
+        self.pretend = Some(log);
+    }
+
+    fn record_statements(&mut self, recorder: StatementRecorder) {
+        self.recorder = Some(recorder);
+    }
+
     async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -238,8 +368,15 @@ impl SchemaManagerTrait for MySqlSchemaManager {
         sql: &str,
         params: Vec<FieldValue>,
     ) -> Result<u64, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(0);
+        }
+
         let mut query = sqlx::query(sql);
         for p in params {
+            if let Some(recorder) = &self.recorder {
+                recorder.bind(&p);
+            }
             query = query.bind(p.to_string());
         }
 
@@ -266,6 +403,10 @@ impl SchemaManagerTrait for MySqlSchemaManager {
     }
 
     async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -297,6 +438,23 @@ impl SchemaManagerTrait for MySqlSchemaManager {
 }
 
 impl MySqlSchemaManager {
+    /// Reports the statement to the query log
+    fn record_sql(&self, sql: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.sql(sql);
+        }
+    }
+
+    /// Records the statement when pretending. Returns true when it must not be run
+    fn pretended(&self, sql: &str) -> bool {
+        self.record_sql(sql);
+        if let Some(log) = &self.pretend {
+            log.record(sql);
+            return true;
+        }
+        false
+    }
+
     async fn do_apply(&self, table: TableBlueprint) -> Result<(), anyhow::Error> {
         return if table.view_query.is_some() {
             // working with view table
@@ -307,7 +465,7 @@ impl MySqlSchemaManager {
         };
     }
 
-    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         let mut params = MySqlArguments::default();
 
         let mut sql;
@@ -393,6 +551,10 @@ impl MySqlSchemaManager {
             }
         }
 
+        if self.pretended(&sql) {
+            return Ok(ExecuteResult::default());
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query_with(&sql, params).execute(&mut *trans).await;
             if result.is_ok() {
@@ -415,7 +577,8 @@ impl MySqlSchemaManager {
         match result {
             Ok(r) => {
                 log::debug!("{} result: {:#?}", query.action(), r);
-                Ok(())
+                let last_insert_id = Some(r.last_insert_id()).filter(|id| *id > 0);
+                Ok(ExecuteResult::new(r.rows_affected(), last_insert_id))
             }
             Err(e) => {
                 log::error!("{} failed: {}", query.action(), e);
@@ -431,6 +594,10 @@ impl MySqlSchemaManager {
 
             let query = format!("CREATE OR REPLACE VIEW `{}` AS ({})", &table.name, sql);
 
+            if self.pretended(&query) {
+                return Ok(());
+            }
+
             let result = sqlx::query_with(&query, params)
                 .execute(self.db_pool.as_ref())
                 .await;
@@ -478,34 +645,37 @@ impl MySqlSchemaManager {
             query = format!("{query} ENGINE='InnoDB';");
         }
 
-        let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
+        // an update may only change the indexes
+        if (table.is_new() || !columns.is_empty()) && !self.pretended(&query) {
+            let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
 
-        match result {
-            Ok(_) => {
-                log::info!(
-                    "Table '{}' {} successfully",
-                    &table.name,
-                    if table.is_new() { "created" } else { "updated" }
-                );
-            }
-            Err(e) => {
-                let name;
-                let action;
+            match result {
+                Ok(_) => {
+                    log::info!(
+                        "Table '{}' {} successfully",
+                        &table.name,
+                        if table.is_new() { "created" } else { "updated" }
+                    );
+                }
+                Err(e) => {
+                    let name;
+                    let action;
 
-                if table.is_new() {
-                    action = "create";
-                    name = table.new_name.unwrap_or(table.name.clone())
-                } else {
-                    action = "update";
-                    name = table.name.clone();
+                    if table.is_new() {
+                        action = "create";
+                        name = table.new_name.unwrap_or(table.name.clone())
+                    } else {
+                        action = "update";
+                        name = table.name.clone();
+                    }
+                    log::error!("Could not {} table {}: {}", action, name, &e);
+                    return Err(anyhow::anyhow!(
+                        "Could not {} table {}: {}",
+                        action,
+                        name,
+                        &e
+                    ));
                 }
-                log::error!("Could not {} table {}: {}", action, name, &e);
-                return Err(anyhow::anyhow!(
-                    "Could not {} table {}: {}",
-                    action,
-                    name,
-                    &e
-                ));
             }
         }
 
@@ -526,6 +696,19 @@ impl MySqlSchemaManager {
                             );
                         }
                     }
+                    IndexType::FullText(index) => {
+                        if index.delete_index() {
+                            sql =
+                                format!("DROP INDEX {}_fulltext ON {}", index.name(), &table.name);
+                        } else {
+                            sql = format!(
+                                "CREATE FULLTEXT INDEX {}_fulltext ON {} ({})",
+                                index.name(),
+                                &table.name,
+                                index.concat_columns()
+                            );
+                        }
+                    }
                     IndexType::Unique(index) => {
                         if index.delete_index() {
                             sql = format!("DROP INDEX {}", index.name());
@@ -540,6 +723,10 @@ impl MySqlSchemaManager {
                     }
                 }
 
+                if self.pretended(&sql) {
+                    continue;
+                }
+
                 let index_result = sqlx::query(&sql).execute(self.db_pool.as_ref()).await;
                 match index_result {
                     Ok(_) => log::info!("table index created"),
@@ -657,10 +844,18 @@ impl MySqlSchemaManager {
         query: &QueryBuilder,
         params: &mut MySqlArguments,
     ) -> Result<String, anyhow::Error> {
-        let mut sql = "SELECT".to_owned();
+        // common table expressions
+        let mut sql = format!("{}SELECT", self.build_ctes(query, params)?);
+
+        // distinct
+        match query.distinct_by() {
+            Some(Distinct::Rows) => sql = format!("{sql} DISTINCT"),
+            Some(Distinct::On(_)) => return Err(anyhow!("mysql does not support DISTINCT ON")),
+            None => (),
+        }
 
         // fields
-        if let QueryAction::Query { columns } = query.action() {
+        if let QueryAction::Query { columns, .. } = query.action() {
             if let Some(fields) = columns {
                 let mut col_names = Vec::new();
                 for a_field in fields {
@@ -687,6 +882,9 @@ impl MySqlSchemaManager {
 
         // from
         sql = format!("{} FROM {}", sql, query.table());
+        if let Some(alias) = query.table_alias() {
+            sql = format!("{} AS {}", sql, alias);
+        }
 
         // joins
         sql = format!("{} {}", sql, self.build_join(query, params)?);
@@ -695,14 +893,23 @@ impl MySqlSchemaManager {
         sql = format!("{} {}", sql, self.build_where_clauses(query, params)?);
 
         // group by
+        if let Some(group) = query.grouped_by()
+            && !group.is_empty()
+        {
+            sql = format!("{sql} {group}");
+        }
+
+        // having
+        sql = format!("{} {}", sql, self.build_having_clauses(query, params)?);
+
+        // unions
+        sql = format!("{} {}", sql, self.build_unions(query, params)?);
 
         // order by
         if let Some(order) = self.build_order_by(query) {
             sql = format!("{sql} {order}");
         }
 
-        // having
-
         // limit
         if let Some(cursor) = query.cursor_by() {
             sql = format!("{sql} {}", cursor.limit());
@@ -765,6 +972,74 @@ impl MySqlSchemaManager {
         Ok(wheres)
     }
 
+    fn build_having_clauses(
+        &self,
+        query: &QueryBuilder,
+        params: &mut MySqlArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut havings = "".to_owned();
+        for having_join in query.having_clauses() {
+            havings = having_join.as_clause(
+                &havings,
+                &self.transform_condition(having_join.condition(), params)?,
+            );
+        }
+
+        if !havings.is_empty() {
+            havings = format!("HAVING {havings}");
+        }
+
+        Ok(havings)
+    }
+
+    fn build_unions(
+        &self,
+        query: &QueryBuilder,
+        params: &mut MySqlArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut sql = "".to_owned();
+        for (index, a_union) in query.unions().iter().enumerate() {
+            let union_sql = self.build_query(a_union.query(), params)?;
+            sql = if a_union.needs_wrapping() {
+                format!(
+                    "{sql} {} SELECT * FROM ({union_sql}) AS union_{index}",
+                    a_union.union_type()
+                )
+            } else {
+                format!("{sql} {} {union_sql}", a_union.union_type())
+            };
+        }
+
+        Ok(sql)
+    }
+
+    fn build_ctes(
+        &self,
+        query: &QueryBuilder,
+        params: &mut MySqlArguments,
+    ) -> Result<String, anyhow::Error> {
+        if query.ctes().is_empty() {
+            return Ok("".to_owned());
+        }
+
+        let mut ctes = Vec::new();
+        for a_cte in query.ctes() {
+            ctes.push(format!(
+                "{} AS ({})",
+                a_cte.name(),
+                self.build_query(a_cte.query(), params)?
+            ));
+        }
+
+        let recursive = if query.ctes().iter().any(|c| c.is_recursive()) {
+            " RECURSIVE"
+        } else {
+            ""
+        };
+
+        Ok(format!("WITH{recursive} {} ", ctes.join(", ")))
+    }
+
     fn build_order_by(&self, query: &QueryBuilder) -> Option<String> {
         if let Some(cursor) = query.cursor_by() {
             Some(cursor.order().to_string())
@@ -778,6 +1053,10 @@ impl MySqlSchemaManager {
         condition: &Condition,
         params: &mut MySqlArguments,
     ) -> Result<String, anyhow::Error> {
+        if let Operator::Match(search) = condition.operator() {
+            return self.match_condition(search, params);
+        }
+
         let placeholder = match condition.value() {
             QueryValue::SubQuery(sub) => self.build_query(sub, params)?,
             QueryValue::ColumnName(name) => name.clone(),
@@ -793,8 +1072,7 @@ impl MySqlSchemaManager {
             }
             _ => {
                 self.transform_value(condition.value(), params)?;
-                if *condition.operator() == Operator::In || *condition.operator() == Operator::NotIn
-                {
+                if condition.operator().is_list() {
                     let length = match &condition.value() {
                         QueryValue::Field(FieldValue::Array(v)) => v.len(),
                         _ => 1,
@@ -809,9 +1087,58 @@ impl MySqlSchemaManager {
             }
         };
 
-        Ok(condition
-            .operator()
-            .as_clause(condition.column(), &placeholder))
+        let column = condition.column();
+        Ok(match condition.operator() {
+            Operator::Json(path, operator) => {
+                operator.as_clause(&self.json_extract(column, path)?, &placeholder)
+            }
+            Operator::JsonContains(path) => format!(
+                "JSON_CONTAINS({column}, {placeholder}, '{}')",
+                JsonPath::parse(path)?
+            ),
+            Operator::JsonLength(path, operator) => operator.as_clause(
+                &format!("JSON_LENGTH({column}, '{}')", JsonPath::parse(path)?),
+                &placeholder,
+            ),
+            operator => operator.as_clause(column, &placeholder),
+        })
+    }
+
+    fn json_extract(&self, column: &str, path: &str) -> Result<String, anyhow::Error> {
+        Ok(format!(
+            "JSON_UNQUOTE(JSON_EXTRACT({column}, '{}'))",
+            JsonPath::parse(path)?
+        ))
+    }
+
+    fn match_condition(
+        &self,
+        search: &FullTextMatch,
+        params: &mut MySqlArguments,
+    ) -> Result<String, anyhow::Error> {
+        let term = match search.mode() {
+            MatchMode::Natural | MatchMode::Boolean => search.term().to_string(),
+            MatchMode::Phrase => format!("\"{}\"", search.words().join(" ")),
+        };
+        self.field_value_to_args(&FieldValue::String(term), params)?;
+
+        let mode = match search.mode() {
+            MatchMode::Natural => "NATURAL LANGUAGE",
+            MatchMode::Boolean | MatchMode::Phrase => "BOOLEAN",
+        };
+        Ok(format!(
+            "MATCH ({}) AGAINST (? IN {mode} MODE)",
+            search.columns().join(",")
+        ))
+    }
+
+    fn relevance(
+        &self,
+        search: &FullTextMatch,
+        params: &mut MySqlArguments,
+    ) -> Result<String, anyhow::Error> {
+        // MySQL returns the relevance of the match itself
+        self.match_condition(search, params)
     }
 
     fn transform_value(
@@ -988,6 +1315,9 @@ impl MySqlSchemaManager {
         field: &FieldValue,
         params: &mut MySqlArguments,
     ) -> Result<(), anyhow::Error> {
+        if let Some(recorder) = &self.recorder {
+            recorder.bind(field);
+        }
         build_field_value_to_args(field, params)
     }
 
@@ -1065,6 +1395,23 @@ impl MySqlSchemaManager {
                         Ok(format!("({aggregate}({sql})) as '{alias}'"))
                     }
                 }
+                QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+                QueryColumnName::Json(n, path) => {
+                    let sql = self.json_extract(n, path)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as '{alias}'"))
+                    }
+                }
+                QueryColumnName::Relevance(search) => {
+                    let sql = self.relevance(search, params)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as '{alias}'"))
+                    }
+                }
             };
         }
         match column.name() {
@@ -1089,6 +1436,32 @@ impl MySqlSchemaManager {
                     Ok(format!("({sql}) as '{alias}'"))
                 }
             }
+            QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+            QueryColumnName::Json(n, path) => {
+                let sql = self.json_extract(n, path)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as '{alias}'"))
+                }
+            }
+            QueryColumnName::Relevance(search) => {
+                let sql = self.relevance(search, params)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as '{alias}'"))
+                }
+            }
+        }
+    }
+
+    fn window_to_string(&self, window: &WindowFunction, alias: &str) -> String {
+        let sql = window.as_clause();
+        if alias.is_empty() {
+            sql
+        } else {
+            format!("{sql} as '{alias}'")
         }
     }
 }
@@ -1155,7 +1528,7 @@ fn build_field_value_to_args(
         }
 
         FieldValue::Null => {
-            _ = Arguments::add(params, "NULL");
+            _ = Arguments::add(params, None::<String>);
         }
         FieldValue::NotSet => (),
         FieldValue::Failable { field, error } => {
@@ -1168,3 +1541,23 @@ fn build_field_value_to_args(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{config::ConnectionConfig, connector::mysql::make_mysql_manager};
+
+    use super::*;
+
+    // needs a running server: DTY_TEST_MYSQL_URL=... cargo test -- --ignored
+    #[tokio::test]
+    #[ignore]
+    async fn test_null_round_trip() {
+        let manager = make_mysql_manager(ConnectionConfig {
+            kind: MYSQL_KIND.into(),
+            url: std::env::var("DTY_TEST_MYSQL_URL").expect("DTY_TEST_MYSQL_URL is not set"),
+            ..Default::default()
+        })
+        .await;
+        crate::connector::assert_null_round_trip(&manager).await;
+    }
+}
@@ -1,10 +1,15 @@
 use crate::base::{
     column::{ColumnBlueprint, ColumnDefault, ColumnType},
+    full_text::{FullTextMatch, MatchMode},
+    introspection::{ColumnDescription, ForeignKeyDescription, TableDescription},
+    json_path::JsonPath,
     query::{QueryAction, QueryBuilder},
     query_conditions::Condition,
+    query_log::StatementRecorder,
     query_operators::Operator,
-    schema::{DatabaseKind, RelationalDbTrait, SchemaManagerTrait},
-    table::TableBlueprint,
+    schema::{DatabaseKind, ExecuteResult, PretendLog, RelationalDbTrait, SchemaManagerTrait},
+    table::{ID_FIELD, INTERNAL_ID_FIELD, TableBlueprint},
+    window_function::WindowFunction,
 };
 use crate::{field_values::FieldValue, query_values::QueryValue, types::ColumnAndValue};
 use anyhow::anyhow;
@@ -27,6 +32,8 @@ pub const POSTGRES_KIND: &str = "postgres";
 pub struct PostgresSchemaManager {
     db_pool: Arc<Pool<Postgres>>,
     trans: Option<PgTransaction<'static>>,
+    pretend: Option<PretendLog>,
+    recorder: Option<StatementRecorder>,
 }
 
 impl PostgresSchemaManager {
@@ -34,6 +41,8 @@ impl PostgresSchemaManager {
         Self {
             db_pool,
             trans: None,
+            pretend: None,
+            recorder: None,
         }
     }
 
@@ -41,6 +50,8 @@ impl PostgresSchemaManager {
         Self {
             db_pool,
             trans: Some(trans),
+            pretend: None,
+            recorder: None,
         }
     }
 }
@@ -98,6 +109,97 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         result.map_err(|e| anyhow::anyhow!(e))
     }
 
+    async fn tables(&mut self) -> Result<Vec<String>, anyhow::Error> {
+        let query = "SELECT table_name::text AS name FROM information_schema.tables WHERE table_schema = current_schema() AND table_type = 'BASE TABLE' ORDER BY table_name";
+
+        Ok(sqlx::query(query)
+            .map(|row: PgRow| row.get::<String, _>("name"))
+            .fetch_all(self.db_pool.as_ref())
+            .await?)
+    }
+
+    async fn describe_table(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<TableDescription>, anyhow::Error> {
+        if !self.has_table(name).await? {
+            return Ok(None);
+        }
+        let mut table = TableDescription::new(name);
+
+        let indexes = sqlx::query(
+            "SELECT i.relname::text AS name, a.attname::text AS column_name, ix.indisunique AS is_unique, ix.indisprimary AS is_primary FROM pg_index ix JOIN pg_class t ON t.oid = ix.indrelid JOIN pg_class i ON i.oid = ix.indexrelid JOIN pg_namespace n ON n.oid = t.relnamespace JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = ANY(ix.indkey) WHERE n.nspname = current_schema() AND t.relname = $1 ORDER BY i.relname, array_position(ix.indkey::int2[], a.attnum)",
+        )
+        .bind(name)
+        .map(|row: PgRow| {
+            (
+                row.get::<String, _>("name"),
+                row.get::<String, _>("column_name"),
+                row.get::<bool, _>("is_unique"),
+                row.get::<bool, _>("is_primary"),
+            )
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+        for (index, column, unique, primary) in indexes {
+            table.push_index_column(&index, &column, unique, primary);
+        }
+
+        let primary_columns = table
+            .indexes
+            .iter()
+            .filter(|index| index.is_primary)
+            .flat_map(|index| index.columns.clone())
+            .collect::<Vec<_>>();
+
+        table.columns = sqlx::query(
+            "SELECT column_name::text AS name, (CASE WHEN character_maximum_length IS NULL THEN data_type ELSE data_type || '(' || character_maximum_length || ')' END)::text AS data_type, is_nullable::text AS nullable, column_default::text AS default_value FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position",
+        )
+        .bind(name)
+        .map(|row: PgRow| {
+            let data_type = row.get::<String, _>("data_type");
+            let column_name = row.get::<String, _>("name");
+            let default = row.get::<Option<String>, _>("default_value");
+            ColumnDescription {
+                column_type: ColumnType::from_sql_type(&data_type),
+                data_type,
+                is_nullable: row.get::<String, _>("nullable") == "YES",
+                is_primary: primary_columns.contains(&column_name),
+                auto_increment: default
+                    .as_ref()
+                    .is_some_and(|value| value.starts_with("nextval(")),
+                name: column_name,
+                default,
+            }
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        table.foreign_keys = sqlx::query(
+            "SELECT kcu.column_name::text AS column_name, ccu.table_name::text AS foreign_table, ccu.column_name::text AS foreign_column, rc.delete_rule::text AS on_delete FROM information_schema.table_constraints tc JOIN information_schema.key_column_usage kcu ON kcu.constraint_name = tc.constraint_name AND kcu.table_schema = tc.table_schema JOIN information_schema.constraint_column_usage ccu ON ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema JOIN information_schema.referential_constraints rc ON rc.constraint_name = tc.constraint_name AND rc.constraint_schema = tc.table_schema WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = current_schema() AND tc.table_name = $1 ORDER BY tc.constraint_name, kcu.ordinal_position",
+        )
+        .bind(name)
+        .map(|row: PgRow| ForeignKeyDescription {
+            column: row.get("column_name"),
+            foreign_table: row.get("foreign_table"),
+            foreign_column: row.get("foreign_column"),
+            on_delete: row.get("on_delete"),
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(Some(table))
+    }
+
+    async fn database_size(&mut self) -> Result<Option<u64>, anyhow::Error> {
+        let query = "SELECT pg_database_size(current_database()) AS size";
+
+        Ok(sqlx::query(query)
+            .map(|row: PgRow| row.get::<i64, _>("size").max(0) as u64)
+            .fetch_optional(self.db_pool.as_ref())
+            .await?)
+    }
+
     async fn stream_result(
         &mut self,
         query_builder: &QueryBuilder,
@@ -106,6 +208,7 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         let mut params = PgArguments::default();
 
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
 
@@ -127,7 +230,7 @@ impl SchemaManagerTrait for PostgresSchemaManager {
     async fn drop_table(&mut self, name: &str) -> Result<(), anyhow::Error> {
         if self.has_table(name).await? {
             let query = QueryBuilder::new(name, QueryAction::DropTable);
-            return self.execute(query).await;
+            self.execute(query).await?;
         }
 
         Ok(())
@@ -137,7 +240,7 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         self.do_apply(table).await
     }
 
-    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         self.do_execute(query).await
     }
 
@@ -149,6 +252,7 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         let mut params = PgArguments::default();
 
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
 
@@ -178,6 +282,7 @@ impl SchemaManagerTrait for PostgresSchemaManager {
     ) -> Result<Option<ColumnAndValue>, anyhow::Error> {
         let mut params = PgArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
         let query = sqlx::query_with(&statement, params);
 
         return match query.fetch_optional(self.db_pool.as_ref()).await {
@@ -189,7 +294,19 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         };
     }
 
+    fn pretend(&mut self, log: PretendLog) {
+        self.pretend = Some(log);
+    }
+
+    fn record_statements(&mut self, recorder: StatementRecorder) {
+        self.recorder = Some(recorder);
+    }
+
     async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -224,9 +341,13 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         sql: &str,
         params: Vec<FieldValue>,
     ) -> Result<u64, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(0);
+        }
+
         let mut built_params = PgArguments::default();
         for field in params {
-            build_field_value_to_args(&field, &mut built_params)?;
+            self.field_value_to_args(&field, &mut built_params)?;
         }
 
         let result = if let Some(mut trans) = self.trans.take() {
@@ -279,8 +400,9 @@ impl SchemaManagerTrait for PostgresSchemaManager {
         let mut built_params = PgArguments::default();
 
         for field in params {
-            build_field_value_to_args(&field, &mut built_params)?;
+            self.field_value_to_args(&field, &mut built_params)?;
         }
+        self.record_sql(sql);
         let query = sqlx::query_with(sql, built_params);
 
         let mut rows = query.fetch(self.db_pool.as_ref());
@@ -304,6 +426,10 @@ impl SchemaManagerTrait for PostgresSchemaManager {
     }
 
     async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -335,6 +461,23 @@ impl SchemaManagerTrait for PostgresSchemaManager {
 }
 
 impl PostgresSchemaManager {
+    /// Reports the statement to the query log
+    fn record_sql(&self, sql: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.sql(sql);
+        }
+    }
+
+    /// Records the statement when pretending. Returns true when it must not be run
+    fn pretended(&self, sql: &str) -> bool {
+        self.record_sql(sql);
+        if let Some(log) = &self.pretend {
+            log.record(sql);
+            return true;
+        }
+        false
+    }
+
     async fn do_apply(&self, table: TableBlueprint) -> Result<(), anyhow::Error> {
         return if table.view_query.is_some() {
             // working with view table
@@ -345,7 +488,32 @@ impl PostgresSchemaManager {
         };
     }
 
-    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn run_statement<'c, E>(
+        sql: &str,
+        params: PgArguments,
+        returning: bool,
+        executor: E,
+    ) -> Result<ExecuteResult, sqlx::Error>
+    where
+        E: sqlx::Executor<'c, Database = Postgres>,
+    {
+        if !returning {
+            let result = sqlx::query_with(sql, params).execute(executor).await?;
+            return Ok(ExecuteResult::new(result.rows_affected(), None));
+        }
+
+        let rows = sqlx::query_with(sql, params).fetch_all(executor).await?;
+        let last_insert_id = rows.last().and_then(|row| {
+            [INTERNAL_ID_FIELD, ID_FIELD]
+                .iter()
+                .find_map(|column| row.try_get::<i64, _>(*column).ok())
+                .map(|id| id as u64)
+        });
+
+        Ok(ExecuteResult::new(rows.len() as u64, last_insert_id))
+    }
+
+    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         let mut params = PgArguments::default();
 
         let mut sql;
@@ -439,8 +607,19 @@ impl PostgresSchemaManager {
             }
         }
 
+        // Postgres does not report the generated ID, a single inserted row is returned instead
+        let returning =
+            matches!(query.action(), QueryAction::Create { rows, .. } if rows.len() == 1);
+        if returning {
+            sql = format!("{sql} RETURNING *");
+        }
+
+        if self.pretended(&sql) {
+            return Ok(ExecuteResult::default());
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
-            let result = sqlx::query_with(&sql, params).execute(&mut *trans).await;
+            let result = Self::run_statement(&sql, params, returning, &mut *trans).await;
             if result.is_ok() {
                 if let Err(e) = trans.commit().await {
                     tracing::error!(target: LOG_TARGET, "committing error: {}", &e);
@@ -453,15 +632,13 @@ impl PostgresSchemaManager {
 
             result
         } else {
-            sqlx::query_with(&sql, params)
-                .execute(self.db_pool.as_ref())
-                .await
+            Self::run_statement(&sql, params, returning, self.db_pool.as_ref()).await
         };
 
         match result {
             Ok(r) => {
                 log::debug!("{} result: {:#?}", query.action(), r);
-                Ok(())
+                Ok(r)
             }
             Err(e) => {
                 log::error!("{} failed: {}", query.action(), e);
@@ -477,6 +654,10 @@ impl PostgresSchemaManager {
 
             let query = format!("CREATE OR REPLACE VIEW \"{}\" AS ({})", &table.name, sql);
 
+            if self.pretended(&query) {
+                return Ok(());
+            }
+
             let result = sqlx::query_with(&query, params)
                 .execute(self.db_pool.as_ref())
                 .await;
@@ -520,34 +701,37 @@ impl PostgresSchemaManager {
             query = format!("{} ADD COLUMN IF NOT EXISTS {}", query, columns.join(","));
         }
 
-        let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
+        // an update may only change the indexes
+        if (table.is_new() || !columns.is_empty()) && !self.pretended(&query) {
+            let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
 
-        match result {
-            Ok(_) => {
-                log::info!(
-                    "Table '{}' {} successfully",
-                    &table.name,
-                    if table.is_new() { "created" } else { "updated" }
-                );
-            }
-            Err(e) => {
-                let name;
-                let action;
+            match result {
+                Ok(_) => {
+                    log::info!(
+                        "Table '{}' {} successfully",
+                        &table.name,
+                        if table.is_new() { "created" } else { "updated" }
+                    );
+                }
+                Err(e) => {
+                    let name;
+                    let action;
 
-                if table.is_new() {
-                    action = "create";
-                    name = table.new_name.unwrap_or(table.name.clone())
-                } else {
-                    action = "update";
-                    name = table.name.clone();
+                    if table.is_new() {
+                        action = "create";
+                        name = table.new_name.unwrap_or(table.name.clone())
+                    } else {
+                        action = "update";
+                        name = table.name.clone();
+                    }
+                    log::error!("Could not {} table {}: {}", action, name, &e);
+                    return Err(anyhow::anyhow!(
+                        "Could not {} table {}: {}",
+                        action,
+                        name,
+                        &e
+                    ));
                 }
-                log::error!("Could not {} table {}: {}", action, name, &e);
-                return Err(anyhow::anyhow!(
-                    "Could not {} table {}: {}",
-                    action,
-                    name,
-                    &e
-                ));
             }
         }
 
@@ -573,6 +757,18 @@ impl PostgresSchemaManager {
                             );
                         }
                     }
+                    IndexType::FullText(index) => {
+                        if index.delete_index() {
+                            sql = format!("DROP INDEX IF EXISTS \"{}_fulltext\"", index.name());
+                        } else {
+                            sql = format!(
+                                "CREATE INDEX IF NOT EXISTS \"{}_fulltext\" ON \"{}\" USING GIN ({})",
+                                index.name(),
+                                &table.name,
+                                ts_vector(index.columns())
+                            );
+                        }
+                    }
                     IndexType::Unique(index) => {
                         if index.delete_index() {
                             sql = format!("DROP INDEX IF EXISTS {}.{}", &table.name, index.name());
@@ -592,6 +788,10 @@ impl PostgresSchemaManager {
                     }
                 }
 
+                if self.pretended(&sql) {
+                    continue;
+                }
+
                 let index_result = sqlx::query(&sql).execute(self.db_pool.as_ref()).await;
                 match index_result {
                     Ok(_) => log::info!("table index created"),
@@ -723,10 +923,16 @@ impl PostgresSchemaManager {
         query: &QueryBuilder,
         params: &mut PgArguments,
     ) -> Result<String, anyhow::Error> {
-        let mut sql = "SELECT".to_owned();
+        // common table expressions
+        let mut sql = format!("{}SELECT", self.build_ctes(query, params)?);
+
+        // distinct
+        if let Some(distinct) = query.distinct_by() {
+            sql = format!("{sql} {distinct}");
+        }
 
         // fields
-        if let QueryAction::Query { columns } = query.action() {
+        if let QueryAction::Query { columns, .. } = query.action() {
             if let Some(fields) = columns {
                 let mut col_names = Vec::new();
                 for a_field in fields {
@@ -753,6 +959,9 @@ impl PostgresSchemaManager {
 
         // from
         sql = format!("{} FROM {}", sql, query.table());
+        if let Some(alias) = query.table_alias() {
+            sql = format!("{} AS {}", sql, alias);
+        }
 
         // joins
         sql = format!("{} {}", sql, self.build_join(query)?);
@@ -761,14 +970,23 @@ impl PostgresSchemaManager {
         sql = format!("{} {}", sql, self.build_where_clauses(query, params)?);
 
         // group by
+        if let Some(group) = query.grouped_by()
+            && !group.is_empty()
+        {
+            sql = format!("{sql} {group}");
+        }
+
+        // having
+        sql = format!("{} {}", sql, self.build_having_clauses(query, params)?);
+
+        // unions
+        sql = format!("{} {}", sql, self.build_unions(query, params)?);
 
         // order by
         if let Some(order) = self.build_order_by(query) {
             sql = format!("{sql} {order}");
         }
 
-        // having
-
         // limit
         if let Some(cursor) = query.cursor_by() {
             sql = format!("{sql} {}", cursor.limit());
@@ -835,11 +1053,83 @@ impl PostgresSchemaManager {
         Ok(wheres)
     }
 
+    fn build_having_clauses(
+        &self,
+        query: &QueryBuilder,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut havings = "".to_owned();
+        for having_join in query.having_clauses() {
+            havings = having_join.as_clause(
+                &havings,
+                &self.transform_condition(having_join.condition(), params)?,
+            );
+        }
+
+        if !havings.is_empty() {
+            havings = format!("HAVING {havings}");
+        }
+
+        Ok(havings)
+    }
+
+    fn build_unions(
+        &self,
+        query: &QueryBuilder,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut sql = "".to_owned();
+        for (index, a_union) in query.unions().iter().enumerate() {
+            let union_sql = self.build_query(a_union.query(), params)?;
+            sql = if a_union.needs_wrapping() {
+                format!(
+                    "{sql} {} SELECT * FROM ({union_sql}) AS union_{index}",
+                    a_union.union_type()
+                )
+            } else {
+                format!("{sql} {} {union_sql}", a_union.union_type())
+            };
+        }
+
+        Ok(sql)
+    }
+
+    fn build_ctes(
+        &self,
+        query: &QueryBuilder,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        if query.ctes().is_empty() {
+            return Ok("".to_owned());
+        }
+
+        let mut ctes = Vec::new();
+        for a_cte in query.ctes() {
+            ctes.push(format!(
+                "{} AS ({})",
+                a_cte.name(),
+                self.build_query(a_cte.query(), params)?
+            ));
+        }
+
+        let recursive = if query.ctes().iter().any(|c| c.is_recursive()) {
+            " RECURSIVE"
+        } else {
+            ""
+        };
+
+        Ok(format!("WITH{recursive} {} ", ctes.join(", ")))
+    }
+
     fn transform_condition(
         &self,
         condition: &Condition,
         params: &mut PgArguments,
     ) -> Result<String, anyhow::Error> {
+        if let Operator::Match(search) = condition.operator() {
+            return self.match_condition(search, params);
+        }
+
         let placeholder = match condition.value() {
             QueryValue::SubQuery(sub) => self.build_query(sub, params)?,
             QueryValue::ColumnName(name) => name.clone(),
@@ -856,8 +1146,7 @@ impl PostgresSchemaManager {
             _ => {
                 let current_total = params.len();
                 self.transform_value(condition.value(), params)?;
-                if *condition.operator() == Operator::In || *condition.operator() == Operator::NotIn
-                {
+                if condition.operator().is_list() {
                     let length = match &condition.value() {
                         QueryValue::Field(FieldValue::Array(v)) => v.len(),
                         _ => 1,
@@ -875,9 +1164,79 @@ impl PostgresSchemaManager {
             }
         };
 
-        Ok(condition
-            .operator()
-            .as_clause(condition.column(), &placeholder))
+        let column = condition.column();
+        Ok(match condition.operator() {
+            Operator::Json(path, operator) => {
+                let value = JsonPath::parse(path)?.to_arrows(column, true);
+                // `->>` returns text, numbers and booleans are compared as such
+                let value = match condition.value() {
+                    QueryValue::Field(
+                        FieldValue::U64(_)
+                        | FieldValue::U32(_)
+                        | FieldValue::I64(_)
+                        | FieldValue::I32(_)
+                        | FieldValue::I16(_)
+                        | FieldValue::I8(_)
+                        | FieldValue::F64(_),
+                    ) => format!("({value})::numeric"),
+                    QueryValue::Field(FieldValue::Boolean(_)) => format!("({value})::boolean"),
+                    _ => format!("({value})"),
+                };
+                operator.as_clause(&value, &placeholder)
+            }
+            Operator::JsonContains(path) => format!(
+                "({})::jsonb @> {placeholder}::jsonb",
+                JsonPath::parse(path)?.to_arrows(column, false)
+            ),
+            Operator::JsonLength(path, operator) => operator.as_clause(
+                &format!(
+                    "jsonb_array_length(({})::jsonb)",
+                    JsonPath::parse(path)?.to_arrows(column, false)
+                ),
+                &placeholder,
+            ),
+            operator => operator.as_clause(column, &placeholder),
+        })
+    }
+
+    fn json_extract(&self, column: &str, path: &str) -> Result<String, anyhow::Error> {
+        Ok(format!(
+            "({})",
+            JsonPath::parse(path)?.to_arrows(column, true)
+        ))
+    }
+
+    fn match_condition(
+        &self,
+        search: &FullTextMatch,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        let query = self.ts_query(search, params)?;
+        Ok(format!("{} @@ {query}", ts_vector(search.columns())))
+    }
+
+    fn relevance(
+        &self,
+        search: &FullTextMatch,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        let query = self.ts_query(search, params)?;
+        Ok(format!("ts_rank({}, {query})", ts_vector(search.columns())))
+    }
+
+    fn ts_query(
+        &self,
+        search: &FullTextMatch,
+        params: &mut PgArguments,
+    ) -> Result<String, anyhow::Error> {
+        let (function, term) = match search.mode() {
+            MatchMode::Natural => ("to_tsquery", search.words().join(" | ")),
+            MatchMode::Boolean => ("websearch_to_tsquery", search.term().to_string()),
+            MatchMode::Phrase => ("phraseto_tsquery", search.term().to_string()),
+        };
+        self.field_value_to_args(&FieldValue::String(term), params)?;
+
+        Ok(format!("{function}('simple', ${})", params.len()))
     }
 
     fn transform_value(
@@ -1079,6 +1438,9 @@ impl PostgresSchemaManager {
         field: &FieldValue,
         params: &mut PgArguments,
     ) -> Result<(), anyhow::Error> {
+        if let Some(recorder) = &self.recorder {
+            recorder.bind(field);
+        }
         build_field_value_to_args(field, params)
     }
 
@@ -1159,6 +1521,23 @@ impl PostgresSchemaManager {
                         Ok(format!("({aggregate}({sql})) as \"{alias}\""))
                     }
                 }
+                QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+                QueryColumnName::Json(n, path) => {
+                    let sql = self.json_extract(n, path)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as \"{alias}\""))
+                    }
+                }
+                QueryColumnName::Relevance(search) => {
+                    let sql = self.relevance(search, params)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as \"{alias}\""))
+                    }
+                }
             };
         }
         match column.name() {
@@ -1183,10 +1562,46 @@ impl PostgresSchemaManager {
                     Ok(format!("({sql}) as \"{alias}\""))
                 }
             }
+            QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+            QueryColumnName::Json(n, path) => {
+                let sql = self.json_extract(n, path)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as \"{alias}\""))
+                }
+            }
+            QueryColumnName::Relevance(search) => {
+                let sql = self.relevance(search, params)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as \"{alias}\""))
+                }
+            }
+        }
+    }
+
+    fn window_to_string(&self, window: &WindowFunction, alias: &str) -> String {
+        let sql = window.as_clause();
+        if alias.is_empty() {
+            sql
+        } else {
+            format!("{sql} as \"{alias}\"")
         }
     }
 }
 
+/// The document searched by full-text queries, the same expression is indexed
+fn ts_vector(columns: &[String]) -> String {
+    let document = columns
+        .iter()
+        .map(|c| format!("coalesce(\"{c}\", '')"))
+        .collect::<Vec<_>>()
+        .join(" || ' ' || ");
+    format!("to_tsvector('simple', {document})")
+}
+
 fn build_field_value_to_args(
     field: &FieldValue,
     params: &mut PgArguments,
@@ -1250,7 +1665,7 @@ fn build_field_value_to_args(
             _ = Arguments::add(params, v);
         }
         FieldValue::Null => {
-            _ = Arguments::add(params, "NULL");
+            _ = Arguments::add(params, None::<String>);
         }
         FieldValue::NotSet => (),
         FieldValue::Failable { field, error } => {
@@ -1263,3 +1678,58 @@ fn build_field_value_to_args(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        base::manager::Manager, config::ConnectionConfig,
+        connector::postgres::make_postgres_manager,
+    };
+
+    use super::*;
+
+    // needs a running server: DTY_TEST_POSTGRES_URL=... cargo test -- --ignored
+    async fn make_manager() -> Manager {
+        make_postgres_manager(ConnectionConfig {
+            kind: POSTGRES_KIND.into(),
+            url: std::env::var("DTY_TEST_POSTGRES_URL").expect("DTY_TEST_POSTGRES_URL is not set"),
+            ..Default::default()
+        })
+        .await
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_null_round_trip() {
+        crate::connector::assert_null_round_trip(&make_manager().await).await;
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_execute_result() {
+        let manager = make_manager().await;
+        _ = manager.drop_table("execute_result").await;
+        manager
+            .create_table_schema("execute_result", |bp| {
+                bp.id(None);
+                bp.string("name");
+            })
+            .await
+            .unwrap();
+
+        let row = |name: &str| HashMap::from([("name".to_string(), FieldValue::from(name))]);
+
+        let result = manager.insert("execute_result", row("ann")).await.unwrap();
+        assert_eq!(result.rows_affected(), 1);
+        assert_eq!(result.last_insert_id(), Some(1));
+
+        let result = manager
+            .insert_multi("execute_result", vec![row("bob"), row("bob")])
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), 2);
+        assert_eq!(result.last_insert_id(), None);
+
+        _ = manager.drop_table("execute_result").await;
+    }
+}
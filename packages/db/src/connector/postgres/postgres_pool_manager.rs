@@ -1,4 +1,4 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
@@ -11,6 +11,7 @@ use crate::{
     },
     config::{ConfigSet, ConnectionConfig},
     connector::postgres::LOG_TARGET,
+    make_replica_pool,
     pool_manager_resolver::DbPoolManagerResolver,
 };
 
@@ -48,6 +49,35 @@ impl ConnectionPoolTrait for PostgresPoolManager {
         }
         tracing::trace!(target: LOG_TARGET,"postgres connection closed: {}", self.db_pool.is_closed());
     }
+
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        sqlx::query("SELECT 1")
+            .execute(self.db_pool.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    async fn replica_lag(&self) -> Result<Option<Duration>, anyhow::Error> {
+        // a replica that replayed everything it received is not behind,
+        // even when the primary has been quiet for a while
+        let lag: Option<f64> = sqlx::query_scalar(
+            "SELECT CASE
+            WHEN NOT pg_is_in_recovery() THEN NULL
+            WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0::float8
+            ELSE EXTRACT(EPOCH FROM (now() - pg_last_xact_replay_timestamp()))::float8
+            END",
+        )
+        .fetch_one(self.db_pool.as_ref())
+        .await?;
+
+        Ok(lag.map(|seconds| Duration::from_secs_f64(seconds.max(0.0))))
+    }
+
+    fn in_use(&self) -> u32 {
+        self.db_pool
+            .size()
+            .saturating_sub(self.db_pool.num_idle() as u32)
+    }
 }
 
 pub async fn resolve(
@@ -56,6 +86,24 @@ pub async fn resolve(
     let mut pools: HashMap<ClientType, Box<dyn ConnectionPoolTrait>> = HashMap::new();
     for (client_type, config) in config_set.iter() {
         if POSTGRES_KIND == config.kind_ref().as_str() && config.enable {
+            if *client_type == ClientType::Read {
+                let replicas = make_replica_pool(config, |config, lazy| async move {
+                    let db_pool = if lazy {
+                        db_connect_lazy(&config)?
+                    } else {
+                        db_connect(&config).await?
+                    };
+                    Ok(Box::new(PostgresPoolManager {
+                        db_pool: Arc::new(db_pool),
+                    }) as Box<dyn ConnectionPoolTrait>)
+                })
+                .await;
+                if let Some(pool) = replicas {
+                    pools.insert(ClientType::Read, pool);
+                }
+                continue;
+            }
+
             match db_connect(config).await {
                 Ok(db_pool) => {
                     pools.insert(
@@ -79,6 +127,13 @@ pub async fn resolve(
     }
 }
 
+/// Creates the pool without connecting, the connections are made when they are used
+pub fn db_connect_lazy(config: &ConnectionConfig) -> anyhow::Result<Pool<Postgres>> {
+    Ok(PgPoolOptions::new()
+        .max_connections(config.max)
+        .connect_lazy(&config.url)?)
+}
+
 pub async fn db_connect(config: &ConnectionConfig) -> anyhow::Result<Pool<Postgres>> {
     tracing::info!(target: LOG_TARGET,"making a new connection pool");
     match PgPoolOptions::new()
@@ -1,11 +1,17 @@
 use crate::base::{
     column::{ColumnBlueprint, ColumnDefault, ColumnType},
+    full_text::{FullTextMatch, MatchMode, shadow_table_name},
     index::IndexType,
+    introspection::{ColumnDescription, ForeignKeyDescription, TableDescription},
+    json_path::JsonPath,
     query::{QueryAction, QueryBuilder},
     query_conditions::Condition,
+    query_log::StatementRecorder,
     query_operators::Operator,
-    schema::{DatabaseKind, RelationalDbTrait, SchemaManagerTrait},
+    schema::{DatabaseKind, ExecuteResult, PretendLog, RelationalDbTrait, SchemaManagerTrait},
     table::TableBlueprint,
+    union_builder::Distinct,
+    window_function::WindowFunction,
 };
 use crate::{field_values::FieldValue, query_values::QueryValue, types::ColumnAndValue};
 use anyhow::anyhow;
@@ -16,7 +22,7 @@ use dirtybase_contract::db_contract::{
 };
 use futures::stream::TryStreamExt;
 use sqlx::{
-    Arguments, Column, Pool, Row, Sqlite, SqliteTransaction, TypeInfo,
+    Arguments, Column, Pool, Row, Sqlite, SqliteTransaction, TypeInfo, ValueRef,
     sqlite::{SqliteArguments, SqliteRow},
     types::chrono,
 };
@@ -28,6 +34,8 @@ pub const SQLITE_KIND: &str = "sqlite";
 pub struct SqliteSchemaManager {
     db_pool: Arc<Pool<Sqlite>>,
     trans: Option<SqliteTransaction<'static>>,
+    pretend: Option<PretendLog>,
+    recorder: Option<StatementRecorder>,
 }
 
 impl SqliteSchemaManager {
@@ -35,6 +43,8 @@ impl SqliteSchemaManager {
         Self {
             db_pool,
             trans: None,
+            pretend: None,
+            recorder: None,
         }
     }
 
@@ -42,6 +52,8 @@ impl SqliteSchemaManager {
         Self {
             db_pool,
             trans: Some(trans),
+            pretend: None,
+            recorder: None,
         }
     }
 }
@@ -67,6 +79,107 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         Ok(result.unwrap_or_default())
     }
 
+    async fn tables(&mut self) -> Result<Vec<String>, anyhow::Error> {
+        // full-text tables and the tables backing them are left out
+        let query = "SELECT name FROM sqlite_master AS m WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+            AND sql NOT LIKE 'CREATE VIRTUAL TABLE%'
+            AND NOT EXISTS (SELECT 1 FROM sqlite_master AS v WHERE v.sql LIKE 'CREATE VIRTUAL TABLE%' AND m.name LIKE v.name || '\\_%' ESCAPE '\\')
+            ORDER BY name";
+
+        Ok(sqlx::query(query)
+            .map(|row: SqliteRow| row.get::<String, _>("name"))
+            .fetch_all(self.db_pool.as_ref())
+            .await?)
+    }
+
+    async fn describe_table(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<TableDescription>, anyhow::Error> {
+        let Some(sql) =
+            sqlx::query("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+                .bind(name)
+                .map(|row: SqliteRow| row.get::<Option<String>, _>("sql").unwrap_or_default())
+                .fetch_optional(self.db_pool.as_ref())
+                .await?
+        else {
+            return Ok(None);
+        };
+        let auto_increment = sql.to_uppercase().contains("AUTOINCREMENT");
+        let mut table = TableDescription::new(name);
+
+        table.columns = sqlx::query(
+            r#"SELECT name, type, "notnull", dflt_value, pk FROM pragma_table_info(?) ORDER BY cid"#,
+        )
+        .bind(name)
+        .map(|row: SqliteRow| {
+            let data_type = row.get::<String, _>("type");
+            let is_primary = row.get::<i64, _>("pk") > 0;
+            ColumnDescription {
+                name: row.get("name"),
+                column_type: ColumnType::from_sql_type(&data_type),
+                auto_increment: is_primary
+                    && auto_increment
+                    && data_type.eq_ignore_ascii_case("integer"),
+                data_type,
+                is_nullable: row.get::<i64, _>("notnull") == 0 && !is_primary,
+                is_primary,
+                default: row.get("dflt_value"),
+            }
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let indexes =
+            sqlx::query(r#"SELECT name, "unique", origin FROM pragma_index_list(?) ORDER BY name"#)
+                .bind(name)
+                .map(|row: SqliteRow| {
+                    (
+                        row.get::<String, _>("name"),
+                        row.get::<i64, _>("unique") == 1,
+                        row.get::<String, _>("origin") == "pk",
+                    )
+                })
+                .fetch_all(self.db_pool.as_ref())
+                .await?;
+
+        for (index, unique, primary) in indexes {
+            let columns = sqlx::query("SELECT name FROM pragma_index_info(?) ORDER BY seqno")
+                .bind(&index)
+                .map(|row: SqliteRow| row.get::<Option<String>, _>("name"))
+                .fetch_all(self.db_pool.as_ref())
+                .await?;
+            for column in columns.into_iter().flatten() {
+                table.push_index_column(&index, &column, unique, primary);
+            }
+        }
+
+        table.foreign_keys = sqlx::query(
+            r#"SELECT "from", "table", "to", on_delete FROM pragma_foreign_key_list(?) ORDER BY id, seq"#,
+        )
+        .bind(name)
+        .map(|row: SqliteRow| ForeignKeyDescription {
+            column: row.get("from"),
+            foreign_table: row.get("table"),
+            foreign_column: row.get::<Option<String>, _>("to").unwrap_or_default(),
+            on_delete: row.get("on_delete"),
+        })
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(Some(table))
+    }
+
+    async fn database_size(&mut self) -> Result<Option<u64>, anyhow::Error> {
+        let query =
+            "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()";
+
+        Ok(sqlx::query(query)
+            .map(|row: SqliteRow| row.get::<i64, _>("size").max(0) as u64)
+            .fetch_optional(self.db_pool.as_ref())
+            .await?)
+    }
+
     async fn begin(&mut self) -> Result<Box<dyn SchemaManagerTrait>, anyhow::Error> {
         match self.db_pool.begin().await {
             Ok(trans) => Ok(Box::new(Self::new_trans(self.db_pool.clone(), trans))),
@@ -101,6 +214,7 @@ impl SchemaManagerTrait for SqliteSchemaManager {
     ) -> Result<(), anyhow::Error> {
         let mut params = SqliteArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
 
@@ -121,7 +235,7 @@ impl SchemaManagerTrait for SqliteSchemaManager {
     async fn drop_table(&mut self, name: &str) -> Result<(), anyhow::Error> {
         if self.has_table(name).await? {
             let query = QueryBuilder::new(name, QueryAction::DropTable);
-            return self.execute(query).await;
+            self.execute(query).await?;
         }
         Ok(())
     }
@@ -130,7 +244,7 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         self.do_apply(table).await
     }
 
-    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         self.do_execute(query).await
     }
 
@@ -142,6 +256,7 @@ impl SchemaManagerTrait for SqliteSchemaManager {
 
         let mut params = SqliteArguments::default();
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
 
         let query = sqlx::query_with(&statement, params);
 
@@ -172,6 +287,7 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         let mut params = SqliteArguments::default();
 
         let statement = self.build_query(query_builder, &mut params)?;
+        self.record_sql(&statement);
         let query = sqlx::query_with(&statement, params);
 
         return match query.fetch_optional(self.db_pool.as_ref()).await {
@@ -183,7 +299,19 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         };
     }
 
+    fn pretend(&mut self, log: PretendLog) {
+        self.pretend = Some(log);
+    }
+
+    fn record_statements(&mut self, recorder: StatementRecorder) {
+        self.recorder = Some(recorder);
+    }
+
     async fn raw_insert(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -218,10 +346,14 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         sql: &str,
         params: Vec<FieldValue>,
     ) -> Result<u64, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(0);
+        }
+
         let mut built_params = SqliteArguments::default();
 
         for field in params {
-            build_field_value_to_args(&field, &mut built_params)?;
+            self.field_value_to_args(&field, &mut built_params)?;
         }
 
         let result = if let Some(mut trans) = self.trans.take() {
@@ -274,8 +406,9 @@ impl SchemaManagerTrait for SqliteSchemaManager {
         let mut built_params = SqliteArguments::default();
 
         for field in params {
-            build_field_value_to_args(&field, &mut built_params)?;
+            self.field_value_to_args(&field, &mut built_params)?;
         }
+        self.record_sql(sql);
         let query = sqlx::query_with(sql, built_params);
 
         let mut rows = query.fetch(self.db_pool.as_ref());
@@ -299,6 +432,10 @@ impl SchemaManagerTrait for SqliteSchemaManager {
     }
 
     async fn raw_statement(&mut self, sql: &str) -> Result<bool, anyhow::Error> {
+        if self.pretended(sql) {
+            return Ok(true);
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query(sql).execute(&mut *trans).await;
             if result.is_ok() {
@@ -330,6 +467,23 @@ impl SchemaManagerTrait for SqliteSchemaManager {
 }
 
 impl SqliteSchemaManager {
+    /// Reports the statement to the query log
+    fn record_sql(&self, sql: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.sql(sql);
+        }
+    }
+
+    /// Records the statement when pretending. Returns true when it must not be run
+    fn pretended(&self, sql: &str) -> bool {
+        self.record_sql(sql);
+        if let Some(log) = &self.pretend {
+            log.record(sql);
+            return true;
+        }
+        false
+    }
+
     async fn do_apply(&mut self, table: TableBlueprint) -> Result<(), anyhow::Error> {
         return if table.view_query.is_some() {
             // working with view table
@@ -340,7 +494,7 @@ impl SqliteSchemaManager {
         };
     }
 
-    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<()> {
+    async fn do_execute(&mut self, query: QueryBuilder) -> anyhow::Result<ExecuteResult> {
         let mut params = SqliteArguments::default();
 
         let mut sql;
@@ -429,6 +583,10 @@ impl SqliteSchemaManager {
             }
         }
 
+        if self.pretended(&sql) {
+            return Ok(ExecuteResult::default());
+        }
+
         let result = if let Some(mut trans) = self.trans.take() {
             let result = sqlx::query_with(&sql, params).execute(&mut *trans).await;
             if result.is_ok() {
@@ -451,7 +609,13 @@ impl SqliteSchemaManager {
         match result {
             Ok(r) => {
                 log::debug!(target: LOG_TARGET,"{} result: {:#?}", query.action(), r);
-                Ok(())
+                let last_insert_id = match query.action() {
+                    QueryAction::Create { .. } if r.rows_affected() > 0 => {
+                        Some(r.last_insert_rowid() as u64)
+                    }
+                    _ => None,
+                };
+                Ok(ExecuteResult::new(r.rows_affected(), last_insert_id))
             }
             Err(e) => {
                 log::error!(target: LOG_TARGET, "{} failed: {}", query.action(), e);
@@ -467,6 +631,10 @@ impl SqliteSchemaManager {
 
             let query = format!("CREATE OR REPLACE VIEW `{}` AS ({})", &table.name, sql);
 
+            if self.pretended(&query) {
+                return Ok(());
+            }
+
             let result = sqlx::query_with(&query, params)
                 .execute(self.db_pool.as_ref())
                 .await;
@@ -521,34 +689,37 @@ impl SqliteSchemaManager {
             }
         }
 
-        let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
+        // an update may only change the indexes
+        if (table.is_new() || !columns.is_empty()) && !self.pretended(&query) {
+            let result = sqlx::query(&query).execute(self.db_pool.as_ref()).await;
 
-        match result {
-            Ok(_) => {
-                log::info!(
-                    "Table '{}' {} successfully",
-                    &table.name,
-                    if table.is_new() { "created" } else { "updated" }
-                );
-            }
-            Err(e) => {
-                let name;
-                let action;
+            match result {
+                Ok(_) => {
+                    log::info!(
+                        "Table '{}' {} successfully",
+                        &table.name,
+                        if table.is_new() { "created" } else { "updated" }
+                    );
+                }
+                Err(e) => {
+                    let name;
+                    let action;
 
-                if table.is_new() {
-                    action = "create";
-                    name = table.new_name.unwrap_or(table.name.clone())
-                } else {
-                    action = "update";
-                    name = table.name.clone();
+                    if table.is_new() {
+                        action = "create";
+                        name = table.new_name.unwrap_or(table.name.clone())
+                    } else {
+                        action = "update";
+                        name = table.name.clone();
+                    }
+                    log::error!("Could not {} table {}: {}", action, name, &e);
+                    return Err(anyhow::anyhow!(
+                        "Could not {} table {}: {}",
+                        action,
+                        name,
+                        &e
+                    ));
                 }
-                log::error!("Could not {} table {}: {}", action, name, &e);
-                return Err(anyhow::anyhow!(
-                    "Could not {} table {}: {}",
-                    action,
-                    name,
-                    &e
-                ));
             }
         }
 
@@ -569,6 +740,41 @@ impl SqliteSchemaManager {
                             );
                         }
                     }
+                    IndexType::FullText(index) => {
+                        // an external content FTS5 table kept in sync by triggers
+                        let fts = shadow_table_name(&table.name, index);
+                        if index.delete_index() {
+                            sql = format!(
+                                "DROP TRIGGER IF EXISTS {fts}_insert; DROP TRIGGER IF EXISTS {fts}_delete; DROP TRIGGER IF EXISTS {fts}_update; DROP TABLE IF EXISTS {fts};"
+                            );
+                        } else {
+                            let columns = index.concat_columns();
+                            let values = |row: &str| {
+                                index
+                                    .columns()
+                                    .iter()
+                                    .map(|c| format!("{row}.{c}"))
+                                    .collect::<Vec<_>>()
+                                    .join(",")
+                            };
+                            let (new, old) = (values("new"), values("old"));
+                            let name = &table.name;
+                            sql = format!(
+                                "CREATE VIRTUAL TABLE IF NOT EXISTS {fts} USING fts5({columns}, content='{name}');
+                                CREATE TRIGGER IF NOT EXISTS {fts}_insert AFTER INSERT ON {name} BEGIN
+                                    INSERT INTO {fts}(rowid, {columns}) VALUES (new.rowid, {new});
+                                END;
+                                CREATE TRIGGER IF NOT EXISTS {fts}_delete AFTER DELETE ON {name} BEGIN
+                                    INSERT INTO {fts}({fts}, rowid, {columns}) VALUES ('delete', old.rowid, {old});
+                                END;
+                                CREATE TRIGGER IF NOT EXISTS {fts}_update AFTER UPDATE ON {name} BEGIN
+                                    INSERT INTO {fts}({fts}, rowid, {columns}) VALUES ('delete', old.rowid, {old});
+                                    INSERT INTO {fts}(rowid, {columns}) VALUES (new.rowid, {new});
+                                END;
+                                INSERT INTO {fts}({fts}) VALUES ('rebuild');"
+                            );
+                        }
+                    }
                     IndexType::Unique(index) => {
                         if index.delete_index() {
                             sql = format!("DROP INDEX IF EXISTS {}.{}", &table.name, index.name());
@@ -583,6 +789,10 @@ impl SqliteSchemaManager {
                     }
                 }
 
+                if self.pretended(&sql) {
+                    continue;
+                }
+
                 let index_result = sqlx::query(&sql).execute(self.db_pool.as_ref()).await;
                 match index_result {
                     Ok(_e) => log::info!("table index created"),
@@ -737,10 +947,18 @@ impl SqliteSchemaManager {
         query: &QueryBuilder,
         params: &mut SqliteArguments,
     ) -> Result<String, anyhow::Error> {
-        let mut sql = "SELECT".to_owned();
+        // common table expressions
+        let mut sql = format!("{}SELECT", self.build_ctes(query, params)?);
+
+        // distinct
+        match query.distinct_by() {
+            Some(Distinct::Rows) => sql = format!("{sql} DISTINCT"),
+            Some(Distinct::On(_)) => return Err(anyhow!("sqlite does not support DISTINCT ON")),
+            None => (),
+        }
 
         // fields
-        if let QueryAction::Query { columns } = query.action() {
+        if let QueryAction::Query { columns, .. } = query.action() {
             if let Some(fields) = columns {
                 let mut col_names = Vec::new();
                 for a_field in fields {
@@ -767,6 +985,9 @@ impl SqliteSchemaManager {
 
         // from
         sql = format!("{} FROM '{}'", sql, query.table());
+        if let Some(alias) = query.table_alias() {
+            sql = format!("{} AS {}", sql, alias);
+        }
 
         // joins
         sql = format!("{} {}", sql, self.build_join(query, params)?);
@@ -775,6 +996,17 @@ impl SqliteSchemaManager {
         sql = format!("{} {}", sql, self.build_where_clauses(query, params)?);
 
         // group by
+        if let Some(group) = query.grouped_by()
+            && !group.is_empty()
+        {
+            sql = format!("{sql} {group}");
+        }
+
+        // having
+        sql = format!("{} {}", sql, self.build_having_clauses(query, params)?);
+
+        // unions
+        sql = format!("{} {}", sql, self.build_unions(query, params)?);
 
         // order by
         if let Some(order) = self.build_order_by(query) {
@@ -852,11 +1084,83 @@ impl SqliteSchemaManager {
         Ok(wheres)
     }
 
+    fn build_having_clauses(
+        &self,
+        query: &QueryBuilder,
+        params: &mut SqliteArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut havings = "".to_owned();
+        for having_join in query.having_clauses() {
+            havings = having_join.as_clause(
+                &havings,
+                &self.transform_condition(having_join.condition(), params)?,
+            );
+        }
+
+        if !havings.is_empty() {
+            havings = format!("HAVING {havings}");
+        }
+
+        Ok(havings)
+    }
+
+    fn build_unions(
+        &self,
+        query: &QueryBuilder,
+        params: &mut SqliteArguments,
+    ) -> Result<String, anyhow::Error> {
+        let mut sql = "".to_owned();
+        for (index, a_union) in query.unions().iter().enumerate() {
+            let union_sql = self.build_query(a_union.query(), params)?;
+            sql = if a_union.needs_wrapping() {
+                format!(
+                    "{sql} {} SELECT * FROM ({union_sql}) AS union_{index}",
+                    a_union.union_type()
+                )
+            } else {
+                format!("{sql} {} {union_sql}", a_union.union_type())
+            };
+        }
+
+        Ok(sql)
+    }
+
+    fn build_ctes(
+        &self,
+        query: &QueryBuilder,
+        params: &mut SqliteArguments,
+    ) -> Result<String, anyhow::Error> {
+        if query.ctes().is_empty() {
+            return Ok("".to_owned());
+        }
+
+        let mut ctes = Vec::new();
+        for a_cte in query.ctes() {
+            ctes.push(format!(
+                "{} AS ({})",
+                a_cte.name(),
+                self.build_query(a_cte.query(), params)?
+            ));
+        }
+
+        let recursive = if query.ctes().iter().any(|c| c.is_recursive()) {
+            " RECURSIVE"
+        } else {
+            ""
+        };
+
+        Ok(format!("WITH{recursive} {} ", ctes.join(", ")))
+    }
+
     fn transform_condition(
         &self,
         condition: &Condition,
         params: &mut SqliteArguments,
     ) -> Result<String, anyhow::Error> {
+        if let Operator::Match(search) = condition.operator() {
+            return self.match_condition(search, params);
+        }
+
         let placeholder = match condition.value() {
             QueryValue::SubQuery(sub) => self.build_query(sub, params)?,
             QueryValue::ColumnName(name) => name.clone(),
@@ -872,8 +1176,7 @@ impl SqliteSchemaManager {
             }
             _ => {
                 self.transform_value(condition.value(), params)?;
-                if *condition.operator() == Operator::In || *condition.operator() == Operator::NotIn
-                {
+                if condition.operator().is_list() {
                     let length = match &condition.value() {
                         QueryValue::Field(FieldValue::Array(v)) => v.len(),
                         _ => 1,
@@ -888,9 +1191,63 @@ impl SqliteSchemaManager {
             }
         };
 
-        Ok(condition
-            .operator()
-            .as_clause(condition.column(), &placeholder))
+        let column = condition.column();
+        Ok(match condition.operator() {
+            Operator::Json(path, operator) => {
+                operator.as_clause(&self.json_extract(column, path)?, &placeholder)
+            }
+            Operator::JsonContains(path) => format!(
+                "EXISTS (SELECT 1 FROM json_each({column}, '{}') WHERE json_each.value = json_extract({placeholder}, '$'))",
+                JsonPath::parse(path)?
+            ),
+            Operator::JsonLength(path, operator) => operator.as_clause(
+                &format!("json_array_length({column}, '{}')", JsonPath::parse(path)?),
+                &placeholder,
+            ),
+            operator => operator.as_clause(column, &placeholder),
+        })
+    }
+
+    fn json_extract(&self, column: &str, path: &str) -> Result<String, anyhow::Error> {
+        Ok(format!(
+            "json_extract({column}, '{}')",
+            JsonPath::parse(path)?
+        ))
+    }
+
+    fn match_condition(
+        &self,
+        search: &FullTextMatch,
+        params: &mut SqliteArguments,
+    ) -> Result<String, anyhow::Error> {
+        let Some(term) = fts_query(search) else {
+            return Ok("1 = 0".to_string());
+        };
+        self.field_value_to_args(&FieldValue::String(term), params)?;
+
+        let fts = search.shadow_table();
+        Ok(format!(
+            "{}.rowid IN (SELECT rowid FROM {fts} WHERE {fts} MATCH ?)",
+            search.table()
+        ))
+    }
+
+    fn relevance(
+        &self,
+        search: &FullTextMatch,
+        params: &mut SqliteArguments,
+    ) -> Result<String, anyhow::Error> {
+        let Some(term) = fts_query(search) else {
+            return Ok("0".to_string());
+        };
+        self.field_value_to_args(&FieldValue::String(term), params)?;
+
+        // bm25 is lower for better matches
+        let fts = search.shadow_table();
+        Ok(format!(
+            "(SELECT -bm25({fts}) FROM {fts} WHERE {fts} MATCH ? AND {fts}.rowid = {}.rowid)",
+            search.table()
+        ))
     }
 
     fn transform_value(
@@ -915,6 +1272,14 @@ impl SqliteSchemaManager {
 
         for col in row.columns() {
             let name = col.name().to_owned();
+            if row
+                .try_get_raw(col.name())
+                .is_ok_and(|value| value.is_null())
+            {
+                this_row.insert(name, FieldValue::Null);
+                continue;
+            }
+
             match col.type_info().name() {
                 "BOOLEAN" | "TINYINT(1)" => {
                     let v: bool = row.try_get::<i8, &str>(col.name()).unwrap_or_default() > 0;
@@ -1036,6 +1401,9 @@ impl SqliteSchemaManager {
         field: &FieldValue,
         params: &mut SqliteArguments,
     ) -> Result<(), anyhow::Error> {
+        if let Some(recorder) = &self.recorder {
+            recorder.bind(field);
+        }
         build_field_value_to_args(field, params)
     }
 
@@ -1113,6 +1481,23 @@ impl SqliteSchemaManager {
                         Ok(format!("({aggregate}({sql})) as '{alias}'"))
                     }
                 }
+                QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+                QueryColumnName::Json(n, path) => {
+                    let sql = self.json_extract(n, path)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as '{alias}'"))
+                    }
+                }
+                QueryColumnName::Relevance(search) => {
+                    let sql = self.relevance(search, params)?;
+                    if alias.is_empty() {
+                        Ok(format!("{aggregate}({sql})"))
+                    } else {
+                        Ok(format!("{aggregate}({sql}) as '{alias}'"))
+                    }
+                }
             };
         }
         match column.name() {
@@ -1137,10 +1522,53 @@ impl SqliteSchemaManager {
                     Ok(format!("({sql}) as '{alias}'"))
                 }
             }
+            QueryColumnName::Window(window) => Ok(self.window_to_string(window, &alias)),
+            QueryColumnName::Json(n, path) => {
+                let sql = self.json_extract(n, path)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as '{alias}'"))
+                }
+            }
+            QueryColumnName::Relevance(search) => {
+                let sql = self.relevance(search, params)?;
+                if alias.is_empty() {
+                    Ok(sql)
+                } else {
+                    Ok(format!("{sql} as '{alias}'"))
+                }
+            }
+        }
+    }
+
+    fn window_to_string(&self, window: &WindowFunction, alias: &str) -> String {
+        let sql = window.as_clause();
+        if alias.is_empty() {
+            sql
+        } else {
+            format!("{sql} as '{alias}'")
         }
     }
 }
 
+/// The term as an FTS5 query, `None` when there is nothing to search for
+fn fts_query(search: &FullTextMatch) -> Option<String> {
+    let words = search.words();
+    match search.mode() {
+        MatchMode::Boolean if !search.term().trim().is_empty() => Some(search.term().to_string()),
+        _ if words.is_empty() => None,
+        MatchMode::Phrase => Some(format!("\"{}\"", words.join(" "))),
+        _ => Some(
+            words
+                .iter()
+                .map(|w| format!("\"{w}\""))
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        ),
+    }
+}
+
 fn build_field_value_to_args(
     field: &FieldValue,
     params: &mut SqliteArguments,
@@ -1203,7 +1631,7 @@ fn build_field_value_to_args(
             _ = Arguments::add(params, v);
         }
         FieldValue::Null => {
-            _ = Arguments::add(params, "NULL");
+            _ = Arguments::add(params, None::<String>);
         }
         FieldValue::NotSet => (),
         FieldValue::Failable { field, error } => {
@@ -1219,19 +1647,23 @@ fn build_field_value_to_args(
 
 #[cfg(test)]
 mod test {
-    use crate::{config::ConnectionConfig, connector::sqlite::sqlite_pool_manager::db_connect};
+    use crate::{
+        base::{query_log::QueryStats, schema::ClientType},
+        config::ConnectionConfig,
+        connector::sqlite::sqlite_pool_manager::db_connect,
+    };
 
     use super::*;
 
     #[tokio::test]
     async fn test_select_builder() {
-        let mut query = QueryBuilder::new("foo", QueryAction::Query { columns: None });
+        let mut query = QueryBuilder::new("foo", QueryAction::query());
         let config = ConnectionConfig::default();
         let pool = db_connect(&config).await;
         let sqlite = SqliteSchemaManager::new(Arc::new(pool.unwrap()));
         let mut params = SqliteArguments::default();
 
-        let mut builder = QueryBuilder::new("inner", QueryAction::Query { columns: None });
+        let mut builder = QueryBuilder::new("inner", QueryAction::query());
         // builder.max_as("point", "user_points");
         builder.is_eq("user", 32);
         let mut col = QueryColumn::from(QueryColumnName::SubQuery(Box::new(builder)));
@@ -1245,4 +1677,806 @@ mod test {
         println!("{:#?}", sqlite.build_query(&query, &mut params));
         println!("{:#?}", &params)
     }
+
+    #[tokio::test]
+    async fn test_group_by_and_having() {
+        let mut query = QueryBuilder::new("orders", QueryAction::query());
+        let config = ConnectionConfig::default();
+        let pool = db_connect(&config).await;
+        let sqlite = SqliteSchemaManager::new(Arc::new(pool.unwrap()));
+        let mut params = SqliteArguments::default();
+
+        query
+            .select("customer_id")
+            .count_as("id", "total")
+            .is_eq("status", "paid")
+            .group_by(["customer_id"])
+            .having(|q| {
+                q.gt("COUNT(id)", 5);
+            })
+            .or_having(|q| {
+                q.gt("SUM(amount)", 100);
+            })
+            .desc("total");
+
+        let sql = sqlite.build_query(&query, &mut params).unwrap();
+        let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+        assert!(sql.ends_with(
+            "WHERE status = ? GROUP BY customer_id HAVING ( COUNT(id) > ?) OR ( SUM(amount) > ?) ORDER BY total DESC"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_union_and_distinct() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        for table in ["posts", "archived_posts"] {
+            manager
+                .raw_statement(&format!(
+                    "CREATE TABLE {table} (title TEXT NOT NULL, author TEXT NOT NULL)"
+                ))
+                .await
+                .unwrap();
+        }
+        manager
+            .raw_statement(
+                "INSERT INTO posts VALUES ('a', 'bob'), ('b', 'bob'), ('b', 'bob'), ('c', 'ann')",
+            )
+            .await
+            .unwrap();
+        manager
+            .raw_statement("INSERT INTO archived_posts VALUES ('b', 'bob'), ('d', 'bob')")
+            .await
+            .unwrap();
+
+        let titles = |rows: Vec<crate::types::StructuredColumnAndValue>| {
+            rows.into_iter()
+                .map(|r| r.fields_ref().get("title").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let distinct = manager
+            .select_from_table("posts", |q| {
+                q.select("title")
+                    .distinct()
+                    .is_eq("author", "bob")
+                    .asc("title");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(titles(distinct), vec!["a", "b"]);
+
+        let mut archived = QueryBuilder::new_query("archived_posts");
+        archived.select("title").is_eq("author", "bob");
+        let all = manager
+            .select_from_table("posts", |q| {
+                q.select("title")
+                    .is_eq("author", "bob")
+                    .union_all(archived.clone())
+                    .asc("title");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(titles(all), vec!["a", "b", "b", "b", "d"]);
+
+        archived.desc("title").limit(1);
+        let unique = manager
+            .select_from_table("posts", |q| {
+                q.select("title")
+                    .is_eq("author", "bob")
+                    .union(archived)
+                    .asc("title");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(titles(unique), vec!["a", "b", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_common_table_expressions() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement(
+                "CREATE TABLE categories (id INTEGER NOT NULL, parent_id INTEGER NOT NULL, name TEXT NOT NULL)",
+            )
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "INSERT INTO categories VALUES (1, 0, 'toys'), (2, 1, 'cars'), (3, 2, 'trucks'), (4, 0, 'books')",
+            )
+            .await
+            .unwrap();
+
+        let names = |rows: Vec<crate::types::StructuredColumnAndValue>| {
+            rows.into_iter()
+                .map(|r| r.fields_ref().get("name").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let roots = manager
+            .select_from_table("roots", |q| {
+                q.with("roots", |cte| {
+                    cte.from("categories").is_eq("parent_id", 0);
+                })
+                .select("name")
+                .desc("name");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(names(roots), vec!["toys", "books"]);
+
+        let tree = manager
+            .select_from_table("tree", |q| {
+                q.with_recursive(
+                    "tree",
+                    |anchor| {
+                        anchor
+                            .from("categories")
+                            .select_multiple(["id", "name"])
+                            .is_eq("id", 1);
+                    },
+                    |recursive| {
+                        recursive
+                            .from("categories")
+                            .select_multiple(["categories.id", "categories.name"])
+                            .inner_join("tree", "tree.id", "=", "categories.parent_id");
+                    },
+                )
+                .select("name")
+                .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(names(tree), vec!["toys", "cars", "trucks"]);
+    }
+
+    #[tokio::test]
+    async fn test_window_functions() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement(
+                "CREATE TABLE scores (id INTEGER NOT NULL, team TEXT NOT NULL, points INTEGER NOT NULL)",
+            )
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "INSERT INTO scores VALUES (1, 'red', 10), (2, 'red', 30), (3, 'blue', 20), (4, 'red', 20)",
+            )
+            .await
+            .unwrap();
+
+        let column = |rows: &[crate::types::StructuredColumnAndValue], name: &str| {
+            rows.iter()
+                .map(|r| r.fields_ref().get(name).unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let rows = manager
+            .select_from_table("scores", |q| {
+                q.select("id")
+                    .select_as(
+                        WindowFunction::rank().partition_by(["team"]).desc("points"),
+                        "position",
+                    )
+                    .select_as(WindowFunction::sum("points").asc("id"), "running_total")
+                    .select_as(WindowFunction::lag("points", 1).asc("id"), "previous")
+                    .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+
+        assert_eq!(column(&rows, "id"), vec!["1", "2", "3", "4"]);
+        assert_eq!(column(&rows, "position"), vec!["3", "1", "1", "2"]);
+        assert_eq!(column(&rows, "running_total"), vec!["10", "40", "60", "80"]);
+        assert_eq!(column(&rows, "previous")[1..], ["10", "30", "20"]);
+    }
+
+    #[tokio::test]
+    async fn test_json_queries() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement("CREATE TABLE profiles (id INTEGER NOT NULL, data TEXT NOT NULL)")
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                r#"INSERT INTO profiles VALUES
+                (1, '{"settings": {"theme": "dark", "size": 12}, "tags": ["rust", "sql"]}'),
+                (2, '{"settings": {"theme": "light", "size": 14}, "tags": ["go"]}'),
+                (3, '{"settings": {"theme": "dark", "size": 16}, "tags": []}')"#,
+            )
+            .await
+            .unwrap();
+
+        let ids = |rows: Vec<crate::types::StructuredColumnAndValue>| {
+            rows.iter()
+                .map(|r| r.fields_ref().get("id").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let rows = manager
+            .select_from_table("profiles", |q| {
+                q.select("id")
+                    .where_json("data", "$.settings.theme", Operator::Equal, "dark")
+                    .where_json("data", "$.settings.size", Operator::Greater, 12)
+                    .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(ids(rows), vec!["3"]);
+
+        let rows = manager
+            .select_from_table("profiles", |q| {
+                q.select("id")
+                    .json_contains("data", "$.tags", "rust")
+                    .or_json_length("data", "$.tags", Operator::Equal, 0)
+                    .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(ids(rows), vec!["1", "3"]);
+
+        let rows = manager
+            .select_from_table("profiles", |q| {
+                q.select_json("data", "$.tags[0]", "first_tag")
+                    .is_eq("id", 2);
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(
+            rows[0].fields_ref().get("first_tag").unwrap().to_string(),
+            "go"
+        );
+
+        let result = manager
+            .select_from_table("profiles", |q| {
+                q.where_json("data", "$.theme' OR 1=1 --", Operator::Equal, "dark");
+            })
+            .fetch_all()
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .create_table_schema("articles", |table| {
+                table.id(None);
+                table.string("title");
+                table.text("body");
+            })
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "INSERT INTO articles (title, body) VALUES
+                ('Rust queries', 'writing rust with a query builder'),
+                ('Go routines', 'concurrency in go'),
+                ('Rust and SQL', 'rust rust rust and some sql')",
+            )
+            .await
+            .unwrap();
+        // existing rows are indexed when the index is created
+        manager
+            .update_table_schema("articles", |table| {
+                table.fulltext_index(&["title", "body"]);
+            })
+            .await
+            .unwrap();
+        manager
+            .raw_statement("INSERT INTO articles (title, body) VALUES ('Cooking', 'bread with rust colored crust')")
+            .await
+            .unwrap();
+        manager
+            .raw_statement("UPDATE articles SET body = 'goroutines and channels' WHERE id = 2")
+            .await
+            .unwrap();
+
+        let titles = |rows: Vec<crate::types::StructuredColumnAndValue>| {
+            rows.iter()
+                .map(|r| r.fields_ref().get("title").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let rows = manager
+            .select_from_table("articles", |q| {
+                q.select("title")
+                    .select_relevance(&["body", "title"], "rust", MatchMode::Natural, "score")
+                    .match_against(&["title", "body"], "rust", MatchMode::Natural)
+                    .desc("score");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(
+            titles(rows),
+            vec!["Rust and SQL", "Rust queries", "Cooking"]
+        );
+
+        let rows = manager
+            .select_from_table("articles", |q| {
+                q.select("title")
+                    .match_against(&["title", "body"], "query builder", MatchMode::Phrase)
+                    .or_match_against(&["title", "body"], "channels", MatchMode::Natural)
+                    .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(titles(rows), vec!["Rust queries", "Go routines"]);
+
+        assert_eq!(manager.tables().await.unwrap(), vec!["articles"]);
+
+        manager
+            .update_table_schema("articles", |table| {
+                table.drop_fulltext_index(&["title", "body"]);
+            })
+            .await
+            .unwrap();
+        manager
+            .raw_statement("DELETE FROM articles WHERE id = 1")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_log() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement("CREATE TABLE authors (id INTEGER NOT NULL, name TEXT NOT NULL)")
+            .await
+            .unwrap();
+
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = entries.clone();
+        manager.on_query(move |entry| log.lock().unwrap().push(entry.clone()));
+
+        let stats = QueryStats::with_n_plus_one_detection(3);
+        let request_manager = manager.with_query_stats(stats.clone());
+        request_manager
+            .raw_update(
+                "INSERT INTO authors (id, name) VALUES (?, ?)",
+                vec![FieldValue::from(1), FieldValue::from("Ada")],
+            )
+            .await
+            .unwrap();
+        for id in 1..=3 {
+            request_manager
+                .select_from_table("authors", |q| {
+                    q.is_eq("id", id);
+                })
+                .fetch_one()
+                .await
+                .unwrap();
+        }
+        // queries of the shared manager are logged but not counted
+        manager.tables().await.unwrap();
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].operation, "raw_update");
+        assert_eq!(entries[0].client_type, ClientType::Write);
+        assert_eq!(entries[0].params.len(), 2);
+        assert_eq!(entries[1].operation, "fetch_one");
+        assert_eq!(entries[1].params, vec![FieldValue::I32(1)]);
+        assert!(entries[1].sql[0].starts_with("SELECT"));
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.repeated().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exists() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement("CREATE TABLE families (id INTEGER NOT NULL, name TEXT NOT NULL)")
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "CREATE TABLE children (family_id INTEGER NOT NULL, age INTEGER NOT NULL)",
+            )
+            .await
+            .unwrap();
+        manager
+            .raw_statement("INSERT INTO families VALUES (1, 'doe'), (2, 'roe'), (3, 'poe')")
+            .await
+            .unwrap();
+        manager
+            .raw_statement("INSERT INTO children VALUES (1, 4), (1, 12), (3, 7)")
+            .await
+            .unwrap();
+
+        let names = |rows: Vec<crate::types::StructuredColumnAndValue>| {
+            rows.into_iter()
+                .map(|r| r.fields_ref().get("name").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let mut children = QueryBuilder::new_query("children");
+        children.is_eq_column("children.family_id", "families.id");
+
+        let with_children = manager
+            .select_from_table("families", |q| {
+                q.select("name").exists(children.clone()).asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(names(with_children), vec!["doe", "poe"]);
+
+        let without_children = manager
+            .select_from_table("families", |q| {
+                q.select("name").not_exists(children.clone()).asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(names(without_children), vec!["roe"]);
+
+        children.gt("age", 10);
+        let with_teens = manager
+            .select_from_table("families", |q| {
+                q.select("name")
+                    .exists(children)
+                    .or_where(|q| {
+                        q.is_eq("name", "roe");
+                    })
+                    .asc("id");
+            })
+            .fetch_all()
+            .await
+            .unwrap();
+        assert_eq!(names(with_teens), vec!["doe", "roe"]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement("CREATE TABLE items (id INTEGER NOT NULL, kind TEXT NOT NULL)")
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "INSERT INTO items VALUES (1, 'a'), (2, 'b'), (3, 'a'), (4, 'c'), (5, 'a'), (6, 'b'), (7, 'a')",
+            )
+            .await
+            .unwrap();
+
+        let ids = |rows: &[crate::types::StructuredColumnAndValue]| {
+            rows.iter()
+                .map(|r| r.fields_ref().get("id").unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let page = manager
+            .select_from_table("items", |q| {
+                q.is_eq("kind", "a").desc("id");
+            })
+            .paginate(2, 3)
+            .await
+            .unwrap();
+        assert_eq!(ids(page.data()), vec!["1"]);
+        assert_eq!(page.total(), 4);
+        assert_eq!(page.last_page(), 2);
+        assert_eq!(page.current_page(), 2);
+
+        let groups = manager
+            .select_from_table("items", |q| {
+                q.select("kind").group_by(["kind"]).asc("kind");
+            })
+            .paginate(1, 2)
+            .await
+            .unwrap();
+        assert_eq!(groups.total(), 3);
+        assert_eq!(groups.data().len(), 2);
+
+        // a row that can not be converted fails the page instead of being left out
+        struct Kind;
+        impl crate::types::FromColumnAndValue for Kind {
+            fn from_column_value(row: crate::types::ColumnAndValue) -> Result<Self, anyhow::Error> {
+                match row.get("kind").map(|v| v.to_string()) {
+                    Some(kind) if kind == "c" => Err(anyhow::anyhow!("unknown kind")),
+                    _ => Ok(Self),
+                }
+            }
+        }
+        let page = manager
+            .select_from_table("items", |q| {
+                q.asc("id");
+            })
+            .paginate_to::<Kind>(1, 3)
+            .await
+            .unwrap();
+        assert_eq!(page.data().len(), 3);
+        assert!(
+            manager
+                .select_from_table("items", |q| {
+                    q.asc("id");
+                })
+                .paginate_to::<Kind>(2, 3)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_column_cursor() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .raw_statement("CREATE TABLE people (id INTEGER NOT NULL, age INTEGER NOT NULL)")
+            .await
+            .unwrap();
+        manager
+            .raw_statement(
+                "INSERT INTO people VALUES (1, 30), (2, 20), (3, 30), (4, 20), (5, 40), (6, 30)",
+            )
+            .await
+            .unwrap();
+
+        let mut cursor = crate::base::cursor_builder::CursorBuilder::new("age", None);
+        cursor.then_by("id").set_limit(2);
+
+        let mut pages = Vec::new();
+        loop {
+            let (next, rows) = manager
+                .select_from_table("people", |_| {})
+                .cursor_paginate(cursor.clone())
+                .await
+                .parts();
+            let rows = rows.unwrap();
+            if rows.is_empty() {
+                break;
+            }
+            pages.push(
+                rows.iter()
+                    .map(|r| r.fields_ref().get("id").unwrap().to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            );
+            cursor = crate::base::cursor_builder::CursorBuilder::decode(&next.encode()).unwrap();
+        }
+        assert_eq!(pages, vec!["2,4", "1,3", "6,5"]);
+
+        let mut cursor = crate::base::cursor_builder::CursorBuilder::new("age", None);
+        cursor.then_by("id").set_desc().set_limit(4);
+        let (next, rows) = manager
+            .select_from_table("people", |_| {})
+            .cursor_paginate(cursor)
+            .await
+            .parts();
+        assert_eq!(rows.unwrap().len(), 4);
+        let rows = manager
+            .select_from_table("people", |_| {})
+            .cursor_paginate(next)
+            .await
+            .parts()
+            .1
+            .unwrap();
+        let ids = rows
+            .iter()
+            .map(|r| r.fields_ref().get("id").unwrap().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(ids, vec!["4", "2"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_result() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .create_table_schema("people", |bp| {
+                bp.id(None);
+                bp.string("name");
+            })
+            .await
+            .unwrap();
+
+        let row = |name: &str| HashMap::from([("name".to_string(), FieldValue::from(name))]);
+
+        let result = manager.insert("people", row("ann")).await.unwrap();
+        assert_eq!(result.rows_affected(), 1);
+        assert_eq!(result.last_insert_id(), Some(1));
+
+        let result = manager
+            .insert_multi("people", vec![row("bob"), row("bob")])
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), 2);
+        assert_eq!(result.last_insert_id(), Some(3));
+
+        let result = manager
+            .update("people", row("rob"), |q| {
+                q.is_eq("name", "bob");
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected(), 2);
+        assert_eq!(result.last_insert_id(), None);
+
+        let result = manager
+            .delete("people", |q| {
+                q.is_eq("name", "bob");
+            })
+            .await
+            .unwrap();
+        assert!(result.is_unchanged());
+    }
+
+    #[tokio::test]
+    async fn test_describe_table() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        manager
+            .create_table_schema("authors", |table| {
+                table.id(None);
+                table.string("name");
+            })
+            .await
+            .unwrap();
+        manager
+            .create_table_schema("posts", |table| {
+                table.id(None);
+                table.sized_string("slug", 80).set_is_unique(true);
+                table.text("body").nullable();
+                table.integer("author_id").references("authors", "id", true);
+            })
+            .await
+            .unwrap();
+        manager
+            .raw_statement("INSERT INTO authors (name) VALUES ('one'), ('two')")
+            .await
+            .unwrap();
+
+        assert_eq!(manager.tables().await.unwrap(), vec!["authors", "posts"]);
+        assert!(manager.describe_table("likes").await.unwrap().is_none());
+
+        let posts = manager.describe_table("posts").await.unwrap().unwrap();
+        let id = posts.column("id").unwrap();
+        assert!(id.is_primary && id.auto_increment);
+        assert_eq!(
+            posts.column("slug").unwrap().column_type,
+            ColumnType::String(80)
+        );
+        assert!(posts.column("body").unwrap().is_nullable);
+        assert!(
+            posts
+                .indexes
+                .iter()
+                .any(|index| index.is_unique && index.columns == ["slug"])
+        );
+        assert_eq!(posts.foreign_keys[0].column, "author_id");
+        assert_eq!(posts.foreign_keys[0].foreign_table, "authors");
+        assert_eq!(posts.foreign_keys[0].on_delete.as_deref(), Some("CASCADE"));
+
+        let database = manager.describe_database().await.unwrap();
+        assert_eq!(
+            database.tables,
+            vec![("authors".to_string(), 2), ("posts".to_string(), 0)]
+        );
+        assert!(database.size.is_some_and(|size| size > 0));
+    }
+
+    #[tokio::test]
+    async fn test_null_round_trip() {
+        let manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        crate::connector::assert_null_round_trip(&manager).await;
+    }
+
+    #[tokio::test]
+    async fn test_connection_sets() {
+        use crate::base::manager::{ConnectionResolver, Manager};
+        use std::sync::{
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        let mut manager = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        let analytics = crate::connector::sqlite::make_sqlite_in_memory_manager().await;
+        analytics
+            .raw_statement("CREATE TABLE page_views (path TEXT NOT NULL)")
+            .await
+            .unwrap();
+
+        // "main" resolves to the manager itself, like the default set does
+        let main: Arc<Mutex<Option<Manager>>> = Arc::default();
+        let resolved = Arc::new(AtomicUsize::new(0));
+        let resolver: ConnectionResolver = {
+            let main = main.clone();
+            let resolved = resolved.clone();
+            Arc::new(move |name| {
+                let main = main.lock().unwrap().clone();
+                let analytics = analytics.clone();
+                resolved.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    match name.as_str() {
+                        "analytics" => Ok(analytics),
+                        "primary" => Ok(main.unwrap()),
+                        _ => Err(anyhow::anyhow!("unknown connection {}", name)),
+                    }
+                })
+            })
+        };
+        manager.set_connection_resolver("default", resolver);
+        *main.lock().unwrap() = Some(manager.clone());
+
+        assert!(!manager.has_table("page_views").await.unwrap());
+
+        let first = manager.connection("analytics").await.unwrap();
+        assert_eq!(first.connection_name(), "analytics");
+        assert!(first.has_table("page_views").await.unwrap());
+
+        // the set is cached and shared with the copies of the manager
+        let copy = manager.clone();
+        assert!(copy.connection("analytics").await.is_ok());
+        assert!(
+            manager
+                .on_connection("analytics")
+                .has_table("page_views")
+                .await
+                .unwrap()
+        );
+        assert_eq!(resolved.load(Ordering::SeqCst), 1);
+
+        // the manager's own set is neither resolved nor cached
+        let same = manager.connection("default").await.unwrap();
+        assert!(!same.has_table("page_views").await.unwrap());
+        assert_eq!(resolved.load(Ordering::SeqCst), 1);
+        let primary = manager.connection("primary").await.unwrap();
+        assert_eq!(primary.connection_name(), "default");
+        assert!(manager.connection("primary").await.is_ok());
+        assert_eq!(resolved.load(Ordering::SeqCst), 3);
+
+        // the pretend, stats and transaction state follow the manager
+        let pretending = manager.pretend().connection("analytics").await.unwrap();
+        assert!(pretending.is_pretending());
+        let stats = QueryStats::new();
+        let counted = manager
+            .with_query_stats(stats.clone())
+            .connection("analytics")
+            .await
+            .unwrap();
+        let rows = counted
+            .select_from_table("page_views", |_| ())
+            .count()
+            .await
+            .unwrap();
+        assert_eq!(rows, 0);
+        assert_eq!(stats.count(), 1);
+        assert!(
+            !manager
+                .connection("analytics")
+                .await
+                .unwrap()
+                .is_pretending()
+        );
+
+        let in_trans = manager
+            .transaction(|trans| async move {
+                Ok(trans.connection("analytics").await?.is_in_transaction())
+            })
+            .await
+            .unwrap();
+        assert!(in_trans);
+        assert_eq!(resolved.load(Ordering::SeqCst), 3);
+
+        let error = manager.connection("reports").await.unwrap_err();
+        assert_eq!(error.to_string(), "unknown connection reports");
+        assert!(
+            manager
+                .on_connection("reports")
+                .has_table("page_views")
+                .await
+                .is_err()
+        );
+    }
 }
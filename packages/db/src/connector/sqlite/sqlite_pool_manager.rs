@@ -7,6 +7,7 @@ use crate::{
     },
     config::{ConfigSet, ConnectionConfig},
     connector::sqlite::LOG_TARGET,
+    make_replica_pool,
     pool_manager_resolver::DbPoolManagerResolver,
 };
 use anyhow::anyhow;
@@ -52,6 +53,19 @@ impl ConnectionPoolTrait for SqlitePoolManager {
         }
         tracing::trace!(target: LOG_TARGET,"connection closed: {}", self.db_pool.is_closed());
     }
+
+    async fn ping(&self) -> Result<(), anyhow::Error> {
+        sqlx::query("SELECT 1")
+            .execute(self.db_pool.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    fn in_use(&self) -> u32 {
+        self.db_pool
+            .size()
+            .saturating_sub(self.db_pool.num_idle() as u32)
+    }
 }
 
 pub async fn resolve(
@@ -60,6 +74,24 @@ pub async fn resolve(
     let mut pools: HashMap<ClientType, Box<dyn ConnectionPoolTrait>> = HashMap::new();
     for (client_type, config) in config_set.iter() {
         if SQLITE_KIND == config.kind_ref().as_str() && config.enable {
+            if *client_type == ClientType::Read {
+                let replicas = make_replica_pool(config, |config, lazy| async move {
+                    let db_pool = if lazy {
+                        db_connect_lazy(&config)?
+                    } else {
+                        db_connect(&config).await?
+                    };
+                    Ok(Box::new(SqlitePoolManager {
+                        db_pool: Arc::new(db_pool),
+                    }) as Box<dyn ConnectionPoolTrait>)
+                })
+                .await;
+                if let Some(pool) = replicas {
+                    pools.insert(ClientType::Read, pool);
+                }
+                continue;
+            }
+
             match db_connect(config).await {
                 Ok(db_pool) => {
                     pools.insert(
@@ -83,23 +115,32 @@ pub async fn resolve(
     }
 }
 
-pub async fn db_connect(config: &ConnectionConfig) -> anyhow::Result<Pool<Sqlite>> {
-    let mut option = SqliteConnectOptions::from_str(&config.url)
+fn connect_options(config: &ConnectionConfig) -> SqliteConnectOptions {
+    let option = SqliteConnectOptions::from_str(&config.url)
         .unwrap()
         .foreign_keys(true)
         .create_if_missing(true);
 
     if config.client_type == ClientType::Write {
-        option = option
+        option
             .journal_mode(SqliteJournalMode::Wal)
-            .busy_timeout(Duration::from_secs(config.busy_timeout.unwrap_or(5)));
+            .busy_timeout(Duration::from_secs(config.busy_timeout.unwrap_or(5)))
     } else {
-        option = option.read_only(true);
+        option.read_only(true)
     }
+}
 
+/// Creates the pool without connecting, the connections are made when they are used
+pub fn db_connect_lazy(config: &ConnectionConfig) -> anyhow::Result<Pool<Sqlite>> {
+    Ok(SqlitePoolOptions::new()
+        .max_connections(config.max)
+        .connect_lazy_with(connect_options(config)))
+}
+
+pub async fn db_connect(config: &ConnectionConfig) -> anyhow::Result<Pool<Sqlite>> {
     match SqlitePoolOptions::new()
         .max_connections(config.max)
-        .connect_with(option)
+        .connect_with(connect_options(config))
         .await
     {
         Ok(conn) => {
@@ -113,3 +154,33 @@ pub async fn db_connect(config: &ConnectionConfig) -> anyhow::Result<Pool<Sqlite
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unreachable_replica_is_kept_ejected() {
+        let config = ConnectionConfig {
+            client_type: ClientType::Read,
+            url: "sqlite:/dirtybase-missing-dir/replica.db".to_string(),
+            ..ConnectionConfig::default()
+        };
+
+        let pool = make_replica_pool(&config, |config, lazy| async move {
+            let db_pool = if lazy {
+                db_connect_lazy(&config)?
+            } else {
+                db_connect(&config).await?
+            };
+            Ok(Box::new(SqlitePoolManager {
+                db_pool: Arc::new(db_pool),
+            }) as Box<dyn ConnectionPoolTrait>)
+        })
+        .await
+        .unwrap();
+
+        assert!(!pool.is_available());
+        assert!(pool.ping().await.is_err());
+    }
+}
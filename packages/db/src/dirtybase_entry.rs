@@ -1,6 +1,21 @@
-use dirtybase_contract::{ExtensionSetup, app_contract::Context, cli_contract::CliCommandManager};
+use dirtybase_contract::{
+    ExtensionSetup,
+    app_contract::Context,
+    cli_contract::CliCommandManager,
+    config_contract::DirtyConfig,
+    db_contract::base::{manager::Manager, query_log::QueryStats},
+    http_contract::{
+        HttpContext,
+        prelude::{CookieJar, Request, Response},
+    },
+};
 
-use crate::{command::setup_commands, resource_manager::register_resource_manager};
+use crate::{
+    command::setup_commands, config::DbConfig, resource_manager::register_resource_manager,
+};
+
+/// Number of times the same read may run during a request before it is reported as an N+1 query
+const N_PLUS_ONE_THRESHOLD: u32 = 5;
 
 #[derive(Debug, Default)]
 pub struct Extension;
@@ -14,4 +29,55 @@ impl ExtensionSetup for Extension {
     fn register_cli_commands(&self, manager: CliCommandManager) -> CliCommandManager {
         setup_commands(manager)
     }
+
+    async fn on_web_request(&self, req: Request, context: Context, _cookie: &CookieJar) -> Request {
+        let enabled = context
+            .get_config_once::<DbConfig>("database")
+            .await
+            .is_ok_and(|config| config.is_enable());
+        if !enabled {
+            return req;
+        }
+
+        // the queries of this request are counted by its own copy of the manager
+        if let Ok(manager) = context.get::<Manager>().await {
+            let is_dev = context
+                .get::<DirtyConfig>()
+                .await
+                .is_ok_and(|config| config.current_env().is_dev());
+            let stats = if is_dev {
+                QueryStats::with_n_plus_one_detection(N_PLUS_ONE_THRESHOLD)
+            } else {
+                QueryStats::new()
+            };
+
+            context.set(manager.with_query_stats(stats.clone())).await;
+            context.set(stats).await;
+        }
+
+        req
+    }
+
+    async fn on_web_response(
+        &self,
+        resp: Response,
+        cookie_jar: CookieJar,
+        context: Context,
+    ) -> (Response, CookieJar) {
+        if let Ok(stats) = context.get::<QueryStats>().await {
+            let path = context
+                .get::<HttpContext>()
+                .await
+                .map(|http| http.path().to_string())
+                .unwrap_or_default();
+            log::debug!(
+                target: "dirtybase_db",
+                "{} ran {} queries",
+                path,
+                stats.count()
+            );
+        }
+
+        (resp, cookie_jar)
+    }
 }
@@ -7,10 +7,10 @@ mod dirtybase_entry;
 mod model;
 mod resource_manager;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use base::schema::DatabaseKind;
-use config::ConfigSet;
+use base::{connection::ConnectionPoolTrait, replica::ReplicaPool, schema::DatabaseKind};
+use config::{ConfigSet, ConnectionConfig};
 use connector::{
     mariadb::mariadb_pool_manager::MariadbPoolManager, mysql::mysql_pool_manager::MysqlPoolManager,
     postgres::postgres_pool_manager::PostgresPoolManager,
@@ -39,13 +39,68 @@ pub fn make_manager(
         is_writable = true;
     }
 
-    Manager::new(
+    let mut manager = Manager::new(
         Arc::new(connections),
         kind,
         write_is_sticky,
         sticky_duration,
         is_writable,
-    )
+    );
+    manager.set_slow_query_threshold(
+        config_set
+            .values()
+            .filter_map(|config| config.slow_query_ms)
+            .min()
+            .map(Duration::from_millis),
+    );
+
+    manager
+}
+
+/// Connects to the read replicas of the config. `connect` is called with `true` to create a
+/// replica's pool without connecting when it can not be reached, the replica is then ejected
+/// until a health check finds it reachable. `None` is returned when no pool could be created,
+/// the writer is then used for reads
+pub async fn make_replica_pool<F, Fut>(
+    config: &ConnectionConfig,
+    connect: F,
+) -> Option<Box<dyn ConnectionPoolTrait>>
+where
+    F: Fn(ConnectionConfig, bool) -> Fut,
+    Fut: Future<Output = Result<Box<dyn ConnectionPoolTrait>, anyhow::Error>>,
+{
+    let mut pools = Vec::new();
+    let mut unreachable = Vec::new();
+    for url in config.replica_urls() {
+        let mut replica = config.clone();
+        replica.url = url;
+        match connect(replica.clone(), false).await {
+            Ok(pool) => pools.push(pool),
+            Err(e) => {
+                tracing::error!(target: "dirtybase_db", "could not connect to a read replica: {}", e);
+                match connect(replica, true).await {
+                    Ok(pool) => {
+                        unreachable.push(pools.len());
+                        pools.push(pool);
+                    }
+                    Err(e) => {
+                        tracing::error!(target: "dirtybase_db", "could not add a read replica: {}", e)
+                    }
+                }
+            }
+        }
+    }
+
+    if pools.is_empty() {
+        return None;
+    }
+
+    let pool = ReplicaPool::new(pools, config.replica_options());
+    for index in unreachable {
+        pool.eject(index);
+    }
+    pool.start_health_checks();
+    Some(Box::new(pool))
 }
 
 pub async fn setup_pool_resolvers() {
@@ -4,6 +4,7 @@ use dirtybase_contract::db_contract::types::{
 };
 
 pub(crate) const TABLE_NAME: &str = "migrations";
+pub(crate) const ID_COLUMN: &str = "id";
 pub(crate) const NAME_COLUMN: &str = "name";
 pub(crate) const BATCH_COLUMN: &str = "batch";
 pub(crate) const CREATED_AT_COLUMN: &str = "created_at";
@@ -17,13 +18,24 @@ pub struct MigrationEntity {
     pub(crate) created_at: OptionalDateTimeField,
 }
 
+impl MigrationEntity {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// The batch the migration ran in
+    pub fn batch(&self) -> IntegerField {
+        self.batch
+    }
+}
+
 impl FromColumnAndValue for MigrationEntity {
     fn from_column_value(
         mut cv: dirtybase_contract::db_contract::types::ColumnAndValue,
     ) -> Result<Self, anyhow::Error> {
         Ok(Self {
             id: cv
-                .remove("id")
+                .remove(ID_COLUMN)
                 .map(InternalIdField::from)
                 .ok_or(anyhow!("migration entity id field is missing"))?,
             name: cv
@@ -1,8 +1,8 @@
-use dirtybase_contract::db_contract::{base::manager::Manager, types::StringField};
+use dirtybase_contract::db_contract::base::manager::Manager;
 use dirtybase_helper::time::current_datetime;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 
-use super::{BATCH_COLUMN, CREATED_AT_COLUMN, MigrationEntity, NAME_COLUMN, TABLE_NAME};
+use super::{BATCH_COLUMN, CREATED_AT_COLUMN, ID_COLUMN, MigrationEntity, NAME_COLUMN, TABLE_NAME};
 
 pub struct MigrationRepository {
     manager: Manager,
@@ -21,7 +21,7 @@ impl MigrationRepository {
         self.manager
             .create_table_schema(TABLE_NAME, |table| {
                 // id
-                table.id(Some("id"));
+                table.id(Some(ID_COLUMN));
 
                 // migration name
                 table.text(NAME_COLUMN);
@@ -48,47 +48,62 @@ impl MigrationRepository {
             .await
     }
 
-    pub async fn get_batch(&self, batch: i64) -> BTreeMap<StringField, MigrationEntity> {
-        if let Ok(collection) = self
-            .manager
+    /// All the migrations that ran, in the order they ran
+    pub async fn all(&self) -> Vec<MigrationEntity> {
+        self.manager
+            .select_from_table(TABLE_NAME, |q| {
+                q.asc(BATCH_COLUMN).asc(ID_COLUMN);
+            })
+            .fetch_all_to::<MigrationEntity>()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// The migrations that ran in the batch, the most recent first
+    pub async fn get_batch(&self, batch: i64) -> Vec<MigrationEntity> {
+        self.manager
             .select_from_table(TABLE_NAME, |q| {
-                q.is_eq(BATCH_COLUMN, batch).desc("created_at");
+                q.is_eq(BATCH_COLUMN, batch).desc(ID_COLUMN);
             })
             .fetch_all_to::<MigrationEntity>()
             .await
-        {
-            return collection
-                .into_iter()
-                .map(|m| (m.name.clone(), m))
-                .collect::<BTreeMap<StringField, MigrationEntity>>();
+            .unwrap_or_default()
+    }
+
+    /// The migrations that ran in the last batch, the most recent first
+    pub async fn get_last_batch(&self) -> Vec<MigrationEntity> {
+        match self.last_batch().await {
+            Some(batch) => self.get_batch(batch).await,
+            None => Vec::new(),
         }
+    }
 
-        BTreeMap::new()
+    /// The last `total` migrations that ran, the most recent first
+    pub async fn get_latest(&self, total: usize) -> Vec<MigrationEntity> {
+        self.manager
+            .select_from_table(TABLE_NAME, |q| {
+                q.desc(BATCH_COLUMN).desc(ID_COLUMN).limit(total);
+            })
+            .fetch_all_to::<MigrationEntity>()
+            .await
+            .unwrap_or_default()
     }
 
-    pub async fn get_last_batch(&self) -> BTreeMap<StringField, MigrationEntity> {
-        if let Ok(Some(last)) = self
-            .manager
+    pub async fn last_batch(&self) -> Option<i64> {
+        self.manager
             .select_from_table(TABLE_NAME, |q| {
                 q.desc(BATCH_COLUMN);
             })
             .fetch_one_to::<MigrationEntity>()
             .await
-            && let Ok(collection) = self
-                .manager
-                .select_from_table(TABLE_NAME, |q| {
-                    q.is_eq(BATCH_COLUMN, last.batch).desc("created_at");
-                })
-                .fetch_all_to::<MigrationEntity>()
-                .await
-        {
-            return collection
-                .into_iter()
-                .map(|m| (m.name.clone(), m))
-                .collect::<BTreeMap<StringField, MigrationEntity>>();
-        }
+            .ok()
+            .flatten()
+            .map(|m| m.batch)
+    }
 
-        BTreeMap::new()
+    /// The batch number the next migrations will be recorded under
+    pub async fn next_batch(&self) -> i64 {
+        self.last_batch().await.unwrap_or_default() + 1
     }
 
     pub async fn delete_batch(&self, batch: i64) {
@@ -105,7 +120,8 @@ impl MigrationRepository {
             .delete(TABLE_NAME, |q| {
                 q.is_eq(NAME_COLUMN, name);
             })
-            .await
+            .await?;
+        Ok(())
     }
 
     pub async fn create(
@@ -1,6 +1,9 @@
-use anyhow::Context as AnyhowCtx;
+use std::sync::Arc;
+
+use anyhow::{Context as AnyhowCtx, anyhow};
 use dirtybase_contract::{
-    app_contract::ContextResourceManager, db_contract::base::manager::Manager,
+    app_contract::{Context, ContextResourceManager},
+    db_contract::base::manager::{ConnectionResolver, Manager},
     prelude::ResourceManager,
 };
 
@@ -34,9 +37,13 @@ pub(crate) async fn register_resource_manager() {
                 let default_set = config
                     .default_set()
                     .expect("could not get default db config set");
-                DbPoolManagerResolver::new(context, default_set)
+                let mut manager = DbPoolManagerResolver::new(context.clone(), default_set)
                     .get_manager()
-                    .await
+                    .await?;
+                let name = config.default_name().to_string();
+                manager.set_connection_resolver(&name, connection_resolver(context, config));
+
+                Ok(manager)
             })
         },
         move |manager| {
@@ -48,3 +55,22 @@ pub(crate) async fn register_resource_manager() {
     )
     .await;
 }
+
+/// Builds the managers of the other connection sets. The default set resolves to the tenant's manager
+fn connection_resolver(context: Context, config: DbConfig) -> ConnectionResolver {
+    Arc::new(move |name| {
+        let context = context.clone();
+        let config = config.clone();
+        Box::pin(async move {
+            if name == config.default_name() {
+                return context.get::<Manager>().await;
+            }
+
+            let set = config
+                .get_set(&name)
+                .filter(|set| set.values().any(|c| c.enable))
+                .ok_or_else(|| anyhow!("database connection `{}` is not configured", name))?;
+            DbPoolManagerResolver::new(context, set).get_manager().await
+        })
+    })
+}
@@ -0,0 +1,90 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut user_repo = UserRepo::new(&manager);
+
+    user_repo.attach_roles(1, [1, 2, 3]).await.unwrap();
+    user_repo.attach_roles(2, [2]).await.unwrap();
+    user_repo.detach_roles(1, [3]).await.unwrap();
+    user_repo.sync_roles(2, [1, 3]).await.unwrap();
+
+    println!("{:#?}", user_repo.with_roles().get().await);
+    println!("{:#?}", user_repo.with_count_roles().get().await);
+
+    // users with the "viewer" role
+    println!(
+        "{:#?}",
+        user_repo
+            .where_has_roles(|query| {
+                query.is_eq(Role::prefix_with_tbl(Role::col_name_for_name()), "viewer");
+            })
+            .get()
+            .await
+    );
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct User {
+    id: Option<i64>,
+    name: String,
+    #[dirty(rel(kind = belongs_to_many, pivot = "role_user", no_soft_delete))]
+    roles: Option<Vec<Role>>,
+    #[dirty(count_of = roles)]
+    roles_count: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Role {
+    id: Option<i64>,
+    name: String,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(User::table_name(), |table| {
+            table.id(None);
+            table.string(User::col_name_for_name());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Role::table_name(), |table| {
+            table.id(None);
+            table.string(Role::col_name_for_name());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema("role_user", |table| {
+            table.id_table_fk::<User>(true);
+            table.id_table_fk::<Role>(true);
+        })
+        .await;
+
+    for name in ["jane", "john"] {
+        _ = manager
+            .insert_into::<User>(User {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .await;
+    }
+
+    for name in ["admin", "editor", "viewer"] {
+        _ = manager
+            .insert_into::<Role>(Role {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .await;
+    }
+}
@@ -0,0 +1,49 @@
+use dirtybase_db::{
+    TableModel,
+    base::manager::Manager,
+    connector::sqlite::make_sqlite_in_memory_manager,
+    types::{BooleanField, OptionalDateTimeField, StringField},
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    create_tables(&manager).await;
+
+    for column in Article::blueprint().columns() {
+        println!(
+            "{}: {:?}, nullable: {:?}",
+            column.name, column.column_type, column.is_nullable
+        );
+    }
+
+    let mut repo = ArticleRepo::new(&manager);
+    let article = repo
+        .insert(Article {
+            title: "Hello".to_string().into(),
+            published: true,
+            ..Default::default()
+        })
+        .await;
+    println!("{:#?}", article);
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(created_at = "posted_at", no_soft_delete)]
+struct Article {
+    id: Option<i64>,
+    title: StringField,
+    summary: Option<String>,
+    published: BooleanField,
+    reviewed_at: OptionalDateTimeField,
+    posted_at: OptionalDateTimeField,
+    updated_at: OptionalDateTimeField,
+}
+
+async fn create_tables(manager: &Manager) {
+    manager
+        .create_table_for::<Article>()
+        .await
+        .expect("could not create the articles table");
+}
@@ -0,0 +1,104 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+use tokio_stream::StreamExt;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut author_repo = AuthorRepo::new(&manager);
+
+    // batches of three authors with their books
+    _ = author_repo
+        .with_books()
+        .chunk(3, |authors| async move {
+            println!(
+                "{:?}",
+                authors
+                    .iter()
+                    .map(|a| format!("{} ({} books)", a.name, a.books.len()))
+                    .collect::<Vec<_>>()
+            );
+            true
+        })
+        .await;
+
+    // stops after the second batch
+    let mut batches = 0;
+    _ = author_repo
+        .chunk_by_id(4, |authors| {
+            batches += 1;
+            let last = authors.last().map(|a| a.name.clone());
+            let keep_going = batches < 2;
+            async move {
+                println!("batch ending with {:?}", last);
+                keep_going
+            }
+        })
+        .await;
+
+    // one author at a time
+    let mut stream = author_repo.with_books().lazy(5);
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(author) => println!("{}: {} books", author.name, author.books.len()),
+            Err(e) => println!("error: {e}"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Author {
+    id: Option<i64>,
+    name: String,
+    #[dirty(rel(kind = has_many, no_soft_delete))]
+    books: Vec<Book>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Book {
+    id: Option<i64>,
+    title: String,
+    author_id: i64,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Author::table_name(), |table| {
+            table.id(None);
+            table.string(Author::col_name_for_name());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Book::table_name(), |table| {
+            table.id(None);
+            table.string(Book::col_name_for_title());
+            table.integer(Book::col_name_for_author_id());
+        })
+        .await;
+
+    for author_id in 1..=10 {
+        _ = manager
+            .insert_into::<Author>(Author {
+                name: format!("author {author_id}"),
+                ..Default::default()
+            })
+            .await;
+
+        for book in 1..=(author_id % 3) {
+            _ = manager
+                .insert_into::<Book>(Book {
+                    title: format!("book {book} of author {author_id}"),
+                    author_id,
+                    ..Default::default()
+                })
+                .await;
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use dirtybase_db::{
+    TableModel,
+    base::manager::{ConnectionResolver, Manager},
+    connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let mut manager = make_sqlite_in_memory_manager().await;
+    let analytics = make_sqlite_in_memory_manager().await;
+    setup_db(&analytics).await;
+
+    // usually set from database.toml's connection sets
+    let resolver: ConnectionResolver = Arc::new(move |name| {
+        let analytics = analytics.clone();
+        Box::pin(async move {
+            if name == "analytics" {
+                Ok(analytics)
+            } else {
+                Err(dirtybase_db::anyhow::anyhow!("unknown connection {}", name))
+            }
+        })
+    });
+    manager.set_connection_resolver("main", resolver);
+
+    // the repo runs its queries on the `analytics` connection
+    let mut repo = PageViewRepo::new(&manager);
+    repo.insert(PageView {
+        path: "/home".to_string(),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    println!("{:#?}", repo.get().await);
+
+    // the default connection does not have the table
+    println!("{:?}", manager.has_table(PageView::table_name()).await);
+
+    let analytics = manager.connection("analytics").await.unwrap();
+    println!("{:?}", analytics.has_table(PageView::table_name()).await);
+    println!(
+        "{:?}",
+        manager
+            .connection("reports")
+            .await
+            .map_err(|e| e.to_string())
+            .err()
+    );
+
+    // a connection that cannot be resolved fails the query
+    println!(
+        "{:?}",
+        manager
+            .on_connection("reports")
+            .has_table(PageView::table_name())
+            .await
+            .map_err(|e| e.to_string())
+    );
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(connection = "analytics", no_timestamp, no_soft_delete)]
+struct PageView {
+    id: Option<i64>,
+    path: String,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(PageView::table_name(), |table| {
+            table.id(None);
+            table.string(PageView::col_name_for_path());
+        })
+        .await;
+}
@@ -0,0 +1,86 @@
+use dirtybase_common::{
+    app::observable::Observable,
+    db::event::{ModelEvent, ModelEventKind},
+};
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+    types::DeletedAtField,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    ModelEvent::<Post>::subscribe(|mut event| async move {
+        match event.kind() {
+            // slug generation
+            ModelEventKind::Creating | ModelEventKind::Updating => {
+                if let Some(post) = event.model_mut() {
+                    post.slug = post.title.to_lowercase().replace(' ', "-");
+                }
+            }
+            // pinned posts can not be deleted
+            ModelEventKind::Deleting if event.model().is_some_and(|post| post.pinned) => {
+                event.veto("pinned posts can not be deleted");
+            }
+            // audit trail
+            kind if !kind.is_before() => println!("audit: {:?} post {:?}", kind, event.id()),
+            _ => (),
+        }
+        event
+    })
+    .await;
+
+    let mut post_repo = PostRepo::new(&manager);
+
+    let mut post = post_repo
+        .insert(Post {
+            title: "Hello World".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    println!("{:#?}", &post);
+
+    post.pinned = true;
+    let post = post_repo.update(post).await.unwrap();
+    println!(
+        "{:?}",
+        post_repo.delete(post).await.map_err(|e| e.to_string())
+    );
+
+    let post = post_repo
+        .insert(Post {
+            title: "Good Bye".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let id = post.id.unwrap();
+    println!("{:#?}", post_repo.delete(post).await);
+    println!("{:#?}", post_repo.restore(id).await);
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp)]
+struct Post {
+    id: Option<i64>,
+    title: String,
+    slug: String,
+    pinned: bool,
+    deleted_at: DeletedAtField,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Post::table_name(), |table| {
+            table.id(None);
+            table.string(Post::col_name_for_title());
+            table.string(Post::col_name_for_slug());
+            table.boolean(Post::col_name_for_pinned());
+            table.soft_deletable();
+        })
+        .await;
+}
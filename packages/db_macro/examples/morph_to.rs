@@ -0,0 +1,122 @@
+use dirtybase_common::db::repo_relation::{MorphRegistry, MorphToRegistry};
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut comment_repo = CommentRepo::new(&manager);
+    let comments = comment_repo.with_commentable().get().await;
+    println!("{:#?}", comments);
+
+    for comment in comments.ok().flatten().unwrap_or_default() {
+        match comment.commentable {
+            Some(Commentable::Post(post)) => println!("{} on post: {}", comment.body, post.title),
+            Some(Commentable::Video(video)) => println!("{} on video: {}", comment.body, video.url),
+            None => println!("{} on nothing", comment.body),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Comment {
+    id: Option<i64>,
+    body: String,
+    commentable_id: i64,
+    commentable_type: String,
+    #[dirty(rel(kind = "morph_to", no_soft_delete))]
+    commentable: Option<Commentable>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Post {
+    id: Option<i64>,
+    title: String,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Video {
+    id: Option<i64>,
+    url: String,
+}
+
+#[derive(Debug, Clone)]
+enum Commentable {
+    Post(Post),
+    Video(Video),
+}
+
+impl MorphToRegistry for Commentable {
+    fn register(registry: &mut MorphRegistry<Self>) {
+        registry
+            .add::<Post>("post", Commentable::Post)
+            .add::<Video>("video", Commentable::Video);
+    }
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Post::table_name(), |table| {
+            table.id(None);
+            table.string(Post::col_name_for_title());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Video::table_name(), |table| {
+            table.id(None);
+            table.string(Video::col_name_for_url());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Comment::table_name(), |table| {
+            table.id(None);
+            table.string(Comment::col_name_for_body());
+            table.integer(Comment::col_name_for_commentable_id());
+            table.string(Comment::col_name_for_commentable_type());
+            table.index(&[
+                Comment::col_name_for_commentable_id(),
+                Comment::col_name_for_commentable_type(),
+            ]);
+        })
+        .await;
+
+    for title in ["first post", "second post"] {
+        _ = manager
+            .insert_into::<Post>(Post {
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .await;
+    }
+
+    _ = manager
+        .insert_into::<Video>(Video {
+            url: "https://example.com/video.mp4".to_string(),
+            ..Default::default()
+        })
+        .await;
+
+    for (body, id, kind) in [
+        ("nice post", 1, "post"),
+        ("nice video", 1, "video"),
+        ("great read", 2, "post"),
+    ] {
+        _ = manager
+            .insert_into::<Comment>(Comment {
+                body: body.to_string(),
+                commentable_id: id,
+                commentable_type: kind.to_string(),
+                ..Default::default()
+            })
+            .await;
+    }
+}
@@ -0,0 +1,117 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut family_repo = FamilyRepo::new(&manager);
+
+    // families, their children and the children's toys
+    println!("{:#?}", family_repo.with("children.toys").get().await);
+
+    // only the number of children
+    println!("{:#?}", family_repo.with_count_children().get().await);
+
+    // children named "child 1" and their toys
+    println!(
+        "{:#?}",
+        family_repo
+            .with_children_where(|relation| {
+                relation
+                    .query_mut()
+                    .is_eq(Child::col_name_for_name(), "child 1");
+            })
+            .with("children.toys")
+            .get()
+            .await
+    );
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Family {
+    id: Option<i64>,
+    name: String,
+    #[dirty(rel(kind = has_many, no_soft_delete))]
+    children: Vec<Child>,
+    #[dirty(count_of = children)]
+    children_count: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Child {
+    id: Option<i64>,
+    name: String,
+    family_id: i64,
+    #[dirty(rel(kind = has_many, foreign_col = child_id, no_soft_delete))]
+    toys: Vec<Toy>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Toy {
+    id: Option<i64>,
+    name: String,
+    child_id: i64,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Family::table_name(), |table| {
+            table.id(None);
+            table.string(Family::col_name_for_name());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Child::table_name(), |table| {
+            table.id(None);
+            table.string(Child::col_name_for_name());
+            table.integer(Child::col_name_for_family_id());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Toy::table_name(), |table| {
+            table.id(None);
+            table.string(Toy::col_name_for_name());
+            table.integer(Toy::col_name_for_child_id());
+        })
+        .await;
+
+    let mut child_id = 0;
+    for family_id in 1..=2 {
+        _ = manager
+            .insert_into::<Family>(Family {
+                name: format!("family {family_id}"),
+                ..Default::default()
+            })
+            .await;
+
+        for child in 1..=family_id {
+            child_id += 1;
+            _ = manager
+                .insert_into::<Child>(Child {
+                    name: format!("child {child}"),
+                    family_id,
+                    ..Default::default()
+                })
+                .await;
+
+            for toy in 1..=2 {
+                _ = manager
+                    .insert_into::<Toy>(Toy {
+                        name: format!("toy {toy} of child {child_id}"),
+                        child_id,
+                        ..Default::default()
+                    })
+                    .await;
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use dirtybase_common::db::base::stale_entity::StaleEntity;
+use dirtybase_db::{
+    TableModel,
+    base::manager::Manager,
+    connector::sqlite::make_sqlite_in_memory_manager,
+    types::{CreatedAtField, DeletedAtField, UpdatedAtField},
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut article_repo = ArticleRepo::new(&manager);
+    let article = article_repo
+        .insert(Article {
+            title: "first draft".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // two admins edit the same article
+    let mut first = article.clone();
+    let mut second = article;
+
+    first.title = "second draft".to_string();
+    let first = article_repo.update(first).await.unwrap();
+    println!("{:#?}", &first);
+
+    // the second edit is based on an old version
+    second.title = "overwritten draft".to_string();
+    let error = article_repo.update(second.clone()).await.unwrap_err();
+    println!("stale: {:?}", error.downcast_ref::<StaleEntity>());
+
+    // soft deleting an old version fails as well
+    println!(
+        "{:?}",
+        article_repo.delete(second).await.map_err(|e| e.to_string())
+    );
+
+    println!("{:#?}", article_repo.delete(first).await);
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(version = "lock_version")]
+struct Article {
+    id: Option<i64>,
+    title: String,
+    lock_version: i64,
+    created_at: CreatedAtField,
+    updated_at: UpdatedAtField,
+    deleted_at: DeletedAtField,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Article::table_name(), |table| {
+            table.id(None);
+            table.string(Article::col_name_for_title());
+            table.integer(Article::col_name_for_lock_version());
+            table.timestamps();
+            table.soft_deletable();
+        })
+        .await;
+}
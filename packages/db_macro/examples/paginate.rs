@@ -0,0 +1,83 @@
+use dirtybase_db::{
+    TableModel,
+    base::{cursor_builder::CursorBuilder, manager::Manager},
+    connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut product_repo = ProductRepo::new(&manager);
+
+    // the second page of products, most expensive first
+    let page = product_repo
+        .filter(|q| {
+            q.desc(Product::col_name_for_price());
+        })
+        .paginate(2, 5)
+        .await
+        .unwrap();
+    println!(
+        "page {} of {}, {} products in total",
+        page.current_page(),
+        page.last_page(),
+        page.total()
+    );
+    for product in page.data() {
+        println!("{}: {}", product.name, product.price);
+    }
+
+    // keyset pagination on a non unique column
+    let mut cursor = CursorBuilder::new(Product::col_name_for_price(), None);
+    cursor.then_by(Product::col_name_for_id()).set_limit(4);
+
+    loop {
+        let (next, rows) = manager
+            .select_from_table(Product::table_name(), |_| {})
+            .cursor_paginate_to::<Product>(cursor.clone())
+            .await
+            .parts();
+        let rows = rows.unwrap();
+        if rows.is_empty() {
+            break;
+        }
+        println!(
+            "{:?}",
+            rows.iter()
+                .map(|p| format!("{}@{}", p.name, p.price))
+                .collect::<Vec<_>>()
+        );
+        cursor = next;
+    }
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Product {
+    id: Option<i64>,
+    name: String,
+    price: i64,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Product::table_name(), |table| {
+            table.id(None);
+            table.string(Product::col_name_for_name());
+            table.integer(Product::col_name_for_price());
+        })
+        .await;
+
+    for index in 1..=12 {
+        _ = manager
+            .insert_into::<Product>(Product {
+                name: format!("product {index}"),
+                price: (index % 4) * 10,
+                ..Default::default()
+            })
+            .await;
+    }
+}
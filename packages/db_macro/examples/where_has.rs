@@ -0,0 +1,111 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+    types::DeletedAtField,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[tokio::main]
+async fn main() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut family_repo = FamilyRepo::new(&manager);
+
+    // families with at least one non-trashed child
+    println!("{:#?}", family_repo.has_children().get().await);
+
+    // families without children
+    println!("{:#?}", family_repo.doesnt_have_children().get().await);
+
+    // families with a child named "child 2"
+    println!(
+        "{:#?}",
+        family_repo
+            .where_has_children(|query| {
+                query.is_eq(
+                    Child::prefix_with_tbl(Child::col_name_for_name()),
+                    "child 2",
+                );
+            })
+            .get()
+            .await
+    );
+
+    // children that belong to "family 1"
+    let mut child_repo = ChildRepo::new(&manager);
+    println!(
+        "{:#?}",
+        child_repo
+            .where_has_family(|query| {
+                query.is_eq(
+                    Family::prefix_with_tbl(Family::col_name_for_name()),
+                    "family 1",
+                );
+            })
+            .get()
+            .await
+    );
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Family {
+    id: Option<i64>,
+    name: String,
+    #[dirty(rel(kind = has_many))]
+    children: Vec<Child>,
+}
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp)]
+struct Child {
+    id: Option<i64>,
+    name: String,
+    family_id: i64,
+    deleted_at: DeletedAtField,
+    #[dirty(rel(kind = belongs_to, local_col = family_id, no_soft_delete))]
+    family: Option<Family>,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Family::table_name(), |table| {
+            table.id(None);
+            table.string(Family::col_name_for_name());
+        })
+        .await;
+
+    _ = manager
+        .create_table_schema(Child::table_name(), |table| {
+            table.id(None);
+            table.string(Child::col_name_for_name());
+            table.integer(Child::col_name_for_family_id());
+            table.soft_deletable();
+        })
+        .await;
+
+    // family 1 has two children, family 2 only has a trashed child and family 3 has none
+    for family_id in 1..=3 {
+        _ = manager
+            .insert_into::<Family>(Family {
+                name: format!("family {family_id}"),
+                ..Default::default()
+            })
+            .await;
+    }
+
+    for (name, family_id, trashed) in [
+        ("child 1", 1, false),
+        ("child 2", 1, false),
+        ("child 3", 2, true),
+    ] {
+        _ = manager
+            .insert_into::<Child>(Child {
+                name: name.to_string(),
+                family_id,
+                deleted_at: trashed.then(dirtybase_helper::time::current_datetime),
+                ..Default::default()
+            })
+            .await;
+    }
+}
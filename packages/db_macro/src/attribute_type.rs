@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use syn::{DeriveInput, Meta};
 
 use crate::relationship::{
-    belongs_to, has_many, has_many_through, has_one, has_one_through, morph_many, morph_one,
+    belongs_to, belongs_to_many, has_many, has_many_through, has_one, has_one_through, morph_many,
+    morph_one, morph_to,
 };
 
 #[derive(Debug, Clone)]
@@ -17,6 +18,8 @@ pub(crate) struct TableAttribute {
     pub(crate) created_at_col: String,
     pub(crate) updated_at_col: String,
     pub(crate) deleted_at_col: String,
+    pub(crate) version_col: Option<String>,
+    pub(crate) connection: Option<String>,
 }
 
 impl Default for TableAttribute {
@@ -31,6 +34,8 @@ impl Default for TableAttribute {
             created_at_col: "created_at".to_string(),
             updated_at_col: "updated_at".to_string(),
             deleted_at_col: "deleted_at".to_string(),
+            version_col: None,
+            connection: None,
         }
     }
 }
@@ -77,6 +82,20 @@ impl From<&DeriveInput> for TableAttribute {
                         }
                     }
 
+                    if arg.to_string() == "version" {
+                        _ = walker.next();
+                        if let Some(name) = walker.next() {
+                            value.version_col = Some(name.to_string().replace('\"', ""));
+                        }
+                    }
+
+                    if arg.to_string() == "connection" {
+                        _ = walker.next();
+                        if let Some(name) = walker.next() {
+                            value.connection = Some(name.to_string().replace('\"', ""));
+                        }
+                    }
+
                     if arg.to_string() == "table" {
                         _ = walker.next();
                         if let Some(name) = walker.next() {
@@ -133,6 +152,7 @@ pub(crate) struct DirtybaseAttributes {
     pub(crate) has_custom_into_handler: bool,
     pub(crate) skip_insert: bool, // Don't include the column in the list of columns when inserting
     pub(crate) relation: Option<RelType>,
+    pub(crate) count_of: Option<String>, // The relation counted into this field
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -172,9 +192,9 @@ pub(crate) enum RelType {
     HasOneThrough { attribute: RelationAttribute },
     HasManyThrough { attribute: RelationAttribute },
     BelongsToMany { attribute: RelationAttribute },
-    // MorphTo { attribute: RelationAttribute },
     MorphOne { attribute: RelationAttribute },
     MorphMany { attribute: RelationAttribute },
+    MorphTo { attribute: RelationAttribute },
 }
 
 impl RelType {
@@ -192,7 +212,7 @@ impl RelType {
                 attribute: belongs_to::build_attribute(attribute, field, input),
             }),
             "belongs_to_many" => Some(Self::BelongsToMany {
-                attribute: attribute.into(),
+                attribute: belongs_to_many::build_attribute(attribute, field, input),
             }),
             "has_many" => Some(Self::HasMany {
                 attribute: has_many::build_attribute(attribute, field, input),
@@ -209,6 +229,9 @@ impl RelType {
             "morph_many" => Some(Self::MorphMany {
                 attribute: morph_many::build_attribute(attribute, field, input),
             }),
+            "morph_to" => Some(Self::MorphTo {
+                attribute: morph_to::build_attribute(attribute, field, input),
+            }),
             _ => None,
         }
     }
@@ -7,7 +7,8 @@ use syn::DeriveInput;
 use crate::{
     attribute_type::{DirtybaseAttributes, RelType, TableAttribute},
     relationship::{
-        belongs_to, has_many, has_many_through, has_one, has_one_through, morph_many, morph_one,
+        self, belongs_to, belongs_to_many, has_many, has_many_through, has_one, has_one_through,
+        morph_many, morph_one, morph_to,
     },
 };
 
@@ -21,6 +22,8 @@ pub fn build_entity_repo(
     let repo_name = format_ident!("{}Repo", &input.ident);
     let mut relationship_methods = HashMap::<String, Vec<TokenStream>>::new();
     let mut append_methods = Vec::<TokenStream>::new();
+    let mut with_arms = Vec::<TokenStream>::new();
+    let mut nested_loads = Vec::<TokenStream>::new();
 
     for attr in columns_attributes.values() {
         let mut methods = Vec::new();
@@ -37,6 +40,10 @@ pub fn build_entity_repo(
                 belongs_to::generate_join_method(attr, input, &mut methods);
                 belongs_to::build_entity_append(attr, &mut append_methods);
             }
+            Some(RelType::BelongsToMany { attribute: _ }) => {
+                belongs_to_many::generate_join_method(attr, input, &mut methods);
+                belongs_to_many::build_entity_append(attr, &mut append_methods);
+            }
             Some(RelType::HasOneThrough { attribute: _ }) => {
                 has_one_through::generate_join_method(attr, input, &mut methods);
                 has_one_through::build_entity_append(attr, &mut append_methods);
@@ -53,11 +60,48 @@ pub fn build_entity_repo(
                 morph_many::generate_join_method(attr, input, &mut methods);
                 morph_many::build_entity_append(attr, &mut append_methods);
             }
+            Some(RelType::MorphTo { attribute: _ }) => {
+                morph_to::generate_join_method(attr, input, &mut methods);
+                morph_to::build_entity_append(attr, &mut append_methods);
+            }
             _ => (),
         }
+        relationship::generate_exists_method(attr, input, &mut methods);
+        if attr.relation.is_some() && !matches!(attr.relation, Some(RelType::MorphTo { .. })) {
+            with_arms.push(build_with_arm(attr));
+            nested_loads.push(build_nested_load(attr));
+        }
         relationship_methods.insert(attr.name.to_string(), methods);
     }
 
+    for attr in columns_attributes.values() {
+        let Some(rel_name) = &attr.count_of else {
+            continue;
+        };
+        let Some(rel_attr) = columns_attributes
+            .values()
+            .find(|a| a.name == *rel_name && a.relation.is_some())
+        else {
+            std::panic!("count_of: `{rel_name}` is not a relation");
+        };
+
+        let mut methods = Vec::new();
+        match &rel_attr.relation {
+            Some(RelType::HasMany { attribute: _ }) => {
+                has_many::generate_count_method(rel_attr, input, &mut methods);
+            }
+            Some(RelType::MorphMany { attribute: _ }) => {
+                morph_many::generate_count_method(rel_attr, input, &mut methods);
+            }
+            Some(RelType::BelongsToMany { attribute: _ }) => {
+                belongs_to_many::generate_count_method(rel_attr, input, &mut methods);
+            }
+            _ => std::panic!("count_of: `{rel_name}` can not be counted"),
+        }
+        relationship::build_count_append(attr, &mut append_methods);
+        relationship_methods.insert(format!("{}:count", &attr.name), methods);
+    }
+
     let relationship_methods = relationship_methods
         .into_values()
         .flatten()
@@ -107,6 +151,13 @@ pub fn build_entity_repo(
         }
     };
 
+    // The ID of the last entity in a batch
+    let pluck_last_id = if id_field_attr.optional {
+        quote! { batch.last().and_then(|record| record.#id_field.clone()) }
+    } else {
+        quote! { batch.last().map(|record| record.#id_field.clone()) }
+    };
+
     // insert
     let set_created_at = if tbl_attr.no_timestamp {
         quote! {}
@@ -115,15 +166,38 @@ pub fn build_entity_repo(
             record.#created_at = Some(::dirtybase_common::dirtybase_helper::time::current_datetime());
         }
     };
-    let insert_method = quote! {
-        pub async fn insert(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error> {
-            #set_created_at
+    // An entity without an ID gets the one generated by the database
+    let insert_with_id = if id_field_attr.optional {
+        quote! {
+            let id = record.#id_field.clone();
+            let result = self.manager.insert_into::<#ident>(record).await?;
+            let id = match id.or_else(|| {
+                result
+                    .last_insert_id()
+                    .map(|last_id| ::dirtybase_common::db::field_values::FieldValue::from(last_id as i64).into())
+            }) {
+                Some(id) => id,
+                None => return Err(::dirtybase_common::anyhow::anyhow!("could not retrieve the ID of the inserted model"))
+            };
+        }
+    } else {
+        quote! {
             #pluck_rec_id
+            _ = self.manager.insert_into::<#ident>(record).await?;
+        }
+    };
 
-           _ = self.manager.insert_into::<#ident>(record).await?;
+    let insert_method = quote! {
+        pub async fn insert(&mut self, record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error> {
+            let mut record = ::dirtybase_common::db::event::ModelEvent::<#ident>::before(::dirtybase_common::db::event::ModelEventKind::Creating, None, record).await?;
+            #set_created_at
+            #insert_with_id
 
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
             match self.by_id(id).await? {
-                Some(v) => Ok(v),
+                Some(v) => ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Created, Some(id_value), Some(v))
+                    .await
+                    .ok_or_else(|| ::dirtybase_common::anyhow::anyhow!("could not retrieve inserted model")),
                 None => Err(::dirtybase_common::anyhow::anyhow!("could not retrieve inserted model"))
             }
         }
@@ -137,17 +211,125 @@ pub fn build_entity_repo(
             record.#updated_at= Some(::dirtybase_common::dirtybase_helper::time::current_datetime());
         }
     };
+    // optimistic locking: the update only goes through when the version is unchanged
+    let mut bump_version = quote! {};
+    let mut version_condition = quote! {};
+    let mut stale_check = quote! { _ = result; };
+    // the same check and bump for the writes that only have an ID
+    let mut version_param = quote! {};
+    let mut version_arg = quote! {};
+    let mut record_version = quote! {};
+    let mut bump_version_column = quote! {};
+    let mut version_field_of_row = None;
+    if let Some(version_col) = &tbl_attr.version_col {
+        let version_attr = columns_attributes
+            .get(version_col)
+            .unwrap_or_else(|| std::panic!("version column `{version_col}` is not a field"));
+        let version_field = format_ident!("{}", version_col);
+        let version_column = quote! {
+            <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#version_col)
+        };
+        let version_type = format_ident!("{}", version_attr.the_type);
+        let version_type = if version_attr.optional {
+            quote! { Option<#version_type> }
+        } else {
+            quote! { #version_type }
+        };
+
+        version_param = quote! { , current_version: #version_type };
+        version_arg = quote! { , current_version };
+        record_version = quote! {
+            let current_version = record.#version_field.clone();
+        };
+        bump_version_column = if version_attr.optional {
+            quote! {
+                cv.insert(#version_col.to_string(), ::dirtybase_common::db::field_values::FieldValue::from(current_version.unwrap_or_default() + 1));
+            }
+        } else {
+            quote! {
+                cv.insert(#version_col.to_string(), ::dirtybase_common::db::field_values::FieldValue::from(current_version + 1));
+            }
+        };
+        version_field_of_row = Some(version_field.clone());
+
+        if version_attr.optional {
+            bump_version = quote! {
+                let current_version = record.#version_field.clone();
+                record.#version_field = Some(current_version.unwrap_or_default() + 1);
+            };
+            version_condition = quote! {
+                match current_version {
+                    Some(version) => { qb.is_eq(#version_column, version); }
+                    None => { qb.is_null(#version_column); }
+                }
+            };
+        } else {
+            bump_version = quote! {
+                let current_version = record.#version_field.clone();
+                record.#version_field += 1;
+            };
+            version_condition = quote! {
+                qb.is_eq(#version_column, current_version);
+            };
+        }
+
+        stale_check = quote! {
+            if result.is_unchanged() {
+                return Err(::dirtybase_common::db::base::stale_entity::StaleEntity::new(
+                    <#ident as ::dirtybase_common::db::table_model::TableModel>::table_name(),
+                    ::dirtybase_common::db::field_values::FieldValue::from(id)
+                ).into());
+            }
+        };
+    }
+
+    // writes by ID are checked against the version the row has now
+    let load_version = |missing: TokenStream| match &version_field_of_row {
+        Some(version_field) => {
+            let include_trashed = if soft_deletable {
+                quote! { self.with_trashed(); }
+            } else {
+                quote! {}
+            };
+            quote! {
+                #include_trashed
+                let current_version = match self.by_id(id.clone()).await? {
+                    Some(row) => row.#version_field,
+                    None => return #missing,
+                };
+            }
+        }
+        None => quote! {},
+    };
+    let no_rows = quote! { Ok(::dirtybase_common::db::base::schema::ExecuteResult::new(0, None)) };
+    let load_version_or_no_rows = load_version(no_rows);
+    let load_version_or_none = load_version(quote! { Ok(None) });
+
     let update_method = quote! {
-        pub async fn update(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error>{
+        pub async fn update(&mut self, record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error>{
+            #pluck_rec_id
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id);
+            let record = ::dirtybase_common::db::event::ModelEvent::<#ident>::before(::dirtybase_common::db::event::ModelEventKind::Updating, Some(id_value.clone()), record).await?;
+            let updated = self.update_record(record).await?;
+
+            ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Updated, Some(id_value), Some(updated))
+                .await
+                .ok_or_else(|| ::dirtybase_common::anyhow::anyhow!("could not retrieve updated model"))
+        }
+
+        async fn update_record(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error>{
             #set_updated_at
             #pluck_rec_id
+            #bump_version
 
-            _ = self.manager.update_table::<#ident>(record, |qb| {
+            let result = self.manager.update_table::<#ident>(record, |qb| {
                 qb.is_eq(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column()
                     ), id.clone());
+                #version_condition
             }).await?;
+            #stale_check
 
             match self.by_id(id).await? {
                 Some(v) =>Ok(v),
@@ -156,77 +338,177 @@ pub fn build_entity_repo(
         }
     };
 
+    let set_updated_at_column = if tbl_attr.no_timestamp {
+        quote! {}
+    } else {
+        quote! {
+            if let Some(name) = <#ident as ::dirtybase_common::db::table_model::TableModel>::updated_at_column() {
+                cv.insert(name.to_string(), ::dirtybase_common::db::field_values::FieldValue::from(now));
+            }
+        }
+    };
+
     let mut restore_method = quote! {};
 
     // destroy record
     let destroy_method = quote! {
-        pub async fn destroy(&mut self, record: #ident) -> Result<(), ::dirtybase_common::anyhow::Error> {
+        pub async fn destroy(&mut self, record: #ident) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
             #pluck_rec_id
-            self.destroy_by_id(id).await
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
+            let record = ::dirtybase_common::db::event::ModelEvent::<#ident>::before(::dirtybase_common::db::event::ModelEventKind::Deleting, Some(id_value.clone()), record).await?;
+            #record_version
+            let result = self.destroy_row(id #version_arg).await?;
+            _ = ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Deleted, Some(id_value), Some(record)).await;
+
+            Ok(result)
+        }
+
+        pub async fn destroy_by_id(&mut self, id: #id_type) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
+            ::dirtybase_common::db::event::ModelEvent::<#ident>::before_id(::dirtybase_common::db::event::ModelEventKind::Deleting, id_value.clone()).await?;
+            #load_version_or_no_rows
+            let result = self.destroy_row(id #version_arg).await?;
+            _ = ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Deleted, Some(id_value), None).await;
+
+            Ok(result)
         }
 
-        pub async fn destroy_by_id(&mut self, id: #id_type) -> Result<(), ::dirtybase_common::anyhow::Error> {
-            self.manager.delete_from_table::<#ident>(|qb|{
+        async fn destroy_row(&mut self, id: #id_type #version_param) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+            let result = self.manager.delete_from_table::<#ident>(|qb|{
                 qb.is_eq(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column())
-                    , id);
-            }).await
+                    , id.clone());
+                #version_condition
+            }).await?;
+            #stale_check
+
+            Ok(result)
+        }
+    };
+
+    let delete_method = quote! {
+        pub async fn delete(&mut self, record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error> {
+            #pluck_rec_id
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id);
+            let record = ::dirtybase_common::db::event::ModelEvent::<#ident>::before(::dirtybase_common::db::event::ModelEventKind::Deleting, Some(id_value.clone()), record).await?;
+            let deleted = self.delete_record(record).await?;
+
+            ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Deleted, Some(id_value), Some(deleted))
+                .await
+                .ok_or_else(|| ::dirtybase_common::anyhow::anyhow!("could not retrieve deleted model"))
+        }
+
+        pub async fn delete_by_id(&mut self, id: #id_type ) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+            let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
+            ::dirtybase_common::db::event::ModelEvent::<#ident>::before_id(::dirtybase_common::db::event::ModelEventKind::Deleting, id_value.clone()).await?;
+            #load_version_or_no_rows
+            let result = self.delete_row(id #version_arg).await?;
+            _ = ::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Deleted, Some(id_value), None).await;
+
+            Ok(result)
         }
     };
 
-    let mut delete_method = quote! {
-        pub async fn delete(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error> {
+    let mut delete_row_methods = quote! {
+        async fn delete_record(&mut self, record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error> {
             #pluck_rec_id
-            _ = self.delete_by_id(id).await?;
+            #record_version
+            _ = self.delete_row(id #version_arg).await?;
 
             Ok(record)
         }
 
-        pub async fn delete_by_id(&mut self, id: #id_type ) -> Result<(), ::dirtybase_common::anyhow::Error> {
-            _ = self.manager.delete_from_table::<#ident>(|qb|{
+        async fn delete_row(&mut self, id: #id_type #version_param) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+            let result = self.manager.delete_from_table::<#ident>(|qb|{
                 qb.is_eq(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
                     <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column())
-                    ,id);
+                    ,id.clone());
+                #version_condition
             }).await?;
-            Ok(())
+            #stale_check
+
+            Ok(result)
         }
     };
 
     if soft_deletable {
-        delete_method = quote! {
-            pub async fn delete(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error>{
+        delete_row_methods = quote! {
+            async fn delete_record(&mut self, mut record: #ident) -> Result<#ident, ::dirtybase_common::anyhow::Error>{
                 record.#deleted_at = Some(::dirtybase_common::dirtybase_helper::time::current_datetime());
-                self.update(record).await
+                // the trashed record has to be fetched back
+                self.with_trashed();
+                self.update_record(record).await
             }
 
-            pub async fn delete_by_id(&mut self, id: #id_type ) -> Result<(), ::dirtybase_common::anyhow::Error> {
-                if let Some(record) = self.by_id(id).await? {
-                    _ = self.delete(record).await?;
-                }
-                Ok(())
+            async fn delete_row(&mut self, id: #id_type #version_param) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+                let now = ::dirtybase_common::dirtybase_helper::time::current_datetime();
+                let mut cv = ::std::collections::HashMap::new();
+                cv.insert(
+                    <#ident as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().expect("could not get entity `deleted at` column").to_string(),
+                    ::dirtybase_common::db::field_values::FieldValue::from(now)
+                );
+                #set_updated_at_column
+                #bump_version_column
+
+                let result = self.manager.update_table::<#ident>(cv, |qb|{
+                    qb.is_eq(
+                        <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column()),
+                        id.clone());
+                    #version_condition
+                }).await?;
+                #stale_check
+
+                Ok(result)
             }
         };
 
         restore_method = quote! {
             pub async fn restore(&mut self, id: #id_type) -> Result<Option<#ident>, ::dirtybase_common::anyhow::Error> {
+                let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
+                ::dirtybase_common::db::event::ModelEvent::<#ident>::before_id(::dirtybase_common::db::event::ModelEventKind::Restoring, id_value.clone()).await?;
+                #load_version_or_none
+                _ = self.restore_row(id.clone() #version_arg).await?;
+
+                match self.by_id(id).await? {
+                    Some(v) => Ok(::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Restored, Some(id_value), Some(v)).await),
+                    None => Ok(None),
+                }
+            }
+
+            /// Restores the trashed record, failing when the record is stale
+            pub async fn restore_record(&mut self, record: #ident) -> Result<Option<#ident>, ::dirtybase_common::anyhow::Error> {
+                #pluck_rec_id
+                let id_value = ::dirtybase_common::db::field_values::FieldValue::from(id.clone());
+                let record = ::dirtybase_common::db::event::ModelEvent::<#ident>::before(::dirtybase_common::db::event::ModelEventKind::Restoring, Some(id_value.clone()), record).await?;
+                #record_version
+                _ = self.restore_row(id.clone() #version_arg).await?;
+
+                match self.by_id(id).await? {
+                    Some(v) => Ok(::dirtybase_common::db::event::ModelEvent::<#ident>::after(::dirtybase_common::db::event::ModelEventKind::Restored, Some(id_value), Some(v)).await),
+                    None => Ok(None),
+                }
+            }
+
+            async fn restore_row(&mut self, id: #id_type #version_param) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
                 let name = <#ident as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().expect("could not get entity `deleted at` column").to_string();
 
                 let mut cv = ::std::collections::HashMap::new();
-                cv.insert(
-                    <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(name),
-                     ::dirtybase_common::db::field_values::FieldValue::Null
-                    );
+                cv.insert(name, ::dirtybase_common::db::field_values::FieldValue::Null);
+                #bump_version_column
 
-                _ = self.manager.update_table::<#ident>(cv, |qb|{
+                let result = self.manager.update_table::<#ident>(cv, |qb|{
                     qb.is_eq(
                             <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
                         <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column()),
                         id.clone());
+                    #version_condition
                 }).await?;
+                #stale_check
 
-                self.by_id(id).await
+                Ok(result)
             }
         };
 
@@ -281,6 +563,8 @@ pub fn build_entity_repo(
             manager: ::dirtybase_common::db::base::manager::Manager,
             settings: Vec<String>,
             relation: ::std::collections::HashMap<String, ::dirtybase_common::db::repo_relation::Relation<#ident>>,
+            nested: ::std::collections::HashMap<String, Vec<String>>,
+            unknown_relations: Vec<String>,
         }
 
 
@@ -291,8 +575,13 @@ pub fn build_entity_repo(
                         <#ident as ::dirtybase_common::db::table_model::TableModel>::table_name(),
                         ::dirtybase_common::db::base::query::QueryAction::query()
                     ),
-                    manager: manager.clone(),
+                    manager: match <#ident as ::dirtybase_common::db::table_model::TableModel>::connection() {
+                        Some(name) => manager.on_connection(name),
+                        None => manager.clone(),
+                    },
                     relation: ::std::collections::HashMap::new(),
+                    nested: ::std::collections::HashMap::new(),
+                    unknown_relations: Vec::new(),
                     settings: Vec::new(),
                 }
             }
@@ -307,10 +596,6 @@ pub fn build_entity_repo(
              ::dirtybase_common::db::base::cursor_builder::CursorResult<#ident>
             {
                 let mut rows_map = ::std::collections::HashMap::<u64, #ident>::new();
-                // <name of a field whos value is used in a join, <entry hash, the field value>>
-                let mut join_field_values = ::std::collections::HashMap::new();
-                //<String, ::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>,
-                let mut rows_rel_map = ::std::collections::HashMap::new();
                 let cursor = if let Some(cursor) = cursor {
                     cursor
                 } else {
@@ -327,8 +612,8 @@ pub fn build_entity_repo(
                 let cursor_result = self.manager.execute_query(self.builder.clone()).cursor_paginate(cursor).await;
                 let (cursor, result) = cursor_result.parts();
 
-              match result {
-                    Ok(mut raw_list) => {
+                match result {
+                    Ok(raw_list) => {
                         for row in raw_list {
                             if let Some(row_entity) = #ident::from_struct_column_value(&row,
                                 Some(<#ident as ::dirtybase_common::db::table_model::TableModel>::table_name())) {
@@ -337,21 +622,12 @@ pub fn build_entity_repo(
                             }
                         }
 
-
-                        for (name, rel) in &self.relation {
-                           if let Err(e)  = rel.clone().process(&name, &self.manager, &rows_map, &mut join_field_values, &mut rows_rel_map).await {
-                                *self = Self::new(&self.manager);
-                                return ::dirtybase_common::db::base::cursor_builder::CursorResult::<#ident>::new(cursor, Err(e));
-                           }
-                        }
-
-                        // now map relationships
-                        for(row_hash, row_entity) in &mut rows_map {
-                            #(#append_methods)*
-                        }
-
+                        let loaded = self.eager_load(&mut rows_map).await;
                         *self = Self::new(&self.manager);
-                        ::dirtybase_common::db::base::cursor_builder::CursorResult::<#ident>::new(cursor,Ok(rows_map.into_iter().map(|e| e.1).collect::<Vec<#ident>>()))
+                        match loaded {
+                            Ok(_) => ::dirtybase_common::db::base::cursor_builder::CursorResult::<#ident>::new(cursor,Ok(rows_map.into_values().collect::<Vec<#ident>>())),
+                            Err(e) => ::dirtybase_common::db::base::cursor_builder::CursorResult::<#ident>::new(cursor, Err(e)),
+                        }
                     }
                     Err(e) => {
                         *self = Self::new(&self.manager);
@@ -362,44 +638,49 @@ pub fn build_entity_repo(
             }
 
             pub async fn get(&mut self) -> Result<Option<Vec<#ident>>, ::dirtybase_common::anyhow::Error> {
-                let mut rows_map = ::std::collections::HashMap::<u64, #ident>::new();
-                // <name of a field whos value is used in a join, <entry hash, the field value>>
-                let mut join_field_values = ::std::collections::HashMap::new();
-                //<String, ::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>,
-                let mut rows_rel_map = ::std::collections::HashMap::new();
                 #append_trash_filter
 
+                self
+                    .builder
+                    .select_multiple(&<#ident as ::dirtybase_common::db::table_model::TableModel>::table_query_col_aliases(None));
+
+                let result = self.fetch_entities(self.builder.clone()).await;
+                *self = Self::new(&self.manager);
+                result.map(Some)
+            }
+
+            /// Fetches a page of entities along with the total number of entities.
+            /// `page` starts at 1
+            pub async fn paginate(&mut self, page: usize, per_page: usize) -> Result<::dirtybase_common::db::base::page_result::PageResult<#ident>, ::dirtybase_common::anyhow::Error> {
+                #append_trash_filter
 
                 self
                     .builder
                     .select_multiple(&<#ident as ::dirtybase_common::db::table_model::TableModel>::table_query_col_aliases(None));
 
-                let result = self.manager.execute_query(self.builder.clone()).all().await;
+                let result = self.manager.execute_query(self.builder.clone()).paginate(page, per_page).await;
 
                 match result {
-                    Ok(mut raw_list) => {
-                        for row in raw_list {
+                    Ok(page_result) => {
+                        let total = page_result.total();
+                        let mut hashes = Vec::new();
+                        let mut rows_map = ::std::collections::HashMap::<u64, #ident>::new();
+                        for row in page_result.into_data() {
                             if let Some(row_entity) = #ident::from_struct_column_value(&row,
                                 Some(<#ident as ::dirtybase_common::db::table_model::TableModel>::table_name())) {
-                                let row_hash= ::dirtybase_common::db::table_model::TableModel::entity_hash(&row_entity);
+                                let row_hash = ::dirtybase_common::db::table_model::TableModel::entity_hash(&row_entity);
+                                hashes.push(row_hash);
                                 rows_map.insert(row_hash, row_entity);
                             }
                         }
 
-                        for (name, rel) in &self.relation {
-                           if let Err(e)  = rel.clone().process(&name, &self.manager, &rows_map, &mut join_field_values, &mut rows_rel_map).await {
-                                *self = Self::new(&self.manager);
-                                return Err(e);
-                           }
-                        }
-
-                        // now map relationships
-                        for(row_hash, row_entity) in &mut rows_map {
-                            #(#append_methods)*
-                        }
-
+                        let loaded = self.eager_load(&mut rows_map).await;
                         *self = Self::new(&self.manager);
-                        Ok(Some(rows_map.into_iter().map(|e| e.1).collect::<Vec<#ident>>()))
+                        loaded?;
+
+                        // keeps the order of the query
+                        let data = hashes.into_iter().filter_map(|hash| rows_map.remove(&hash)).collect();
+                        Ok(::dirtybase_common::db::base::page_result::PageResult::new(data, total, page, per_page))
                     },
                     Err(e) => {
                         *self = Self::new(&self.manager);
@@ -408,6 +689,183 @@ pub fn build_entity_repo(
                 }
             }
 
+            /// Calls `callback` with batches of up to `size` entities until the rows run out
+            /// or the callback returns `false`. The requested relations are loaded per batch.
+            /// Batches are fetched with `LIMIT`/`OFFSET` and ordered by the entity ID unless
+            /// the query has an order, use `chunk_by_id` when the rows are changed while chunking
+            pub async fn chunk<F, Fut>(&mut self, size: usize, mut callback: F) -> Result<(), ::dirtybase_common::anyhow::Error>
+            where
+                F: FnMut(Vec<#ident>) -> Fut,
+                Fut: ::std::future::Future<Output = bool>,
+            {
+                let size = size.max(1);
+                #append_trash_filter
+
+                self
+                    .builder
+                    .select_multiple(&<#ident as ::dirtybase_common::db::table_model::TableModel>::table_query_col_aliases(None));
+                // without an order the database may return the rows of a batch again
+                if self.builder.order_by().is_none() {
+                    self.builder.asc(<#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column()));
+                }
+
+                let repo = ::std::mem::replace(self, Self::new(&self.manager));
+                let mut offset = 0;
+                loop {
+                    let mut query = repo.builder.clone();
+                    query.limit(size).offset(offset);
+
+                    let batch = repo.fetch_entities(query).await?;
+                    let total = batch.len();
+                    if total == 0 || !callback(batch).await || total < size {
+                        return Ok(());
+                    }
+                    offset += size;
+                }
+            }
+
+            /// Like `chunk` but the batches are ordered and fetched by the entity ID,
+            /// starting after the last ID of the previous batch.
+            /// Fails when the query has its own order, use `chunk` instead
+            pub async fn chunk_by_id<F, Fut>(&mut self, size: usize, mut callback: F) -> Result<(), ::dirtybase_common::anyhow::Error>
+            where
+                F: FnMut(Vec<#ident>) -> Fut,
+                Fut: ::std::future::Future<Output = bool>,
+            {
+                if self.builder.order_by().is_some() {
+                    *self = Self::new(&self.manager);
+                    return Err(::dirtybase_common::anyhow::anyhow!("chunk_by_id orders the batches by the ID, use chunk for an ordered query"));
+                }
+
+                let size = size.max(1);
+                let id_column = <#ident as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#ident as ::dirtybase_common::db::table_model::TableModel>::id_column());
+                #append_trash_filter
+
+                self
+                    .builder
+                    .select_multiple(&<#ident as ::dirtybase_common::db::table_model::TableModel>::table_query_col_aliases(None));
+
+                let repo = ::std::mem::replace(self, Self::new(&self.manager));
+                let mut last_id: Option<#id_type> = None;
+                loop {
+                    let mut query = repo.builder.clone();
+                    if let Some(id) = last_id.take() {
+                        query.gt(&id_column, id);
+                    }
+                    query.asc(&id_column).limit(size);
+
+                    let batch = repo.fetch_entities(query).await?;
+                    let total = batch.len();
+                    last_id = #pluck_last_id;
+                    if total == 0 || last_id.is_none() || !callback(batch).await || total < size {
+                        return Ok(());
+                    }
+                }
+            }
+
+            /// Returns a stream of the entities. They are fetched in the background
+            /// with `chunk_by_id`, `size` entities at a time
+            pub fn lazy(&mut self, size: usize) -> ::dirtybase_common::db::base::lazy_stream::LazyStream<#ident> {
+                let mut repo = ::std::mem::replace(self, Self::new(&self.manager));
+
+                ::dirtybase_common::db::base::lazy_stream::lazy_stream(size, move |sender| async move {
+                    let result = repo.chunk_by_id(size, |batch| {
+                        let sender = sender.clone();
+                        async move {
+                            for entity in batch {
+                                if sender.send(Ok(entity)).await.is_err() {
+                                    return false;
+                                }
+                            }
+                            true
+                        }
+                    }).await;
+
+                    if let Err(e) = result {
+                        _ = sender.send(Err(e)).await;
+                    }
+                })
+            }
+
+            /// Runs the query and loads the requested relations, keeping the order of the rows
+            async fn fetch_entities(&self, query: ::dirtybase_common::db::base::query::QueryBuilder) -> Result<Vec<#ident>, ::dirtybase_common::anyhow::Error> {
+                let mut hashes = Vec::new();
+                let mut rows_map = ::std::collections::HashMap::<u64, #ident>::new();
+
+                for row in self.manager.execute_query(query).all().await? {
+                    if let Some(row_entity) = #ident::from_struct_column_value(&row,
+                        Some(<#ident as ::dirtybase_common::db::table_model::TableModel>::table_name())) {
+                        let row_hash = ::dirtybase_common::db::table_model::TableModel::entity_hash(&row_entity);
+                        hashes.push(row_hash);
+                        rows_map.insert(row_hash, row_entity);
+                    }
+                }
+
+                self.eager_load(&mut rows_map).await?;
+                Ok(hashes.into_iter().filter_map(|hash| rows_map.remove(&hash)).collect())
+            }
+
+            /// Loads the requested relations into already fetched entities
+            pub async fn load(&mut self, entities: Vec<#ident>) -> Result<Vec<#ident>, ::dirtybase_common::anyhow::Error> {
+                let mut rows_map = entities
+                    .into_iter()
+                    .map(|e| (::dirtybase_common::db::table_model::TableModel::entity_hash(&e), e))
+                    .collect::<::std::collections::HashMap<u64, #ident>>();
+
+                let loaded = self.eager_load(&mut rows_map).await;
+                *self = Self::new(&self.manager);
+                loaded.map(|_| rows_map.into_values().collect())
+            }
+
+            /// Loads the requested relations, and their nested relations, into the entities of `rows_map`
+            pub async fn eager_load(&self, rows_map: &mut ::std::collections::HashMap<u64, #ident>) -> Result<(), ::dirtybase_common::anyhow::Error> {
+                if let Some(name) = self.unknown_relations.first() {
+                    return Err(::dirtybase_common::anyhow::anyhow!("`{}` is not a relation of {}", name, stringify!(#ident)));
+                }
+
+                // <name of a field whos value is used in a join, <entry hash, the field value>>
+                let mut join_field_values = ::std::collections::HashMap::new();
+                //<String, ::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>,
+                let mut rows_rel_map = ::std::collections::HashMap::new();
+
+                for (name, rel) in &self.relation {
+                    rel.clone().process(name, &self.manager, rows_map, &mut join_field_values, &mut rows_rel_map).await?;
+                }
+
+                // now map relationships
+                for(row_hash, row_entity) in rows_map.iter_mut() {
+                    #(#append_methods)*
+                }
+
+                #(#nested_loads)*
+
+                Ok(())
+            }
+
+            /// Eager loads a relation by name. Nested relations are separated by a dot,
+            /// e.g. `children.toys`. Loading fails when a name is not a relation
+            pub fn with(&mut self, path: &str) -> &mut Self {
+                let (name, nested) = match path.split_once('.') {
+                    Some((name, nested)) => (name, Some(nested)),
+                    None => (path, None),
+                };
+
+                let known = match name {
+                    #(#with_arms)*
+                    _ => {
+                        self.unknown_relations.push(name.to_string());
+                        false
+                    }
+                };
+
+                if let (true, Some(nested)) = (known, nested) {
+                    self.nested.entry(name.to_string()).or_default().push(nested.to_string());
+                }
+                self
+            }
+
             pub async fn one(&mut self) -> Result<Option<#ident>, ::dirtybase_common::anyhow::Error> {
                 match self.limit(1).get().await {
                     Ok(Some(mut list)) => {
@@ -502,9 +960,83 @@ pub fn build_entity_repo(
             #insert_method
             #update_method
             #delete_method
+            #delete_row_methods
             #destroy_method
             #restore_method
             #(#column_names)*
         }
+
+        impl ::dirtybase_common::db::repo_relation::EagerLoad for #ident {
+            fn eager_load<'a>(
+                manager: &'a ::dirtybase_common::db::base::manager::Manager,
+                rows_map: &'a mut ::std::collections::HashMap<u64, Self>,
+                paths: &'a [String],
+            ) -> ::std::pin::Pin<Box<dyn ::std::future::Future<Output = Result<(), ::dirtybase_common::anyhow::Error>> + Send + 'a>> {
+                Box::pin(async move {
+                    let mut repo = #repo_name::new(manager);
+                    for path in paths {
+                        repo.with(path);
+                    }
+                    repo.eager_load(rows_map).await
+                })
+            }
+        }
+    }
+}
+
+/// A `with` match arm that loads the relation when it is not already requested
+fn build_with_arm(attr: &DirtybaseAttributes) -> TokenStream {
+    let name = &attr.name;
+    let method_name = format_ident!("with_{}", name);
+
+    quote! {
+        #name => {
+            if !self.relation.contains_key(#name) {
+                self.#method_name();
+            }
+            true
+        }
+    }
+}
+
+/// Loads the nested relations of the related entities with their own repo
+fn build_nested_load(attr: &DirtybaseAttributes) -> TokenStream {
+    let name = &attr.name;
+    let name_ident = format_ident!("{}", name);
+    let foreign_type = format_ident!("{}", attr.the_type);
+
+    let related = if attr.is_vec && attr.optional {
+        quote! { row_entity.#name_ident.iter_mut().flatten() }
+    } else if attr.is_vec || attr.optional {
+        quote! { row_entity.#name_ident.iter_mut() }
+    } else {
+        quote! { ::std::iter::once(&mut row_entity.#name_ident) }
+    };
+
+    quote! {
+        if let Some(paths) = self.nested.get(#name) {
+            // the related entities are moved out and put back in the same order once loaded
+            let mut related_map = ::std::collections::HashMap::<u64, #foreign_type>::new();
+            for row_entity in rows_map.values_mut() {
+                for related in #related {
+                    related_map.insert(related_map.len() as u64, ::std::mem::take(related));
+                }
+            }
+
+            if !related_map.is_empty() {
+                let loaded = <#foreign_type as ::dirtybase_common::db::repo_relation::EagerLoad>::eager_load(&self.manager, &mut related_map, paths).await;
+
+                let mut index = 0_u64;
+                for row_entity in rows_map.values_mut() {
+                    for related in #related {
+                        if let Some(entity) = related_map.remove(&index) {
+                            *related = entity;
+                        }
+                        index += 1;
+                    }
+                }
+                loaded?;
+            }
+        }
     }
 }
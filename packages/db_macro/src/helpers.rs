@@ -1,5 +1,5 @@
 use crate::{
-    attribute_type::{DirtybaseAttributes, TableAttribute},
+    attribute_type::{DirtybaseAttributes, RelType, TableAttribute},
     relationship::process_relation_attribute,
 };
 use proc_macro2::TokenStream;
@@ -153,6 +153,13 @@ pub(crate) fn attribute_to_attribute_type(
             "skip" => {
                 include = false;
             }
+            "count_of" => {
+                _ = walker.next();
+                let relation = walker.next().unwrap().to_string().replace('\"', "");
+                dirty_attribute.count_of = Some(relation);
+                dirty_attribute.skip_select = true;
+                dirty_attribute.skip_insert = true;
+            }
             "flatten" => {
                 dirty_attribute.flatten = true;
             }
@@ -319,6 +326,24 @@ pub(crate) fn build_from_handlers(
             continue;
         }
 
+        // A morph_to registry is only built by the relation loader
+        if matches!(item.1.relation, Some(RelType::MorphTo { .. })) {
+            built.push(if item.1.optional {
+                quote! {
+                    pub fn #fn_name <'a>(_field: Option<&'a ::dirtybase_common::db::field_values::FieldValue>) -> Option<#returns> {
+                        None
+                    }
+                }
+            } else {
+                quote! {
+                    pub fn #fn_name <'a>(_field: Option<&'a ::dirtybase_common::db::field_values::FieldValue>) -> #returns {
+                        ::std::default::Default::default()
+                    }
+                }
+            });
+            continue;
+        }
+
         built.push(
                     if item.1.optional {
                         if item.1.is_vec {
@@ -518,6 +543,24 @@ pub(crate) fn build_special_column_methods(
         });
     }
 
+    // optimistic locking
+    if let Some(name) = &tbl_attr.version_col {
+        tokens.push(quote! {
+            fn version_column() -> Option<&'static str> {
+                Some(#name)
+            }
+        });
+    }
+
+    // connection
+    if let Some(name) = &tbl_attr.connection {
+        tokens.push(quote! {
+            fn connection() -> Option<&'static str> {
+                Some(#name)
+            }
+        });
+    }
+
     tokens
 }
 
@@ -4,12 +4,13 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{DeriveInput, parse_macro_input};
 
-use crate::entity_repo::build_entity_repo;
+use crate::{entity_repo::build_entity_repo, table_blueprint::build_blueprint_method};
 
 mod attribute_type;
 mod entity_repo;
 mod helpers;
 mod relationship;
+mod table_blueprint;
 
 #[proc_macro_derive(DirtyTable, attributes(dirty, dirty_rel))]
 pub fn derive_dirtybase_entity(item: TokenStream) -> TokenStream {
@@ -34,6 +35,7 @@ pub fn derive_dirtybase_entity(item: TokenStream) -> TokenStream {
     let column_name_methods = build_prop_column_names_getter(&columns_attributes);
     let defaults = spread_default(&columns_attributes, &input);
     let entity_repo = build_entity_repo(&input, &columns_attributes, &table_attribute);
+    let blueprint_method = build_blueprint_method(&input, &columns_attributes, &table_attribute);
     let mut from_column_for_id = format_ident!("from_column_for_id");
     for (name, col_attr) in &columns_attributes {
         if table_attribute.id_field == *name {
@@ -95,6 +97,8 @@ pub fn derive_dirtybase_entity(item: TokenStream) -> TokenStream {
 
         #(#special_column_methods)*
 
+        #blueprint_method
+
         fn table_columns() -> Vec<&'static str> {
           let main = vec![
              #(#column),*
@@ -151,8 +155,6 @@ pub fn derive_dirtybase_entity(item: TokenStream) -> TokenStream {
       // Entity repo
       #entity_repo
 
-      // TODO: Generate a function that can be used in a migration to create the entity table
-
     };
 
     TokenStream::from(expanded)
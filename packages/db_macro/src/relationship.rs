@@ -1,15 +1,18 @@
 pub(crate) mod belongs_to;
+pub(crate) mod belongs_to_many;
 pub(crate) mod has_many;
 pub(crate) mod has_many_through;
 pub(crate) mod has_one;
 pub(crate) mod has_one_through;
 pub(crate) mod morph_many;
 pub(crate) mod morph_one;
+pub(crate) mod morph_to;
 
 use std::collections::HashMap;
 
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
+use quote::{format_ident, quote};
 use syn::DeriveInput;
 
 use crate::attribute_type::{DirtybaseAttributes, RelType};
@@ -45,3 +48,289 @@ pub(crate) fn process_relation_attribute(
     dirty_attribute.relation = RelType::new(attributes, field, input);
     true
 }
+
+/// Builds the `with_count_<relation>` method. The related rows are counted
+/// per parent with a grouped query on `source_table`
+pub(crate) fn build_count_method(
+    input: &DeriveInput,
+    name: &str,
+    rel_type: TokenStream,
+    source_table: TokenStream,
+    key_col: TokenStream,
+    parent_col: TokenStream,
+    conditions: TokenStream,
+) -> TokenStream {
+    let method_name = format_ident!("with_count_{}", name);
+    let parent = format_ident!("{}", &input.ident);
+    let relation_name = format!("{name}:count");
+
+    quote! {
+        pub fn #method_name(&mut self,) -> &mut Self {
+            let query = ::dirtybase_common::db::base::query::QueryBuilder::new_query(#source_table);
+            let relation = ::dirtybase_common::db::repo_relation::Relation::<#parent>::new(
+                ::dirtybase_common::db::repo_relation::RelationType::#rel_type{ query },
+                |
+                    relation: ::dirtybase_common::db::repo_relation::Relation<#parent>,
+                    rows: &::std::collections::HashMap<u64, #parent>,
+                    join_values: &mut ::std::collections::HashMap<String,::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>
+                | {
+                    let (mut query, _) = relation.rel_type().builders();
+                    let key = format!("{}.{}", #source_table, #key_col);
+
+                    let mut total = ::dirtybase_common::db::query_column::QueryColumn::new(key.as_str(), None, Some("_count.total"));
+                    total.set_aggregate(::dirtybase_common::db::base::aggregate::Aggregate::Count);
+                    query.select(::dirtybase_common::db::query_column::QueryColumn::new(
+                        #key_col,
+                        Some(#source_table),
+                        Some(&format!("_count.{}", #key_col))
+                    ));
+                    query.select(total);
+                    query.group_by([key.clone()]);
+                    #conditions
+
+                    let parent_col_name = #parent_col.to_string();
+                    if join_values.get(&parent_col_name).is_none() {
+                        let mut values = ::std::collections::HashMap::new();
+                        for (hash, a_row) in rows {
+                            if let Ok(cv) = ::dirtybase_common::db::types::ToColumnAndValue::to_column_value(a_row) {
+                                if let Some(v) = cv.get(&parent_col_name).cloned() {
+                                    values.insert(hash.clone(), v);
+                                }
+                            }
+                        }
+                        join_values.insert(parent_col_name.clone(), values);
+                    }
+
+                    let mut values = join_values.get(&parent_col_name).cloned().unwrap().into_values().collect::<Vec<
+                        ::dirtybase_common::db::field_values::FieldValue
+                    >>();
+                    values.dedup();
+
+                    query.is_in(key, values);
+                    ::dirtybase_common::db::repo_relation::RelationProcessor::new(query, parent_col_name, "_count".to_string(), #key_col.to_string())
+                }
+            );
+
+            self.relation.insert(#relation_name.to_string(), relation);
+            self
+        }
+    }
+}
+
+/// Builds the `has_<relation>`, `where_has_<relation>`, `doesnt_have_<relation>` and
+/// `where_doesnt_have_<relation>` methods. The relation is checked with an `EXISTS`
+/// sub query on the related table. When the related table is the entity's own table,
+/// it is aliased as `_<relation>` so that a row does not match itself
+pub(crate) fn generate_exists_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    let (relation, attribute) = match &attr.relation {
+        Some(
+            relation @ (RelType::HasOne { attribute }
+            | RelType::HasMany { attribute }
+            | RelType::BelongsTo { attribute }
+            | RelType::BelongsToMany { attribute }
+            | RelType::HasOneThrough { attribute }
+            | RelType::HasManyThrough { attribute }
+            | RelType::MorphOne { attribute }
+            | RelType::MorphMany { attribute }),
+        ) => (relation, attribute),
+        _ => return,
+    };
+
+    let name = &attr.name;
+    let parent = format_ident!("{}", &input.ident);
+    let foreign_type = format_ident!("{}", attr.the_type);
+    let alias = format!("_{name}");
+
+    let column_or = |field: &Option<String>, default: TokenStream| match field {
+        Some(field) => quote! { #field },
+        None => default,
+    };
+    let parent_id =
+        quote! { <#parent as ::dirtybase_common::db::table_model::TableModel>::id_column() };
+    let parent_foreign_id = quote! { <#parent as ::dirtybase_common::db::table_model::TableModel>::foreign_id_column() };
+    let related_id =
+        quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::id_column() };
+    let related_foreign_id = quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::foreign_id_column() };
+    // `related` is the name of the related table in the sub query
+    let related_col = |col: TokenStream| quote! { &format!("{}.{}", related, #col) };
+
+    let (key_col, parent_col, conditions) = match relation {
+        RelType::HasOne { .. } | RelType::HasMany { .. } => (
+            related_col(column_or(&attribute.foreign_col, parent_foreign_id)),
+            column_or(&attribute.local_col, parent_id),
+            quote! {},
+        ),
+        RelType::BelongsTo { .. } => (
+            related_col(column_or(&attribute.foreign_col, related_id)),
+            column_or(&attribute.local_col, related_foreign_id),
+            quote! {},
+        ),
+        RelType::HasOneThrough { .. } | RelType::HasManyThrough { .. } => {
+            let Some(pivot) = &attribute.pivot else {
+                std::panic!("pivot type not specified for: {name}");
+            };
+            let pivot_type = format_ident!("{}", pivot);
+            let foreign_col = column_or(&attribute.foreign_col, parent_foreign_id);
+            let pivot_through_col = column_or(&attribute.pivot_through_col, related_foreign_id);
+            let through_col = related_col(column_or(&attribute.through_col, related_id));
+            (
+                quote! { &<#pivot_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#foreign_col) },
+                column_or(&attribute.local_col, parent_id),
+                quote! {
+                    query.inner_join(
+                        <#pivot_type as ::dirtybase_common::db::table_model::TableModel>::table_name(),
+                        &<#pivot_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#pivot_through_col),
+                        "=",
+                        #through_col
+                    );
+                },
+            )
+        }
+        RelType::BelongsToMany { .. } => {
+            let belongs_to_many::PivotColumns {
+                pivot_table,
+                parent_col,
+                foreign_col,
+                pivot_through_col,
+                through_col,
+            } = belongs_to_many::pivot_columns(name, attribute, &parent, &foreign_type);
+            let through_col = related_col(through_col);
+            (
+                quote! { &format!("{}.{}", #pivot_table, #foreign_col) },
+                parent_col,
+                quote! {
+                    query.inner_join(
+                        #pivot_table,
+                        &format!("{}.{}", #pivot_table, #pivot_through_col),
+                        "=",
+                        #through_col
+                    );
+                },
+            )
+        }
+        _ => {
+            let Some(morph_name) = &attribute.morph_name else {
+                std::panic!("morph relation must have a name. {name}");
+            };
+            let Some(morph_type) = &attribute.morph_type else {
+                std::panic!("morph relation must have a type value. {name}");
+            };
+            let foreign_col = attribute
+                .foreign_col
+                .clone()
+                .unwrap_or_else(|| format!("{morph_name}_id"));
+            let morph_type_col = attribute
+                .morph_type_col
+                .clone()
+                .unwrap_or_else(|| format!("{morph_name}_type"));
+            let morph_type_col = related_col(quote! { #morph_type_col });
+            (
+                related_col(quote! { #foreign_col }),
+                column_or(&attribute.local_col, parent_id),
+                quote! {
+                    query.is_eq(#morph_type_col, #morph_type);
+                },
+            )
+        }
+    };
+
+    let trash_condition = if attribute.no_soft_delete {
+        quote! {}
+    } else {
+        let deleted_at = related_col(quote! {
+            <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+        });
+        quote! {
+            query.is_null(#deleted_at);
+        }
+    };
+
+    let query_method = format_ident!("{}_exists_query", name);
+    let has_method = format_ident!("has_{}", name);
+    let where_has_method = format_ident!("where_has_{}", name);
+    let doesnt_have_method = format_ident!("doesnt_have_{}", name);
+    let where_doesnt_have_method = format_ident!("where_doesnt_have_{}", name);
+
+    list.push(quote! {
+        fn #query_method<F>(callback: F) -> ::dirtybase_common::db::base::query::QueryBuilder
+            where F: FnOnce(&mut ::dirtybase_common::db::base::query::QueryBuilder)
+        {
+            let table = <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_name();
+            let mut query = ::dirtybase_common::db::base::query::QueryBuilder::new_query(table);
+            let related = if table == <#parent as ::dirtybase_common::db::table_model::TableModel>::table_name() {
+                query.alias(#alias);
+                #alias
+            } else {
+                table
+            };
+            query.is_eq_column(
+                #key_col,
+                <#parent as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#parent_col)
+            );
+            #conditions
+            #trash_condition
+            callback(&mut query);
+            query
+        }
+
+        pub fn #has_method(&mut self,) -> &mut Self {
+            self.#where_has_method(|_| {})
+        }
+
+        pub fn #where_has_method<F>(&mut self, callback: F) -> &mut Self
+            where F: FnOnce(&mut ::dirtybase_common::db::base::query::QueryBuilder)
+        {
+            self.builder.exists(Self::#query_method(callback));
+            self
+        }
+
+        pub fn #doesnt_have_method(&mut self,) -> &mut Self {
+            self.#where_doesnt_have_method(|_| {})
+        }
+
+        pub fn #where_doesnt_have_method<F>(&mut self, callback: F) -> &mut Self
+            where F: FnOnce(&mut ::dirtybase_common::db::base::query::QueryBuilder)
+        {
+            self.builder.not_exists(Self::#query_method(callback));
+            self
+        }
+    });
+}
+
+/// Sets the count field when its `with_count_<relation>` method was called
+pub(crate) fn build_count_append(count_attr: &DirtybaseAttributes, list: &mut Vec<TokenStream>) {
+    let field = format_ident!("{}", &count_attr.name);
+    let relation_name = format!(
+        "{}:count",
+        count_attr
+            .count_of
+            .as_ref()
+            .expect("count field without a relation")
+    );
+
+    let body = if count_attr.optional {
+        quote! { row_entity.#field = Some(count); }
+    } else {
+        quote! { row_entity.#field = count; }
+    };
+
+    list.push(quote! {
+        // Note: rows_rel_map, row_hash ane row_entity are from the `get` method of the repo instance
+        if self.relation.contains_key(#relation_name) {
+            let count = rows_rel_map
+                .get_mut(#relation_name)
+                .and_then(|rows| rows.remove(row_hash))
+                .and_then(|rows| rows.into_iter().next())
+                .and_then(|row| match row.get("_count") {
+                    Some(::dirtybase_common::db::field_values::FieldValue::Object(v)) => v.get("total").map(::std::primitive::i64::from),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            #body
+        }
+    });
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+use crate::attribute_type::RelType;
+use crate::attribute_type::{DirtybaseAttributes, RelationAttribute};
+
+pub(crate) fn build_attribute(
+    attr: HashMap<String, String>,
+    _field: &syn::Field,
+    _input: &DeriveInput,
+) -> RelationAttribute {
+    //
+    RelationAttribute::from(attr)
+}
+
+/// The columns linking the parent, the pivot table and the related entity
+pub(crate) struct PivotColumns {
+    pub(crate) pivot_table: String,
+    /// Parent column matched by the pivot
+    pub(crate) parent_col: TokenStream,
+    /// Pivot column referencing the parent
+    pub(crate) foreign_col: TokenStream,
+    /// Pivot column referencing the related entity
+    pub(crate) pivot_through_col: TokenStream,
+    /// Related column matched by the pivot
+    pub(crate) through_col: TokenStream,
+}
+
+pub(crate) fn pivot_columns(
+    name: &str,
+    attribute: &RelationAttribute,
+    parent: &Ident,
+    foreign_type: &Ident,
+) -> PivotColumns {
+    let Some(pivot_table) = attribute.pivot.clone() else {
+        std::panic!("pivot table not specified for: {name}");
+    };
+    let column_or = |field: &Option<String>, default: TokenStream| match field {
+        Some(field) => quote! { #field },
+        None => default,
+    };
+
+    PivotColumns {
+        pivot_table,
+        parent_col: column_or(
+            &attribute.local_col,
+            quote! { <#parent as ::dirtybase_common::db::table_model::TableModel>::id_column() },
+        ),
+        foreign_col: column_or(
+            &attribute.foreign_col,
+            quote! { <#parent as ::dirtybase_common::db::table_model::TableModel>::foreign_id_column() },
+        ),
+        pivot_through_col: column_or(
+            &attribute.pivot_through_col,
+            quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::foreign_id_column() },
+        ),
+        through_col: column_or(
+            &attribute.through_col,
+            quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::id_column() },
+        ),
+    }
+}
+
+pub(crate) fn generate_join_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    if let Some(RelType::BelongsToMany { attribute }) = &attr.relation {
+        // method
+        let name = &attr.name;
+        let method_name_st = format!("with_{name}");
+        let method_name = format_ident!("{}", &method_name_st);
+        let when_method_name = format_ident!("{}_when", &name);
+        let method_name_where = format_ident!("{}_where", &method_name_st);
+        let trashed_method_name = format_ident!("with_trashed_{}", &name);
+        let trashed_method_name_where = format_ident!("with_trashed_{}_where", &name);
+        let with_only_trashed_method_name = format_ident!("with_trashed_only_{}", &name);
+        let with_only_trashed_method_name_where =
+            format_ident!("with_trashed_only_{}_where", &name);
+        let pivot_method_name = format_ident!("{}_pivot", &name);
+        let attach_method_name = format_ident!("attach_{}", &name);
+        let detach_method_name = format_ident!("detach_{}", &name);
+        let sync_method_name = format_ident!("sync_{}", &name);
+        let parent = format_ident!("{}", &input.ident);
+        let foreign_type = format_ident!("{}", attr.the_type);
+
+        let PivotColumns {
+            pivot_table,
+            parent_col,
+            foreign_col,
+            pivot_through_col,
+            through_col,
+        } = pivot_columns(name, attribute, &parent, &foreign_type);
+
+        let empty_callback = quote! {
+            |_: &mut ::dirtybase_common::db::repo_relation::Relation<#parent>| {
+                // nothing to do
+            }
+        };
+
+        let trash_condition = if attribute.no_soft_delete {
+            quote! {}
+        } else {
+            quote! {
+                relation.query_mut()
+                 .is_null(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+                    )
+                 );
+            }
+        };
+
+        list.push(quote! {
+            pub fn #when_method_name<F>(&mut self , mut callback: F) -> &mut Self
+                where F: FnMut(&mut ::dirtybase_common::db::repo_relation::Relation<#parent>)
+             {
+                let query = <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::make_query_builder();
+
+                let mut relation = ::dirtybase_common::db::repo_relation::Relation::<#parent>::new(
+                    ::dirtybase_common::db::repo_relation::RelationType::BelongsToMany{ query },
+                    |relation: ::dirtybase_common::db::repo_relation::Relation<#parent>,
+                     rows: &::std::collections::HashMap<u64, #parent>,
+                     join_values: &mut ::std::collections::HashMap<String,::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>
+                    | {
+                        let (mut query, _) = relation.rel_type().builders();
+
+                        query.select_multiple(&<#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_query_col_aliases(None));
+
+                        let parent_col_name = #parent_col.to_string();
+                        if join_values.get(&parent_col_name).is_none() {
+                            let mut values = ::std::collections::HashMap::new();
+                            for (hash, a_row) in rows {
+                                if let Ok(cv) = ::dirtybase_common::db::types::ToColumnAndValue::to_column_value(a_row) {
+                                    if let Some(v) = cv.get(&parent_col_name).cloned() {
+                                        values.insert(hash.clone(), v);
+                                    }
+                                }
+                            }
+                            join_values.insert(parent_col_name.clone(), values);
+                        }
+
+                        let values = join_values.get(&parent_col_name).cloned().unwrap().into_values().collect::<Vec<
+                            ::dirtybase_common::db::field_values::FieldValue
+                        >>();
+                        let pivot_data = format!("{}_pivot", #name);
+                        let pivot_alias = format!("{}.{}", &pivot_data, #foreign_col);
+                        query.inner_join_and_select(
+                            #pivot_table,
+                            &<#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#through_col),
+                            "=",
+                            &format!("{}.{}", #pivot_table, #pivot_through_col),
+                            vec![::dirtybase_common::db::query_column::QueryColumn::new(#foreign_col, Some(#pivot_table), Some(&pivot_alias))]
+                        );
+                        query.is_in(format!("{}.{}", #pivot_table, #foreign_col), values);
+                        ::dirtybase_common::db::repo_relation::RelationProcessor::new(query, parent_col_name, pivot_data, #foreign_col.to_string())
+                    }
+                );
+
+                callback(&mut relation);
+                self.relation.insert(#name.to_string(), relation);
+                self
+             }
+        });
+
+        let call_callback = quote! {
+            callback(relation);
+        };
+
+        let token = quote! {
+            pub fn #method_name(&mut self,) -> &mut Self {
+                self.#method_name_where(#empty_callback)
+            }
+
+            pub fn #method_name_where<F>(&mut self,mut callback: F) -> &mut Self
+             where F: FnMut(&mut ::dirtybase_common::db::repo_relation::Relation<#parent>)
+            {
+                self.#when_method_name(|relation|{
+                    #call_callback
+                    #trash_condition
+                })
+            }
+        };
+
+        list.push(token);
+
+        // pivot rows helpers
+        list.push(quote! {
+            pub fn #pivot_method_name() -> ::dirtybase_common::db::repo_relation::PivotTable {
+                ::dirtybase_common::db::repo_relation::PivotTable::new(#pivot_table, #foreign_col, #pivot_through_col)
+            }
+
+            pub async fn #attach_method_name<I: Into<::dirtybase_common::db::field_values::FieldValue>>(
+                &mut self,
+                parent: impl Into<::dirtybase_common::db::field_values::FieldValue>,
+                related: impl IntoIterator<Item = I>
+            ) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+                Self::#pivot_method_name()
+                    .attach(&self.manager, parent.into(), related.into_iter().map(Into::into).collect())
+                    .await
+            }
+
+            pub async fn #detach_method_name<I: Into<::dirtybase_common::db::field_values::FieldValue>>(
+                &mut self,
+                parent: impl Into<::dirtybase_common::db::field_values::FieldValue>,
+                related: impl IntoIterator<Item = I>
+            ) -> Result<::dirtybase_common::db::base::schema::ExecuteResult, ::dirtybase_common::anyhow::Error> {
+                Self::#pivot_method_name()
+                    .detach(&self.manager, parent.into(), related.into_iter().map(Into::into).collect())
+                    .await
+            }
+
+            pub async fn #sync_method_name<I: Into<::dirtybase_common::db::field_values::FieldValue>>(
+                &mut self,
+                parent: impl Into<::dirtybase_common::db::field_values::FieldValue>,
+                related: impl IntoIterator<Item = I>
+            ) -> Result<(), ::dirtybase_common::anyhow::Error> {
+                Self::#pivot_method_name()
+                    .sync(&self.manager, parent.into(), related.into_iter().map(Into::into).collect())
+                    .await
+            }
+        });
+
+        if !attribute.no_soft_delete {
+            list.push(quote! {
+                pub fn #trashed_method_name(&mut self,) -> &mut Self {
+                    self.#trashed_method_name_where(#empty_callback)
+                }
+
+                pub fn #trashed_method_name_where<F>(&mut self,mut callback: F) -> &mut Self
+                    where F: FnMut(&mut ::dirtybase_common::db::repo_relation::Relation<#parent>)
+                {
+                    self.#when_method_name(|relation| {
+                        #call_callback
+                    })
+                }
+            });
+
+            list.push(quote! {
+                pub fn #with_only_trashed_method_name_where<F>(&mut self,mut callback: F) -> &mut Self
+                    where F: FnMut(&mut ::dirtybase_common::db::repo_relation::Relation<#parent>)
+                {
+                    self.#when_method_name(|relation| {
+                        #call_callback
+                        relation.rel_type_mut().query_mut().is_not_null(
+                                <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+                                )
+                            );
+                        }
+                    )
+                }
+
+                pub fn #with_only_trashed_method_name(&mut self,) -> &mut Self {
+                    self.#with_only_trashed_method_name_where(#empty_callback)
+                }
+            });
+        }
+    }
+}
+
+pub(crate) fn generate_count_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    if let Some(RelType::BelongsToMany { attribute }) = &attr.relation {
+        let name = &attr.name;
+        let parent = format_ident!("{}", &input.ident);
+        let foreign_type = format_ident!("{}", attr.the_type);
+        let PivotColumns {
+            pivot_table,
+            parent_col,
+            foreign_col,
+            pivot_through_col,
+            through_col,
+        } = pivot_columns(name, attribute, &parent, &foreign_type);
+
+        // trashed related rows are not counted
+        let trash_condition = if attribute.no_soft_delete {
+            quote! {}
+        } else {
+            quote! {
+                query.inner_join(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_name(),
+                    &<#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#through_col),
+                    "=",
+                    &format!("{}.{}", #pivot_table, #pivot_through_col)
+                );
+                query.is_null(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+                    )
+                );
+            }
+        };
+
+        list.push(super::build_count_method(
+            input,
+            name,
+            quote! { BelongsToMany },
+            quote! { #pivot_table },
+            foreign_col,
+            parent_col,
+            trash_condition,
+        ));
+    }
+}
+
+pub(crate) fn build_entity_append(attr: &DirtybaseAttributes, list: &mut Vec<TokenStream>) {
+    let name = &attr.name;
+    let name_ident = format_ident!("{}", name);
+    let foreign_type = format_ident!("{}", attr.the_type);
+
+    let transform = quote! {
+        related_rows.into_iter().map(|row|{
+            #foreign_type::from_struct_column_value(
+                &row,
+                Some(<#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_name())
+            )
+        }).flatten().collect::<Vec<#foreign_type>>()
+    };
+
+    let body = if attr.optional {
+        quote! {
+                row_entity.#name_ident = Some(#transform);
+        }
+    } else {
+        quote! {
+                row_entity.#name_ident = #transform;
+        }
+    };
+
+    let token = quote! {
+        // Note: rows_rel_map, row_hash ane row_entity are from the `get` method of the repo instance
+        if let Some(rows) = rows_rel_map.get_mut(#name) {
+            if let Some(related_rows) = rows.remove(row_hash)  {
+                #body
+            }
+        }
+    };
+    list.push(token);
+}
@@ -175,6 +175,51 @@ pub(crate) fn generate_join_method(
     }
 }
 
+pub(crate) fn generate_count_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    if let Some(RelType::HasMany { attribute }) = &attr.relation {
+        let parent = format_ident!("{}", &input.ident);
+        let foreign_type = format_ident!("{}", attr.the_type);
+
+        let parent_col = if let Some(field) = &attribute.local_col {
+            quote! { #field }
+        } else {
+            quote! {<#parent as ::dirtybase_common::db::table_model::TableModel>::id_column()}
+        };
+
+        let foreign_col = if let Some(field) = &attribute.foreign_col {
+            quote! { #field }
+        } else {
+            quote! { <#parent as ::dirtybase_common::db::table_model::TableModel>::foreign_id_column() }
+        };
+
+        let trash_condition = if attribute.no_soft_delete {
+            quote! {}
+        } else {
+            quote! {
+                query.is_null(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+                    )
+                );
+            }
+        };
+
+        list.push(super::build_count_method(
+            input,
+            &attr.name,
+            quote! { HasMany },
+            quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_name() },
+            foreign_col,
+            parent_col,
+            trash_condition,
+        ));
+    }
+}
+
 pub(crate) fn build_entity_append(attr: &DirtybaseAttributes, list: &mut Vec<TokenStream>) {
     let name = &attr.name;
     let foreign_type = format_ident!("{}", attr.the_type);
@@ -207,6 +207,74 @@ pub(crate) fn generate_join_method(
     }
 }
 
+pub(crate) fn generate_count_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    if let Some(RelType::MorphMany { attribute }) = &attr.relation {
+        let name = &attr.name;
+        let parent = format_ident!("{}", &input.ident);
+        let foreign_type = format_ident!("{}", attr.the_type);
+        let morph_name = if let Some(field) = &attribute.morph_name {
+            field
+        } else {
+            std::panic!("morph relation must have a name. {name}");
+        };
+        let morph_type = if let Some(field) = &attribute.morph_type {
+            field
+        } else {
+            std::panic!("morph relation must have a type value. {name}");
+        };
+
+        let parent_col = if let Some(field) = &attribute.local_col {
+            quote! { #field }
+        } else {
+            quote! {<#parent as ::dirtybase_common::db::table_model::TableModel>::id_column()}
+        };
+
+        let foreign_col = if let Some(field) = &attribute.foreign_col {
+            field.clone()
+        } else {
+            format!("{}_id", &morph_name)
+        };
+
+        let morph_type_col = if let Some(field) = &attribute.morph_type_col {
+            field.clone()
+        } else {
+            format!("{}_type", &morph_name)
+        };
+
+        let trash_condition = if attribute.no_soft_delete {
+            quote! {}
+        } else {
+            quote! {
+                query.is_null(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(
+                        <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::deleted_at_column().as_ref().unwrap()
+                    )
+                );
+            }
+        };
+
+        list.push(super::build_count_method(
+            input,
+            name,
+            quote! { MorphMany },
+            quote! { <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::table_name() },
+            quote! { #foreign_col },
+            parent_col,
+            quote! {
+                query.is_eq(
+                    <#foreign_type as ::dirtybase_common::db::table_model::TableModel>::prefix_with_tbl(#morph_type_col),
+                    #morph_type
+                );
+                #trash_condition
+            },
+        ));
+    }
+}
+
 pub(crate) fn build_entity_append(attr: &DirtybaseAttributes, list: &mut Vec<TokenStream>) {
     let name = &attr.name;
     let foreign_type = format_ident!("{}", attr.the_type);
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+use crate::attribute_type::RelType;
+use crate::attribute_type::{DirtybaseAttributes, RelationAttribute};
+
+pub(crate) fn build_attribute(
+    attr: HashMap<String, String>,
+    field: &syn::Field,
+    _input: &DeriveInput,
+) -> RelationAttribute {
+    let mut attribute = RelationAttribute::from(attr);
+    if attribute.morph_name.is_none() {
+        attribute.morph_name = field.ident.as_ref().map(|i| i.to_string());
+    }
+    attribute
+}
+
+pub(crate) fn generate_join_method(
+    attr: &DirtybaseAttributes,
+    input: &DeriveInput,
+    list: &mut Vec<TokenStream>,
+) {
+    if let Some(RelType::MorphTo { attribute }) = &attr.relation {
+        // method
+        let name = &attr.name;
+        let method_name_st = format!("with_{name}");
+        let method_name = format_ident!("{}", &method_name_st);
+        let when_method_name = format_ident!("{}_when", &name);
+        let method_name_where = format_ident!("{}_where", &method_name_st);
+        let trashed_method_name = format_ident!("with_trashed_{}", &name);
+        let trashed_method_name_where = format_ident!("with_trashed_{}_where", &name);
+        let with_only_trashed_method_name = format_ident!("with_trashed_only_{}", &name);
+        let with_only_trashed_method_name_where =
+            format_ident!("with_trashed_only_{}_where", &name);
+        let parent = format_ident!("{}", &input.ident);
+        let registry_type = format_ident!("{}", attr.the_type);
+        let morph_name = if let Some(field) = &attribute.morph_name {
+            field
+        } else {
+            std::panic!("morph relation must have a name. {name}");
+        };
+
+        let foreign_key_name = format!("{}_id", &morph_name);
+        let morph_type_name = format!("{}_type", &morph_name);
+
+        // parent column holding the related entity's ID
+        let mut foreign_col = quote! { #foreign_key_name };
+        // parent column holding the morph type
+        let mut morph_type_col = quote! { #morph_type_name };
+
+        if let Some(field) = &attribute.foreign_col {
+            foreign_col = quote! { #field };
+        }
+
+        if let Some(field) = &attribute.morph_type_col {
+            morph_type_col = quote! { #field };
+        }
+
+        let empty_callback = quote! {
+            |_: &::dirtybase_common::db::repo_relation::MorphEntry<#registry_type>, _: &mut ::dirtybase_common::db::base::query::QueryBuilder| {
+                // nothing to do
+            }
+        };
+        let call_callback = quote! {
+            callback(entry, query);
+        };
+
+        let trash_condition = if attribute.no_soft_delete {
+            quote! {}
+        } else {
+            quote! {
+                if let Some(deleted_at) = entry.deleted_at_column() {
+                    query.is_null(format!("{}.{}", entry.table(), deleted_at));
+                }
+            }
+        };
+
+        list.push(quote! {
+            pub fn #when_method_name<F>(&mut self, callback: F) -> &mut Self
+                where F: Fn(&::dirtybase_common::db::repo_relation::MorphEntry<#registry_type>, &mut ::dirtybase_common::db::base::query::QueryBuilder) + Send + Sync + 'static
+             {
+                let query = <#parent as ::dirtybase_common::db::table_model::TableModel>::make_query_builder();
+
+                let relation = ::dirtybase_common::db::repo_relation::Relation::<#parent>::new(
+                    ::dirtybase_common::db::repo_relation::RelationType::MorphTo{ query },
+                    move |
+                        _relation: ::dirtybase_common::db::repo_relation::Relation<#parent>,
+                        rows: &::std::collections::HashMap<u64, #parent>,
+                        join_values: &mut ::std::collections::HashMap<String,::std::collections::HashMap<u64,::dirtybase_common::db::field_values::FieldValue>>
+                    | {
+                        let registry = <#registry_type as ::dirtybase_common::db::repo_relation::MorphToRegistry>::registry();
+                        let mut batches = Vec::new();
+
+                        for entry in registry.entries() {
+                            // parent rows are grouped per morph type
+                            let parent_col_name = format!("{}@{}", #foreign_col, entry.name());
+                            let mut values = ::std::collections::HashMap::new();
+                            for (hash, a_row) in rows {
+                                if let Ok(cv) = ::dirtybase_common::db::types::ToColumnAndValue::to_column_value(a_row) {
+                                    let is_type = cv.get(#morph_type_col).map(|v| v.to_string() == entry.name()).unwrap_or_default();
+                                    if let (true, Some(v)) = (is_type, cv.get(#foreign_col).cloned()) {
+                                        values.insert(hash.clone(), v);
+                                    }
+                                }
+                            }
+
+                            if values.is_empty() {
+                                continue;
+                            }
+
+                            let mut ids = values.values().cloned().collect::<Vec<
+                                ::dirtybase_common::db::field_values::FieldValue
+                            >>();
+                            ids.dedup();
+                            join_values.insert(parent_col_name.clone(), values);
+
+                            let mut query = entry.query();
+                            query.is_in(format!("{}.{}", entry.table(), entry.id_column()), ids);
+                            callback(entry, &mut query);
+
+                            batches.push(::dirtybase_common::db::repo_relation::RelationProcessor::new(
+                                query,
+                                parent_col_name,
+                                entry.table().to_string(),
+                                entry.id_column().to_string()
+                            ));
+                        }
+
+                        ::dirtybase_common::db::repo_relation::RelationProcessor::batched(batches)
+                    }
+                );
+
+                self.relation.insert(#name.to_string(), relation);
+                self
+            }
+        });
+
+        list.push(quote! {
+            pub fn #method_name(&mut self,) -> &mut Self {
+                self.#method_name_where(#empty_callback)
+            }
+
+            pub fn #method_name_where<F>(&mut self, callback: F) -> &mut Self
+                where F: Fn(&::dirtybase_common::db::repo_relation::MorphEntry<#registry_type>, &mut ::dirtybase_common::db::base::query::QueryBuilder) + Send + Sync + 'static
+            {
+                self.#when_method_name(move |entry, query| {
+                    #call_callback
+                    #trash_condition
+                })
+            }
+        });
+
+        if !attribute.no_soft_delete {
+            list.push(quote! {
+                pub fn #trashed_method_name(&mut self,) -> &mut Self {
+                    self.#trashed_method_name_where(#empty_callback)
+                }
+
+                pub fn #trashed_method_name_where<F>(&mut self, callback: F) -> &mut Self
+                    where F: Fn(&::dirtybase_common::db::repo_relation::MorphEntry<#registry_type>, &mut ::dirtybase_common::db::base::query::QueryBuilder) + Send + Sync + 'static
+                {
+                    self.#when_method_name(callback)
+                }
+
+                pub fn #with_only_trashed_method_name(&mut self,) -> &mut Self {
+                    self.#with_only_trashed_method_name_where(#empty_callback)
+                }
+
+                pub fn #with_only_trashed_method_name_where<F>(&mut self, callback: F) -> &mut Self
+                    where F: Fn(&::dirtybase_common::db::repo_relation::MorphEntry<#registry_type>, &mut ::dirtybase_common::db::base::query::QueryBuilder) + Send + Sync + 'static
+                {
+                    self.#when_method_name(move |entry, query| {
+                        #call_callback
+                        if let Some(deleted_at) = entry.deleted_at_column() {
+                            query.is_not_null(format!("{}.{}", entry.table(), deleted_at));
+                        }
+                    })
+                }
+            });
+        }
+    }
+}
+
+pub(crate) fn build_entity_append(attr: &DirtybaseAttributes, list: &mut Vec<TokenStream>) {
+    let name = &attr.name;
+    let registry_type = format_ident!("{}", attr.the_type);
+    let name_ident = format_ident!("{}", name);
+    let morph_type_col = if let Some(RelType::MorphTo { attribute }) = &attr.relation {
+        attribute.morph_type_col.clone().unwrap_or_else(|| {
+            format!(
+                "{}_type",
+                attribute
+                    .morph_name
+                    .as_ref()
+                    .expect("morph relation must have a name")
+            )
+        })
+    } else {
+        return;
+    };
+
+    let transform = quote! {
+        {
+            let morph_type = ::dirtybase_common::db::types::ToColumnAndValue::to_column_value(row_entity)
+                .ok()
+                .and_then(|cv| cv.get(#morph_type_col).map(|v| v.to_string()))
+                .unwrap_or_default();
+            let registry = <#registry_type as ::dirtybase_common::db::repo_relation::MorphToRegistry>::registry();
+            related_rows.iter().find_map(|row| registry.build(&morph_type, row))
+        }
+    };
+
+    let body = if attr.optional {
+        quote! {
+                row_entity.#name_ident = #transform;
+        }
+    } else {
+        quote! {
+                if let Some(value) = #transform {
+                    row_entity.#name_ident = value;
+                }
+        }
+    };
+
+    let token = quote! {
+        // Note: rows_rel_map, row_hash ane row_entity are from the `get` method of the repo instance
+        if let Some(rows) = rows_rel_map.get_mut(#name) {
+            if let Some(related_rows) = rows.remove(row_hash)  {
+                #body
+            }
+        }
+    };
+
+    list.push(token);
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput};
+
+use crate::attribute_type::{DirtybaseAttributes, TableAttribute};
+
+/// Builds the `blueprint` method of the `TableModel` implementation
+pub(crate) fn build_blueprint_method(
+    input: &DeriveInput,
+    columns_attributes: &HashMap<String, DirtybaseAttributes>,
+    tbl_attr: &TableAttribute,
+) -> TokenStream {
+    let table_name = &tbl_attr.table_name;
+    let mut special_columns = Vec::new();
+    if !tbl_attr.no_timestamp {
+        special_columns.push(tbl_attr.created_at_col.as_str());
+        special_columns.push(tbl_attr.updated_at_col.as_str());
+    }
+    if !tbl_attr.no_soft_delete {
+        special_columns.push(tbl_attr.deleted_at_col.as_str());
+    }
+
+    let mut columns = Vec::new();
+    if let Data::Struct(data) = &input.data {
+        for field in data.fields.iter() {
+            let Some(name) = field.ident.as_ref().map(|i| i.to_string()) else {
+                continue;
+            };
+            let Some(attribute) = columns_attributes.get(&name) else {
+                continue;
+            };
+
+            if attribute.relation.is_some()
+                || attribute.count_of.is_some()
+                || special_columns.contains(&attribute.name.as_str())
+            {
+                continue;
+            }
+
+            if attribute.flatten {
+                let the_type = format_ident!("{}", &attribute.the_type);
+                columns.push(quote! {
+                    for column in <#the_type as ::dirtybase_common::db::TableModel>::blueprint().columns {
+                        if !table.columns.iter().any(|c| c.name == column.name) {
+                            table.columns.push(column);
+                        }
+                    }
+                });
+                continue;
+            }
+
+            if tbl_attr.id_field == name {
+                columns.push(build_id_column(attribute));
+            } else {
+                columns.push(build_column(attribute));
+            }
+        }
+    }
+
+    if !tbl_attr.no_timestamp {
+        let created_at = &tbl_attr.created_at_col;
+        let updated_at = &tbl_attr.updated_at_col;
+        columns.push(quote! {
+            table.timestamp(#created_at).set_is_nullable(false);
+            table.timestamp(#updated_at).set_is_nullable(true);
+        });
+    }
+
+    if !tbl_attr.no_soft_delete {
+        let deleted_at = &tbl_attr.deleted_at_col;
+        columns.push(quote! {
+            table.timestamp(#deleted_at).set_is_nullable(true);
+        });
+    }
+
+    quote! {
+        fn blueprint() -> ::dirtybase_common::db::base::table::TableBlueprint {
+            let mut table = ::dirtybase_common::db::base::table::TableBlueprint::new(#table_name);
+            #(#columns)*
+            table
+        }
+    }
+}
+
+fn build_id_column(attribute: &DirtybaseAttributes) -> TokenStream {
+    let name = &attribute.name;
+    match column_method(attribute).to_string().as_str() {
+        "integer" => quote! {
+            table.id(Some(#name));
+        },
+        "uuid" => quote! {
+            table.uuid_as_id(Some(#name));
+        },
+        "ulid" => quote! {
+            table.ulid_as_id(Some(#name));
+        },
+        _ => {
+            let method = column_method(attribute);
+            quote! {
+                table.#method(#name).set_as_primary();
+            }
+        }
+    }
+}
+
+fn build_column(attribute: &DirtybaseAttributes) -> TokenStream {
+    let name = &attribute.name;
+    let method = column_method(attribute);
+    let nullable = is_nullable(attribute);
+
+    quote! {
+        table.#method(#name).set_is_nullable(#nullable);
+    }
+}
+
+/// Optional fields and the aliases of `Option` types are nullable
+fn is_nullable(attribute: &DirtybaseAttributes) -> bool {
+    attribute.optional
+        || attribute.the_type.starts_with("Optional")
+        || matches!(
+            attribute.the_type.as_str(),
+            "AutoGeneratedIntegerField" | "CreatedAtField" | "UpdatedAtField" | "DeletedAtField"
+        )
+}
+
+/// The `TableBlueprint` method used to create the field's column
+fn column_method(attribute: &DirtybaseAttributes) -> proc_macro2::Ident {
+    if attribute.embedded {
+        return format_ident!("json");
+    }
+
+    if attribute.is_vec {
+        return if attribute.the_type == "u8" {
+            format_ident!("binary")
+        } else {
+            format_ident!("json")
+        };
+    }
+
+    let method = match attribute.the_type.as_str() {
+        "ArcUuid7" | "Uuid" => "uuid",
+        "UlidField" | "ArcUlidField" | "OptionalUlidField" | "OptionalArcUlidField" => "ulid",
+        "AutoGeneratedIntegerField"
+        | "InternalIdField"
+        | "OptionalInternalIdField"
+        | "SingedIntegerField"
+        | "OptionalSingedIntegerField"
+        | "IntegerField"
+        | "OptionalIntegerField"
+        | "UnsignedIntegerField"
+        | "OptionalUnsignedIntegerField"
+        | "SnowflakeField"
+        | "i8"
+        | "i16"
+        | "i32"
+        | "i64"
+        | "u8"
+        | "u16"
+        | "u32"
+        | "u64"
+        | "isize"
+        | "usize" => "integer",
+        "NumberField" | "OptionalNumberField" => "number",
+        "FloatField" | "OptionalFloatField" | "f32" | "f64" => "float",
+        "BooleanField" | "OptionalBooleanField" | "bool" => "boolean",
+        "DateTimeField" | "OptionalDateTimeField" | "DateTime" => "datetime",
+        "TimestampField"
+        | "OptionalTimestampField"
+        | "CreatedAtField"
+        | "UpdatedAtField"
+        | "DeletedAtField" => "timestamp",
+        "DateField" | "OptionalDateField" | "NaiveDate" => "date",
+        "JsonField"
+        | "OptionalJsonField"
+        | "JsonValueField"
+        | "OptionalJsonValueField"
+        | "Value"
+        | "Map" => "json",
+        _ => "string",
+    };
+
+    format_ident!("{}", method)
+}
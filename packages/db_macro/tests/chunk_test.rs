@@ -0,0 +1,59 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Author {
+    id: Option<i64>,
+    name: String,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Author::table_name(), |table| {
+            table.id(None);
+            table.string(Author::col_name_for_name());
+        })
+        .await;
+
+    let mut repo = AuthorRepo::new(manager);
+    for name in ["a", "b", "c", "d", "e"] {
+        repo.insert(Author {
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_chunk_by_id_rejects_ordered_query() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut repo = AuthorRepo::new(&manager);
+    let mut names = Vec::new();
+    let result = repo
+        .filter(|q| {
+            q.desc(Author::col_name_for_name());
+        })
+        .chunk_by_id(2, |batch| {
+            names.extend(batch.into_iter().map(|a| a.name));
+            async { true }
+        })
+        .await;
+    assert!(result.is_err());
+    assert!(names.is_empty());
+
+    // the order is not kept for the next query
+    repo.chunk_by_id(2, |batch| {
+        names.extend(batch.into_iter().map(|a| a.name));
+        async { true }
+    })
+    .await
+    .unwrap();
+    assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+}
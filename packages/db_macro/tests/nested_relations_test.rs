@@ -0,0 +1,115 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+// the entities are not `Clone` on purpose
+#[derive(Debug, Default, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Family {
+    id: Option<i64>,
+    name: String,
+    #[dirty(rel(kind = has_many, no_soft_delete))]
+    children: Vec<Child>,
+}
+
+#[derive(Debug, Default, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Child {
+    id: Option<i64>,
+    name: String,
+    family_id: i64,
+    #[dirty(rel(kind = has_many, foreign_col = child_id, no_soft_delete))]
+    toys: Vec<Toy>,
+}
+
+#[derive(Debug, Default, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Toy {
+    id: Option<i64>,
+    name: String,
+    child_id: i64,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Family::table_name(), |table| {
+            table.id(None);
+            table.string(Family::col_name_for_name());
+        })
+        .await;
+    _ = manager
+        .create_table_schema(Child::table_name(), |table| {
+            table.id(None);
+            table.string(Child::col_name_for_name());
+            table.integer(Child::col_name_for_family_id());
+        })
+        .await;
+    _ = manager
+        .create_table_schema(Toy::table_name(), |table| {
+            table.id(None);
+            table.string(Toy::col_name_for_name());
+            table.integer(Toy::col_name_for_child_id());
+        })
+        .await;
+
+    _ = manager
+        .insert_into::<Family>(Family {
+            name: "family 1".to_string(),
+            ..Default::default()
+        })
+        .await;
+    for name in ["child 1", "child 2"] {
+        _ = manager
+            .insert_into::<Child>(Child {
+                name: name.to_string(),
+                family_id: 1,
+                ..Default::default()
+            })
+            .await;
+    }
+    for (name, child_id) in [("ball", 1), ("kite", 1), ("drum", 2)] {
+        _ = manager
+            .insert_into::<Toy>(Toy {
+                name: name.to_string(),
+                child_id,
+                ..Default::default()
+            })
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn test_nested_relations_of_non_clone_entities() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut families = FamilyRepo::new(&manager)
+        .with("children.toys")
+        .get()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(families.len(), 1);
+
+    let mut children = families.pop().unwrap().children;
+    children.sort_by_key(|child| child.id);
+    let toys = children
+        .iter()
+        .map(|child| child.toys.len())
+        .collect::<Vec<_>>();
+    assert_eq!(toys, [2, 1]);
+}
+
+#[tokio::test]
+async fn test_unknown_relation_path() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut repo = FamilyRepo::new(&manager);
+    assert!(repo.with("pets").get().await.is_err());
+    assert!(repo.with("children.pets").get().await.is_err());
+
+    // the repo is reset after the failed query
+    assert!(repo.get().await.is_ok());
+}
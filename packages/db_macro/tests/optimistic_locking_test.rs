@@ -0,0 +1,82 @@
+use dirtybase_common::db::base::stale_entity::StaleEntity;
+use dirtybase_db::{
+    TableModel,
+    base::manager::Manager,
+    connector::sqlite::make_sqlite_in_memory_manager,
+    types::{CreatedAtField, DeletedAtField, UpdatedAtField},
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(version = "lock_version")]
+struct Article {
+    id: Option<i64>,
+    title: String,
+    lock_version: i64,
+    created_at: CreatedAtField,
+    updated_at: UpdatedAtField,
+    deleted_at: DeletedAtField,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Article::table_name(), |table| {
+            table.id(None);
+            table.string(Article::col_name_for_title());
+            table.integer(Article::col_name_for_lock_version());
+            table.timestamps();
+            table.soft_deletable();
+        })
+        .await;
+}
+
+fn is_stale(result: Result<impl std::fmt::Debug, anyhow::Error>) -> bool {
+    matches!(result, Err(e) if e.downcast_ref::<StaleEntity>().is_some())
+}
+
+#[tokio::test]
+async fn test_stale_copy_writes_fail() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut repo = ArticleRepo::new(&manager);
+    let article = repo
+        .insert(Article {
+            title: "first draft".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let mut first = article.clone();
+    let stale = article;
+
+    first.title = "second draft".to_string();
+    let first = repo.update(first).await.unwrap();
+    assert_eq!(first.lock_version, 1);
+
+    assert!(is_stale(repo.update(stale.clone()).await));
+    assert!(is_stale(repo.delete(stale.clone()).await));
+    assert!(is_stale(repo.destroy(stale.clone()).await));
+
+    // trashing bumps the version so the stale copy can not un-trash the row
+    let trashed = repo.delete(first.clone()).await.unwrap();
+    assert!(is_stale(repo.update(first).await));
+    assert!(is_stale(repo.restore_record(stale).await));
+
+    let trashed = repo
+        .with_trashed()
+        .by_id(trashed.id.unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(trashed.deleted_at.is_some());
+    let restored = repo.restore_record(trashed).await.unwrap().unwrap();
+    assert!(restored.deleted_at.is_none());
+
+    // writes by ID use the current version
+    let id = restored.id.unwrap();
+    assert!(repo.delete_by_id(id).await.is_ok());
+    assert!(repo.restore(id).await.unwrap().is_some());
+    assert_eq!(repo.destroy_by_id(id).await.unwrap().rows_affected(), 1);
+}
@@ -0,0 +1,65 @@
+use dirtybase_db::{
+    TableModel, base::manager::Manager, connector::sqlite::make_sqlite_in_memory_manager,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[derive(Debug, Default, Clone, DirtyTable)]
+#[dirty(no_timestamp, no_soft_delete)]
+struct Category {
+    id: Option<i64>,
+    name: String,
+    parent_id: Option<i64>,
+    #[dirty(rel(kind = has_many, foreign_col = parent_id, no_soft_delete))]
+    children: Vec<Category>,
+}
+
+async fn setup_db(manager: &Manager) {
+    _ = manager
+        .create_table_schema(Category::table_name(), |table| {
+            table.id(None);
+            table.string(Category::col_name_for_name());
+            table.integer(Category::col_name_for_parent_id()).nullable();
+        })
+        .await;
+
+    // "books" has two children, each without children of their own
+    for (name, parent_id) in [("books", None), ("novels", Some(1)), ("poems", Some(1))] {
+        _ = manager
+            .insert_into::<Category>(Category {
+                name: name.to_string(),
+                parent_id,
+                ..Default::default()
+            })
+            .await;
+    }
+}
+
+fn names(list: Option<Vec<Category>>) -> Vec<String> {
+    list.unwrap_or_default()
+        .into_iter()
+        .map(|c| c.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_self_referencing_relation() {
+    let manager = make_sqlite_in_memory_manager().await;
+    setup_db(&manager).await;
+
+    let mut repo = CategoryRepo::new(&manager);
+    assert_eq!(names(repo.has_children().get().await.unwrap()), ["books"]);
+    assert_eq!(
+        names(repo.doesnt_have_children().get().await.unwrap()),
+        ["novels", "poems"]
+    );
+
+    // the related rows are aliased as `_<relation>`
+    let found = repo
+        .where_has_children(|query| {
+            query.is_eq("_children.name", "poems");
+        })
+        .get()
+        .await
+        .unwrap();
+    assert_eq!(names(found), ["books"]);
+}
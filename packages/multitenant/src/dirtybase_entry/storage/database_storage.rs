@@ -34,7 +34,8 @@ impl TenantStorage for TenantDatabaseStorage {
 
     async fn delete(&self, id: TenantId) -> Result<(), anyhow::Error> {
         let mut w_lock = self.repo.write().await;
-        w_lock.delete_by_id(id).await
+        _ = w_lock.delete_by_id(id).await?;
+        Ok(())
     }
 
     async fn restore(&self, id: TenantId) -> Result<(), anyhow::Error> {
@@ -45,7 +46,8 @@ impl TenantStorage for TenantDatabaseStorage {
 
     async fn destroy(&self, id: TenantId) -> Result<(), anyhow::Error> {
         let mut w_lock = self.repo.write().await;
-        w_lock.destroy_by_id(id).await
+        _ = w_lock.destroy_by_id(id).await?;
+        Ok(())
     }
 
     async fn find(
@@ -0,0 +1,37 @@
+mod work;
+
+use dirtybase_contract::cli_contract::{
+    CliCommandManager,
+    clap::{self, Arg, ArgAction},
+};
+
+pub(crate) fn setup_cli(mut manager: CliCommandManager) -> CliCommandManager {
+    // $ queue:work --queue emails,default --concurrency 8
+    let command = clap::Command::new("queue:work")
+        .about("Starts a worker that handles queued jobs")
+        .arg(
+            Arg::new("queue")
+                .short('q')
+                .long("queue")
+                .help("Comma separated list of queues to work on, in order of priority"),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .short('c')
+                .long("concurrency")
+                .value_parser(clap::value_parser!(usize))
+                .help("Number of jobs to handle at the same time"),
+        )
+        .arg(
+            Arg::new("stop-when-empty")
+                .long("stop-when-empty")
+                .action(ArgAction::SetTrue)
+                .help("Exit once the queues are empty"),
+        );
+
+    manager.register(command, |_name, matches, context| {
+        Box::pin(async move { work::execute(context, matches).await })
+    });
+
+    manager
+}
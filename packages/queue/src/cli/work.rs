@@ -0,0 +1,60 @@
+use dirtybase_contract::{cli_contract::prelude::ArgMatches, prelude::Context};
+use tokio_util::sync::CancellationToken;
+
+use crate::{QueueManager, WorkerOption};
+
+pub(super) async fn execute(context: Context, matches: ArgMatches) -> Result<(), anyhow::Error> {
+    let manager = context.get::<QueueManager>().await?;
+    let config = manager.config();
+
+    let queues = matches
+        .get_one::<String>("queue")
+        .map(|q| {
+            q.split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<String>>()
+        })
+        .filter(|list| !list.is_empty())
+        .unwrap_or_else(|| vec![config.queue().to_string()]);
+    let concurrency = matches
+        .get_one::<usize>("concurrency")
+        .cloned()
+        .unwrap_or_else(|| config.concurrency());
+
+    let mut option = WorkerOption::new(queues, concurrency);
+    if matches.get_flag("stop-when-empty") {
+        option = option.stop_when_empty();
+    }
+
+    let token = CancellationToken::new();
+    let worker_token = token.clone();
+    let mut worker = tokio::spawn(async move { manager.work(option, worker_token).await });
+
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => token.cancel(),
+        _ = terminate => token.cancel(),
+        _ = &mut worker => return Ok(()),
+    }
+
+    // let the jobs in flight complete
+    worker.await?;
+    Ok(())
+}
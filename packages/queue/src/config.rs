@@ -0,0 +1,110 @@
+use dirtybase_contract::{
+    app_contract::Context,
+    config_contract::{ConfigResult, DirtyConfig, TryFromDirtyConfig},
+};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    connector: String,
+    queue: String,
+    concurrency: usize,
+    max_attempts: u32,
+    backoff: u64,
+    retry_after: u64,
+    sleep: u64,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            connector: String::from("memory"),
+            queue: String::from("default"),
+            concurrency: 4,
+            max_attempts: 3,
+            backoff: 5,
+            retry_after: 90,
+            sleep: 3,
+        }
+    }
+}
+
+impl QueueConfig {
+    pub fn connector(&self) -> String {
+        self.connector.clone()
+    }
+
+    pub fn connector_ref(&self) -> &str {
+        self.connector.as_str()
+    }
+
+    /// The queue workers listen on when none is specified
+    pub fn queue(&self) -> &str {
+        self.queue.as_str()
+    }
+
+    /// Number of jobs a worker handles at the same time
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.max(1)
+    }
+
+    /// Number of times a job is tried before it is considered failed
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Initial delay in seconds before a failed job is retried
+    pub fn backoff(&self) -> u64 {
+        self.backoff
+    }
+
+    /// Number of seconds a job stays reserved by a worker
+    pub fn retry_after(&self) -> u64 {
+        self.retry_after
+    }
+
+    /// Number of seconds a worker waits when the queue is empty
+    pub fn sleep(&self) -> u64 {
+        self.sleep
+    }
+
+    pub fn set_connector(&mut self, connector: &str) -> &mut Self {
+        self.connector = connector.to_string();
+        self
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: u32) -> &mut Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn set_backoff(&mut self, backoff: u64) -> &mut Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn set_sleep(&mut self, sleep: u64) -> &mut Self {
+        self.sleep = sleep;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl TryFromDirtyConfig for QueueConfig {
+    type Returns = Self;
+    async fn from_config(config: &DirtyConfig, _ctx: &Context) -> ConfigResult<Self::Returns> {
+        let con: Self = config
+            .optional_file("queue.toml", Some("DTY_QUEUE"))
+            .build()
+            .await?
+            .try_deserialize()
+            .unwrap_or_default();
+
+        Ok(con)
+    }
+}
@@ -1 +1,7 @@
+mod database_connector;
 mod memory_connector;
+mod redis_connector;
+
+pub use database_connector::*;
+pub use memory_connector::*;
+pub use redis_connector::*;
@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+
+use dirtybase_common::db::{TableModel, base::manager::Manager, field_values::FieldValue};
+use dirtybase_contract::{
+    db_contract::base::query::QueryBuilder,
+    queue_contract::{Connector, FailedJob, JobPayload},
+};
+use dirtybase_helper::time::now;
+
+use crate::{
+    ConnectorResolver,
+    model::{QueueFailedJobEntity, QueueJobEntity, QueueJobEntityRepo},
+};
+
+/// Number of candidate rows fetched when trying to reserve a job
+const CLAIM_BATCH: usize = 5;
+
+/// Stores jobs in the `queue_jobs` table of the default database
+///
+/// A job is reserved by setting its `reserved_until` column. A value of
+/// zero means the job is not reserved. Jobs
+/// reserved by a worker that crashed become available again once that
+/// time is reached.
+#[derive(Debug, Clone)]
+pub struct DatabaseConnector {
+    manager: Manager,
+}
+
+impl DatabaseConnector {
+    pub fn new(manager: Manager) -> Self {
+        Self { manager }
+    }
+
+    pub async fn register() {
+        ConnectorResolver::register("database", |mut resolver| async move {
+            match resolver.context_ref().get::<Manager>().await {
+                Ok(manager) => resolver.set_connector(Self::new(manager)),
+                Err(e) => tracing::error!("could not get db manager for queue connector: {}", e),
+            }
+
+            resolver
+        })
+        .await;
+    }
+
+    /// SQLite does not support `SELECT ... FOR UPDATE`
+    fn supports_lock_for_update(&self) -> bool {
+        self.manager.db_kind().as_str() != "sqlite"
+    }
+
+    fn is_claimable(query: &mut QueryBuilder, now: i64) {
+        query.le_or_eq(QueueJobEntity::col_name_for_reserved_until(), now);
+    }
+
+    /// Tries to reserve the job for the current worker
+    ///
+    /// The update only changes the row when the job has not been claimed in the meantime
+    async fn claim(
+        manager: &Manager,
+        entity: &QueueJobEntity,
+        reserve_for: u64,
+    ) -> Result<Option<JobPayload>, anyhow::Error> {
+        let now = now().timestamp();
+        let mut job = entity.to_payload()?;
+        job.reserve(reserve_for);
+
+        let mut row = Self::state_of(&job)?;
+        row.insert(
+            QueueJobEntity::col_name_for_reserved_by(),
+            FieldValue::from(job.reserved_by().unwrap_or_default()),
+        );
+
+        let result = manager
+            .update(QueueJobEntity::table_name(), row, |q| {
+                q.is_eq(QueueJobEntity::col_name_for_id(), entity.id());
+                Self::is_claimable(q, now);
+            })
+            .await?;
+
+        Ok((result.rows_affected() > 0).then_some(job))
+    }
+
+    /// Limits the query to the job while it is still reserved by the worker holding it
+    fn is_owned(query: &mut QueryBuilder, job: &JobPayload) {
+        query
+            .is_eq(QueueJobEntity::col_name_for_id(), job.id())
+            .is_eq(
+                QueueJobEntity::col_name_for_reserved_by(),
+                job.reserved_by().unwrap_or_default(),
+            );
+    }
+
+    fn lost_reservation(job: &JobPayload) -> anyhow::Error {
+        anyhow::anyhow!("job {} is no longer reserved by this worker", job.id())
+    }
+
+    /// Deletes the job when the worker still holds its reservation
+    async fn delete_owned(manager: &Manager, job: &JobPayload) -> Result<(), anyhow::Error> {
+        let result = manager
+            .delete(QueueJobEntity::table_name(), |q| Self::is_owned(q, job))
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Self::lost_reservation(job));
+        }
+        Ok(())
+    }
+
+    /// The columns that reflect the current state of the job
+    fn state_of(job: &JobPayload) -> Result<HashMap<&'static str, FieldValue>, anyhow::Error> {
+        Ok(HashMap::from([
+            (
+                QueueJobEntity::col_name_for_payload(),
+                FieldValue::from(serde_json::to_string(job)?),
+            ),
+            (
+                QueueJobEntity::col_name_for_attempts(),
+                FieldValue::from(job.attempts()),
+            ),
+            (
+                QueueJobEntity::col_name_for_reserved_until(),
+                FieldValue::from(job.reserved_until().unwrap_or_default()),
+            ),
+            (
+                QueueJobEntity::col_name_for_available_at(),
+                FieldValue::from(job.available_at()),
+            ),
+        ]))
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for DatabaseConnector {
+    async fn push(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        self.manager
+            .insert(
+                QueueJobEntity::table_name(),
+                QueueJobEntity::try_from(&job)?,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn pop(
+        &self,
+        queue: &str,
+        reserve_for: u64,
+    ) -> Result<Option<JobPayload>, anyhow::Error> {
+        let now = now().timestamp();
+        let lock = self.supports_lock_for_update();
+
+        // the candidates are locked and claimed on the writer, a replica may be behind
+        self.manager
+            .transaction(|manager| async move {
+                let candidates = manager
+                    .select_from_table(QueueJobEntity::table_name(), |q| {
+                        q.is_eq(QueueJobEntity::col_name_for_queue(), queue)
+                            .le_or_eq(QueueJobEntity::col_name_for_available_at(), now);
+                        Self::is_claimable(q, now);
+                        q.asc(QueueJobEntity::col_name_for_available_at())
+                            .limit(CLAIM_BATCH);
+                        if lock {
+                            q.lock_for_update();
+                        }
+                    })
+                    .fetch_all_to::<QueueJobEntity>()
+                    .await?;
+
+                for entity in candidates.iter() {
+                    if let Some(job) = Self::claim(&manager, entity, reserve_for).await? {
+                        return Ok(Some(job));
+                    }
+                }
+
+                Ok(None)
+            })
+            .await
+    }
+
+    async fn release(&self, job: JobPayload, delay: u64) -> Result<(), anyhow::Error> {
+        let mut released = job.clone();
+        released.release(delay);
+        let mut row = Self::state_of(&released)?;
+        row.insert(
+            QueueJobEntity::col_name_for_reserved_by(),
+            FieldValue::from(""),
+        );
+
+        let result = self
+            .manager
+            .update(QueueJobEntity::table_name(), row, |q| {
+                Self::is_owned(q, &job)
+            })
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Self::lost_reservation(&job));
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        Self::delete_owned(&self.manager, &job).await
+    }
+
+    async fn fail(&self, job: FailedJob) -> Result<(), anyhow::Error> {
+        let entity = QueueFailedJobEntity::try_from(&job)?;
+
+        // the failure is only recorded by the worker that still holds the job
+        self.manager
+            .transaction(|manager| async move {
+                Self::delete_owned(&manager, job.job()).await?;
+                manager
+                    .insert(QueueFailedJobEntity::table_name(), entity)
+                    .await?;
+                Ok(())
+            })
+            .await
+    }
+
+    async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error> {
+        let list = self
+            .manager
+            .select_from_table(QueueFailedJobEntity::table_name(), |q| {
+                q.asc(QueueFailedJobEntity::col_name_for_failed_at());
+            })
+            .fetch_all_to::<QueueFailedJobEntity>()
+            .await?;
+
+        list.iter().map(|entity| entity.to_failed_job()).collect()
+    }
+
+    async fn forget(&self, id: &str) -> Result<Option<FailedJob>, anyhow::Error> {
+        let Some(entity) = self
+            .manager
+            .select_from_table(QueueFailedJobEntity::table_name(), |q| {
+                q.is_eq(QueueFailedJobEntity::col_name_for_id(), id);
+            })
+            .fetch_one_to::<QueueFailedJobEntity>()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        self.manager
+            .delete(QueueFailedJobEntity::table_name(), |q| {
+                q.is_eq(QueueFailedJobEntity::col_name_for_id(), id);
+            })
+            .await?;
+
+        entity.to_failed_job().map(Some)
+    }
+
+    async fn size(&self, queue: &str) -> Result<usize, anyhow::Error> {
+        let now = now().timestamp();
+        let total = QueueJobEntityRepo::new(&self.manager)
+            .filter(|q| {
+                q.is_eq(QueueJobEntity::col_name_for_queue(), queue);
+                Self::is_claimable(q, now);
+            })
+            .count()
+            .await?;
+
+        Ok(total as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dirtybase_contract::{
+        app_contract::Context, db_contract::migration::Migration, queue_contract::Job,
+    };
+    use dirtybase_db::connector::sqlite::make_sqlite_in_memory_manager;
+
+    use super::*;
+    use crate::dirtybase_entry::migration::Mig1792281600CreateQueueTables;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct PingJob;
+
+    #[async_trait::async_trait]
+    impl Job for PingJob {
+        fn name() -> &'static str {
+            "test::ping_job"
+        }
+
+        async fn handle(&self, _context: Context) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+    }
+
+    async fn make_connector() -> DatabaseConnector {
+        let manager = make_sqlite_in_memory_manager().await;
+        Mig1792281600CreateQueueTables.up(&manager).await.unwrap();
+        DatabaseConnector::new(manager)
+    }
+
+    #[tokio::test]
+    async fn test_reserving_jobs() {
+        let connector = make_connector().await;
+        let job = JobPayload::new(&PingJob, 3, 5).unwrap();
+        connector.push(job.clone()).await.unwrap();
+        assert_eq!(connector.size("default").await.unwrap(), 1);
+
+        let reserved = connector.pop("default", 60).await.unwrap().unwrap();
+        assert_eq!(reserved.id(), job.id());
+        assert_eq!(reserved.attempts(), 1);
+        assert_eq!(connector.size("default").await.unwrap(), 0);
+        assert!(connector.pop("default", 60).await.unwrap().is_none());
+
+        connector.release(reserved, 0).await.unwrap();
+        let reserved = connector.pop("default", 60).await.unwrap().unwrap();
+        assert_eq!(reserved.attempts(), 2);
+
+        connector.delete(reserved).await.unwrap();
+        assert_eq!(connector.size("default").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_lost_reservation() {
+        let connector = make_connector().await;
+        connector
+            .push(JobPayload::new(&PingJob, 3, 5).unwrap())
+            .await
+            .unwrap();
+
+        // the reservation ends right away so a second worker can take the job
+        let lost = connector.pop("default", 0).await.unwrap().unwrap();
+        let current = connector.pop("default", 60).await.unwrap().unwrap();
+        assert_eq!(lost.id(), current.id());
+
+        assert!(connector.release(lost.clone(), 0).await.is_err());
+        assert!(connector.delete(lost.clone()).await.is_err());
+        assert!(
+            connector
+                .fail(FailedJob::new(lost, "failed on purpose"))
+                .await
+                .is_err()
+        );
+        assert!(connector.failed().await.unwrap().is_empty());
+
+        connector.delete(current).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_jobs() {
+        let connector = make_connector().await;
+        connector
+            .push(JobPayload::new(&PingJob, 3, 5).unwrap())
+            .await
+            .unwrap();
+
+        let reserved = connector.pop("default", 60).await.unwrap().unwrap();
+        let id = reserved.id().to_string();
+        connector
+            .fail(FailedJob::new(reserved, "failed on purpose"))
+            .await
+            .unwrap();
+
+        assert_eq!(connector.size("default").await.unwrap(), 0);
+        let failed = connector.failed().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].reason(), "failed on purpose");
+
+        assert!(connector.forget(&id).await.unwrap().is_some());
+        assert!(connector.failed().await.unwrap().is_empty());
+    }
+}
@@ -1 +1,194 @@
+use std::{collections::HashMap, sync::Arc};
 
+use dirtybase_contract::queue_contract::{Connector, FailedJob, JobPayload};
+use tokio::sync::RwLock;
+
+use crate::ConnectorResolver;
+
+#[derive(Debug, Default)]
+struct Storage {
+    queues: HashMap<String, Vec<JobPayload>>,
+    reserved: HashMap<String, JobPayload>,
+    failed: Vec<FailedJob>,
+}
+
+impl Storage {
+    /// Puts the jobs whose reservation expired back on their queue
+    fn requeue_expired(&mut self) {
+        let expired = self
+            .reserved
+            .iter()
+            .filter(|(_, job)| !job.is_reserved())
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        for id in expired {
+            if let Some(mut job) = self.reserved.remove(&id) {
+                job.release(0);
+                self.queues
+                    .entry(job.queue().to_string())
+                    .or_default()
+                    .push(job);
+            }
+        }
+    }
+
+    /// Removes the reservation when the worker still holds it
+    fn take_reservation(&mut self, job: &JobPayload) -> Result<(), anyhow::Error> {
+        match self.reserved.get(job.id()) {
+            Some(reserved) if reserved.reserved_by() == job.reserved_by() => {
+                self.reserved.remove(job.id());
+                Ok(())
+            }
+            _ => Err(anyhow::anyhow!(
+                "job {} is no longer reserved by this worker",
+                job.id()
+            )),
+        }
+    }
+}
+
+/// Keeps jobs in the current process memory
+///
+/// Jobs are lost when the process ends
+#[derive(Debug, Clone, Default)]
+pub struct MemoryConnector {
+    storage: Arc<RwLock<Storage>>,
+}
+
+impl MemoryConnector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn register() {
+        ConnectorResolver::register("memory", |mut resolver| async move {
+            resolver.set_connector(MemoryConnector::new());
+            resolver
+        })
+        .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for MemoryConnector {
+    async fn push(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        let mut w_lock = self.storage.write().await;
+        w_lock
+            .queues
+            .entry(job.queue().to_string())
+            .or_default()
+            .push(job);
+
+        Ok(())
+    }
+
+    async fn pop(
+        &self,
+        queue: &str,
+        reserve_for: u64,
+    ) -> Result<Option<JobPayload>, anyhow::Error> {
+        let mut w_lock = self.storage.write().await;
+        w_lock.requeue_expired();
+        let Some(list) = w_lock.queues.get_mut(queue) else {
+            return Ok(None);
+        };
+
+        let Some(index) = list.iter().position(|job| job.is_available()) else {
+            return Ok(None);
+        };
+
+        let mut job = list.remove(index);
+        job.reserve(reserve_for);
+        w_lock.reserved.insert(job.id().to_string(), job.clone());
+
+        Ok(Some(job))
+    }
+
+    async fn release(&self, mut job: JobPayload, delay: u64) -> Result<(), anyhow::Error> {
+        self.storage.write().await.take_reservation(&job)?;
+        job.release(delay);
+        self.push(job).await
+    }
+
+    async fn delete(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        self.storage.write().await.take_reservation(&job)
+    }
+
+    async fn fail(&self, job: FailedJob) -> Result<(), anyhow::Error> {
+        let mut w_lock = self.storage.write().await;
+        w_lock.take_reservation(job.job())?;
+        w_lock.failed.push(job);
+        Ok(())
+    }
+
+    async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error> {
+        Ok(self.storage.read().await.failed.clone())
+    }
+
+    async fn forget(&self, id: &str) -> Result<Option<FailedJob>, anyhow::Error> {
+        let mut w_lock = self.storage.write().await;
+        Ok(w_lock
+            .failed
+            .iter()
+            .position(|job| job.id() == id)
+            .map(|index| w_lock.failed.remove(index)))
+    }
+
+    async fn size(&self, queue: &str) -> Result<usize, anyhow::Error> {
+        Ok(self
+            .storage
+            .read()
+            .await
+            .queues
+            .get(queue)
+            .map(|list| list.len())
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dirtybase_contract::{app_contract::Context, queue_contract::Job};
+
+    use super::*;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct NoopJob;
+
+    #[async_trait::async_trait]
+    impl Job for NoopJob {
+        fn name() -> &'static str {
+            "test::noop_job"
+        }
+
+        async fn handle(&self, _context: Context) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_reservation_is_requeued() {
+        let connector = MemoryConnector::new();
+        let job = JobPayload::new(&NoopJob, 3, 0).unwrap();
+        let queue = job.queue().to_string();
+        connector.push(job).await.unwrap();
+
+        // the reservation expires right away
+        let first = connector.pop(&queue, 0).await.unwrap().unwrap();
+        assert_eq!(connector.size(&queue).await.unwrap(), 0);
+
+        let second = connector.pop(&queue, 60).await.unwrap().unwrap();
+        assert_eq!(second.id(), first.id());
+        assert_eq!(second.attempts(), 2);
+        assert!(connector.pop(&queue, 60).await.unwrap().is_none());
+
+        // the first worker lost the job
+        assert!(connector.delete(first.clone()).await.is_err());
+        assert!(connector.release(first, 0).await.is_err());
+        assert_eq!(connector.size(&queue).await.unwrap(), 0);
+
+        connector.delete(second).await.unwrap();
+        assert!(connector.pop(&queue, 60).await.unwrap().is_none());
+    }
+}
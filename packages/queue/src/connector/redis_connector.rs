@@ -0,0 +1,370 @@
+use std::sync::OnceLock;
+
+use dirtybase_contract::queue_contract::{Connector, FailedJob, JobPayload};
+use dirtybase_helper::time::now_ts;
+use redis::AsyncCommands;
+
+use crate::ConnectorResolver;
+
+const KEY_PREFIX: &str = "queues";
+
+/// Moves the due delayed jobs and the expired reservations back onto the
+/// queue, then pops the next job, reserves it and counts the attempt
+static POP_SCRIPT: OnceLock<redis::Script> = OnceLock::new();
+
+/// Stores jobs in redis
+///
+/// For each queue the job IDs live in a list (ready to be picked), a sorted
+/// set scored by the time they become available (delayed) and a sorted set
+/// scored by the time their reservation ends (in flight). The jobs' payload
+/// are kept in a hash so that the IDs can move between them atomically, and
+/// the reservations made since a payload was last written are counted in
+/// another hash.
+///
+/// The queue name is used as the keys' hash tag so that all the keys of a
+/// queue live on the same Redis Cluster slot.
+#[derive(Clone)]
+pub struct RedisConnector {
+    client: redis::aio::MultiplexedConnection,
+}
+
+impl RedisConnector {
+    pub fn new(client: redis::aio::MultiplexedConnection) -> Self {
+        Self { client }
+    }
+
+    pub async fn register() {
+        ConnectorResolver::register("redis", |mut resolver| async move {
+            dirtybase_3rd_client::redis::init().await;
+            match dirtybase_3rd_client::redis::get_client().await {
+                Ok(client) => resolver.set_connector(Self::new(client)),
+                Err(e) => tracing::error!("could not get redis client for queue connector: {}", e),
+            }
+
+            resolver
+        })
+        .await;
+    }
+
+    fn ready_key(queue: &str) -> String {
+        format!("{KEY_PREFIX}:{{{queue}}}:ready")
+    }
+
+    fn delayed_key(queue: &str) -> String {
+        format!("{KEY_PREFIX}:{{{queue}}}:delayed")
+    }
+
+    fn reserved_key(queue: &str) -> String {
+        format!("{KEY_PREFIX}:{{{queue}}}:reserved")
+    }
+
+    fn jobs_key(queue: &str) -> String {
+        format!("{KEY_PREFIX}:{{{queue}}}:jobs")
+    }
+
+    fn attempts_key(queue: &str) -> String {
+        format!("{KEY_PREFIX}:{{{queue}}}:attempts")
+    }
+
+    fn failed_key() -> String {
+        format!("{KEY_PREFIX}:failed")
+    }
+
+    fn pop_script() -> &'static redis::Script {
+        POP_SCRIPT.get_or_init(|| {
+            redis::Script::new(
+                r"
+                local ready, delayed, reserved, jobs, attempts = KEYS[1], KEYS[2], KEYS[3], KEYS[4], KEYS[5]
+                local now = tonumber(ARGV[1])
+
+                for _, key in ipairs({delayed, reserved}) do
+                    local due = redis.call('ZRANGEBYSCORE', key, '-inf', now)
+                    if #due > 0 then
+                        redis.call('ZREMRANGEBYSCORE', key, '-inf', now)
+                        for i = 1, #due do
+                            redis.call('RPUSH', ready, due[i])
+                        end
+                    end
+                end
+
+                local id = redis.call('LPOP', ready)
+                if not id then
+                    return false
+                end
+
+                local payload = redis.call('HGET', jobs, id)
+                if not payload then
+                    return false
+                end
+
+                redis.call('ZADD', reserved, tonumber(ARGV[2]), id)
+                local reservations = redis.call('HINCRBY', attempts, id, 1)
+                return {payload, reservations}
+                ",
+            )
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Connector for RedisConnector {
+    async fn push(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        let mut client = self.client.clone();
+        let mut pipe = redis::pipe();
+        pipe.atomic().hset(
+            Self::jobs_key(job.queue()),
+            job.id(),
+            serde_json::to_string(&job)?,
+        );
+
+        if job.is_available() {
+            pipe.rpush(Self::ready_key(job.queue()), job.id());
+        } else {
+            pipe.zadd(Self::delayed_key(job.queue()), job.id(), job.available_at());
+        }
+
+        Ok(pipe.exec_async(&mut client).await?)
+    }
+
+    async fn pop(
+        &self,
+        queue: &str,
+        reserve_for: u64,
+    ) -> Result<Option<JobPayload>, anyhow::Error> {
+        let mut client = self.client.clone();
+        let now = now_ts();
+
+        let popped: Option<(String, u32)> = Self::pop_script()
+            .key(Self::ready_key(queue))
+            .key(Self::delayed_key(queue))
+            .key(Self::reserved_key(queue))
+            .key(Self::jobs_key(queue))
+            .key(Self::attempts_key(queue))
+            .arg(now)
+            .arg(now + reserve_for as i64)
+            .invoke_async(&mut client)
+            .await?;
+
+        let Some((payload, reservations)) = popped else {
+            return Ok(None);
+        };
+
+        // reservations that expired without a release count as attempts too
+        let mut job: JobPayload = serde_json::from_str(&payload)?;
+        for _ in 0..reservations {
+            job.reserve(reserve_for);
+        }
+
+        Ok(Some(job))
+    }
+
+    async fn release(&self, mut job: JobPayload, delay: u64) -> Result<(), anyhow::Error> {
+        let mut client = self.client.clone();
+        job.release(delay);
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .zrem(Self::reserved_key(job.queue()), job.id())
+            .hset(
+                Self::jobs_key(job.queue()),
+                job.id(),
+                serde_json::to_string(&job)?,
+            )
+            .hdel(Self::attempts_key(job.queue()), job.id());
+
+        if delay == 0 {
+            pipe.rpush(Self::ready_key(job.queue()), job.id());
+        } else {
+            pipe.zadd(Self::delayed_key(job.queue()), job.id(), job.available_at());
+        }
+
+        Ok(pipe.exec_async(&mut client).await?)
+    }
+
+    async fn delete(&self, job: JobPayload) -> Result<(), anyhow::Error> {
+        let mut client = self.client.clone();
+        Ok(redis::pipe()
+            .atomic()
+            .zrem(Self::reserved_key(job.queue()), job.id())
+            .hdel(Self::jobs_key(job.queue()), job.id())
+            .hdel(Self::attempts_key(job.queue()), job.id())
+            .exec_async(&mut client)
+            .await?)
+    }
+
+    async fn fail(&self, job: FailedJob) -> Result<(), anyhow::Error> {
+        let mut client = self.client.clone();
+        let queue = job.job().queue();
+
+        // the failed jobs are not on the queue's cluster slot. The job is
+        // recorded first so that it is never lost, if removing it from the
+        // queue fails it is picked again once its reservation ends
+        client
+            .hset::<_, _, _, ()>(Self::failed_key(), job.id(), serde_json::to_string(&job)?)
+            .await?;
+        Ok(redis::pipe()
+            .atomic()
+            .zrem(Self::reserved_key(queue), job.id())
+            .hdel(Self::jobs_key(queue), job.id())
+            .hdel(Self::attempts_key(queue), job.id())
+            .exec_async(&mut client)
+            .await?)
+    }
+
+    async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error> {
+        let mut client = self.client.clone();
+        let list: Vec<String> = client.hvals(Self::failed_key()).await?;
+
+        let mut jobs = list
+            .iter()
+            .map(|entry| serde_json::from_str::<FailedJob>(entry))
+            .collect::<Result<Vec<FailedJob>, _>>()?;
+        jobs.sort_by_key(|job| job.failed_at());
+
+        Ok(jobs)
+    }
+
+    async fn forget(&self, id: &str) -> Result<Option<FailedJob>, anyhow::Error> {
+        let mut client = self.client.clone();
+        let (entry, _): (Option<String>, u32) = redis::pipe()
+            .atomic()
+            .hget(Self::failed_key(), id)
+            .hdel(Self::failed_key(), id)
+            .query_async(&mut client)
+            .await?;
+
+        entry
+            .map(|entry| serde_json::from_str(&entry))
+            .transpose()
+            .map_err(anyhow::Error::from)
+    }
+
+    async fn size(&self, queue: &str) -> Result<usize, anyhow::Error> {
+        let mut client = self.client.clone();
+        // expired reservations are moved back onto the queue by the next pop
+        let (ready, delayed, expired): (usize, usize, usize) = redis::pipe()
+            .llen(Self::ready_key(queue))
+            .zcard(Self::delayed_key(queue))
+            .zcount(Self::reserved_key(queue), "-inf", now_ts())
+            .query_async(&mut client)
+            .await?;
+
+        Ok(ready + delayed + expired)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use dirtybase_contract::{app_contract::Context, queue_contract::Job};
+
+    use super::*;
+
+    const QUEUE: &str = "redis_connector_test";
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct PingJob;
+
+    #[async_trait::async_trait]
+    impl Job for PingJob {
+        fn name() -> &'static str {
+            "test::redis_ping_job"
+        }
+
+        fn queue(&self) -> &str {
+            QUEUE
+        }
+
+        async fn handle(&self, _context: Context) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+    }
+
+    // needs a running server: DTY_TEST_REDIS_URL=redis://127.0.0.1 cargo test -- --ignored
+    async fn make_connector() -> RedisConnector {
+        let url = std::env::var("DTY_TEST_REDIS_URL").expect("DTY_TEST_REDIS_URL is not set");
+        let mut client = redis::Client::open(url)
+            .unwrap()
+            .get_multiplexed_async_connection()
+            .await
+            .unwrap();
+        let keys = [
+            RedisConnector::ready_key(QUEUE),
+            RedisConnector::delayed_key(QUEUE),
+            RedisConnector::reserved_key(QUEUE),
+            RedisConnector::jobs_key(QUEUE),
+            RedisConnector::attempts_key(QUEUE),
+        ];
+        client.del::<_, ()>(&keys).await.unwrap();
+
+        RedisConnector::new(client)
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_reserving_jobs() {
+        let connector = make_connector().await;
+        let job = JobPayload::new(&PingJob, 3, 5).unwrap();
+        connector.push(job.clone()).await.unwrap();
+        assert_eq!(connector.size(QUEUE).await.unwrap(), 1);
+
+        let reserved = connector.pop(QUEUE, 60).await.unwrap().unwrap();
+        assert_eq!(reserved.id(), job.id());
+        assert_eq!(reserved.attempts(), 1);
+        assert_eq!(connector.size(QUEUE).await.unwrap(), 0);
+        assert!(connector.pop(QUEUE, 60).await.unwrap().is_none());
+
+        connector.release(reserved, 0).await.unwrap();
+        let reserved = connector.pop(QUEUE, 60).await.unwrap().unwrap();
+        assert_eq!(reserved.attempts(), 2);
+
+        connector.release(reserved, 60).await.unwrap();
+        assert!(connector.pop(QUEUE, 60).await.unwrap().is_none());
+        assert_eq!(connector.size(QUEUE).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_expired_reservations_count_as_attempts() {
+        let connector = make_connector().await;
+        connector
+            .push(JobPayload::new(&PingJob, 3, 5).unwrap())
+            .await
+            .unwrap();
+
+        assert!(connector.pop(QUEUE, 0).await.unwrap().is_some());
+        let reserved = connector.pop(QUEUE, 60).await.unwrap().unwrap();
+        assert_eq!(reserved.attempts(), 2);
+
+        connector.delete(reserved).await.unwrap();
+        assert_eq!(connector.size(QUEUE).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_failed_jobs() {
+        let connector = make_connector().await;
+        connector
+            .push(JobPayload::new(&PingJob, 3, 5).unwrap())
+            .await
+            .unwrap();
+
+        let reserved = connector.pop(QUEUE, 60).await.unwrap().unwrap();
+        let id = reserved.id().to_string();
+        connector
+            .fail(FailedJob::new(reserved, "failed on purpose"))
+            .await
+            .unwrap();
+
+        assert_eq!(connector.size(QUEUE).await.unwrap(), 0);
+        assert!(connector.pop(QUEUE, 60).await.unwrap().is_none());
+        let failed = connector.failed().await.unwrap();
+        assert!(
+            failed
+                .iter()
+                .any(|job| job.id() == id && job.reason() == "failed on purpose")
+        );
+
+        assert!(connector.forget(&id).await.unwrap().is_some());
+        assert!(connector.forget(&id).await.unwrap().is_none());
+    }
+}
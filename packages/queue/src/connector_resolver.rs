@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use dirtybase_contract::{
+    prelude::Context,
+    queue_contract::{Connector, ConnectorProvider},
+};
+
+use crate::config::QueueConfig;
+
+pub struct ConnectorResolver {
+    context: Context,
+    provider: Option<ConnectorProvider>,
+    config: QueueConfig,
+}
+
+impl ConnectorResolver {
+    pub fn new(context: Context, config: QueueConfig) -> Self {
+        Self {
+            config,
+            context,
+            provider: None,
+        }
+    }
+
+    pub fn has_provider(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    pub fn context_ref(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn config_ref(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    pub fn context(&self) -> Context {
+        self.context.clone()
+    }
+
+    pub fn set_connector(&mut self, connector: impl Connector + 'static) {
+        self.provider = Some(ConnectorProvider::new(connector));
+    }
+
+    pub async fn get_provider(self) -> Result<ConnectorProvider, anyhow::Error> {
+        let name = self.config.connector();
+        match Self::get_middleware().await.send(self).await.provider {
+            Some(p) => Ok(p),
+            None => Err(anyhow::anyhow!(
+                "could not resolve the queue connector: {}",
+                name
+            )),
+        }
+    }
+
+    pub async fn register<F, Fut>(name: &str, callback: F)
+    where
+        F: Clone + Fn(Self) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Self> + Send + 'static,
+    {
+        let resolvers = Self::get_middleware().await;
+
+        let arc_name = Arc::new(name.to_string());
+        resolvers
+            .next(move |mut resolver, next| {
+                let cb = callback.clone();
+                let name = arc_name.clone();
+                Box::pin(async move {
+                    if resolver.config_ref().connector_ref() == name.as_str() {
+                        resolver = (cb)(resolver).await;
+                    }
+
+                    if !resolver.has_provider() {
+                        next.call(resolver).await
+                    } else {
+                        resolver
+                    }
+                })
+            })
+            .await;
+    }
+
+    async fn get_middleware() -> Arc<simple_middleware::Manager<Self, Self>> {
+        if let Some(r) = busybody::helpers::service_container().get().await {
+            r
+        } else {
+            let manager = simple_middleware::Manager::<Self, Self>::last(|resolver, _| {
+                Box::pin(async move { resolver })
+            })
+            .await;
+            busybody::helpers::service_container()
+                .set(manager)
+                .await
+                .get()
+                .await
+                .unwrap() // Should never failed as we just registered the instance
+        }
+    }
+}
@@ -0,0 +1,23 @@
+pub(crate) mod migration;
+
+use dirtybase_contract::{
+    ExtensionMigrations, ExtensionSetup, app_contract::Context, cli_contract::CliCommandManager,
+};
+
+#[derive(Debug, Default)]
+pub struct Extension;
+
+#[dirtybase_contract::async_trait]
+impl ExtensionSetup for Extension {
+    async fn setup(&mut self, context: &Context) {
+        super::setup(context).await;
+    }
+
+    fn migrations(&self, _context: &Context) -> Option<ExtensionMigrations> {
+        migration::setup()
+    }
+
+    fn register_cli_commands(&self, manager: CliCommandManager) -> CliCommandManager {
+        super::cli::setup_cli(manager)
+    }
+}
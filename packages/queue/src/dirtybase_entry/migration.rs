@@ -0,0 +1,15 @@
+mod mig_1792281600_create_queue_tables;
+
+#[cfg(test)]
+pub(crate) use mig_1792281600_create_queue_tables::Mig1792281600CreateQueueTables;
+
+/**
+ * The following function is automatically modified
+ * do not manually edit it
+ */
+pub(crate) fn setup() -> Option<dirtybase_contract::ExtensionMigrations> {
+    dirtybase_contract::register_migration![
+        mig_1792281600_create_queue_tables::Mig1792281600CreateQueueTables,
+        //
+    ]
+}
@@ -0,0 +1,48 @@
+use dirtybase_common::db::TableModel;
+use dirtybase_contract::db_contract::base::manager::Manager;
+use dirtybase_contract::db_contract::migration::Migration;
+
+use crate::model::{QueueFailedJobEntity, QueueJobEntity};
+
+pub struct Mig1792281600CreateQueueTables;
+
+#[dirtybase_contract::async_trait]
+impl Migration for Mig1792281600CreateQueueTables {
+    async fn up(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+        manager
+            .create_table_schema(QueueJobEntity::table_name(), |bp| {
+                bp.sized_string(QueueJobEntity::col_name_for_id(), 64)
+                    .set_as_primary();
+                bp.string(QueueJobEntity::col_name_for_queue());
+                bp.text(QueueJobEntity::col_name_for_payload());
+                bp.integer(QueueJobEntity::col_name_for_attempts())
+                    .default_is_zero();
+                bp.sized_string(QueueJobEntity::col_name_for_reserved_by(), 64)
+                    .default_is_empty_string();
+                bp.integer(QueueJobEntity::col_name_for_reserved_until())
+                    .default_is_zero();
+                bp.integer(QueueJobEntity::col_name_for_available_at());
+
+                bp.index(&[
+                    QueueJobEntity::col_name_for_queue(),
+                    QueueJobEntity::col_name_for_available_at(),
+                ]);
+            })
+            .await?;
+
+        manager
+            .create_table_schema(QueueFailedJobEntity::table_name(), |bp| {
+                bp.sized_string(QueueFailedJobEntity::col_name_for_id(), 64)
+                    .set_as_primary();
+                bp.string(QueueFailedJobEntity::col_name_for_queue());
+                bp.text(QueueFailedJobEntity::col_name_for_payload());
+                bp.integer(QueueFailedJobEntity::col_name_for_failed_at());
+            })
+            .await
+    }
+
+    async fn down(&self, manager: &Manager) -> Result<(), anyhow::Error> {
+        manager.drop_table(QueueJobEntity::table_name()).await?;
+        manager.drop_table(QueueFailedJobEntity::table_name()).await
+    }
+}
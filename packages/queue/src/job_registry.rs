@@ -0,0 +1,55 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+use anyhow::anyhow;
+use dirtybase_contract::{
+    app_contract::Context,
+    queue_contract::{Job, JobPayload},
+};
+use futures::future::BoxFuture;
+use tokio::sync::RwLock;
+
+type JobHandler =
+    Arc<dyn Fn(JobPayload, Context) -> BoxFuture<'static, Result<(), anyhow::Error>> + Send + Sync>;
+
+static JOB_HANDLERS: OnceLock<RwLock<HashMap<String, JobHandler>>> = OnceLock::new();
+
+/// Keeps track of the jobs a worker knows how to handle
+pub struct JobRegistry;
+
+impl JobRegistry {
+    /// Registers a job type so that workers can rebuild and handle it
+    pub async fn register<J: Job>() {
+        let handler: JobHandler = Arc::new(|payload: JobPayload, context: Context| {
+            Box::pin(async move {
+                let job = payload.to_job::<J>()?;
+                job.handle(context).await
+            })
+        });
+
+        Self::handlers()
+            .write()
+            .await
+            .insert(J::name().to_string(), handler);
+    }
+
+    pub async fn has(name: &str) -> bool {
+        Self::handlers().read().await.contains_key(name)
+    }
+
+    /// Rebuilds the job and calls its handler
+    pub async fn handle(payload: JobPayload, context: Context) -> Result<(), anyhow::Error> {
+        let handler = Self::handlers().read().await.get(payload.name()).cloned();
+
+        match handler {
+            Some(handler) => (handler)(payload, context).await,
+            None => Err(anyhow!("no handler registered for job: {}", payload.name())),
+        }
+    }
+
+    fn handlers() -> &'static RwLock<HashMap<String, JobHandler>> {
+        JOB_HANDLERS.get_or_init(|| RwLock::new(HashMap::new()))
+    }
+}
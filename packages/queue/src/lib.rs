@@ -1,6 +1,29 @@
-mod connector;
-mod manager;
+mod connector_resolver;
+mod dirtybase_entry;
+mod job_registry;
+mod queue_manager;
+mod resource_manager;
 
-pub async fn setup() {
-    // Setup stuff...
+pub(crate) mod cli;
+
+pub mod config;
+pub mod connector;
+pub mod model;
+
+pub use connector_resolver::*;
+pub use dirtybase_entry::*;
+pub use job_registry::*;
+pub use queue_manager::*;
+pub use resource_manager::*;
+
+use config::QueueConfig;
+use dirtybase_contract::app_contract::Context;
+
+pub async fn setup(context: &Context) {
+    context
+        .load_config::<QueueConfig>("queue")
+        .await
+        .expect("could not configure queue manager");
+
+    register_resource_manager().await;
 }
@@ -1,5 +0,0 @@
-// pub struct Manager;
-
-// impl dirtybase_contract::queue_contract::Manager for Manager {
-//     // ...
-// }
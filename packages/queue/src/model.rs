@@ -0,0 +1,5 @@
+mod queue_failed_job_entity;
+mod queue_job_entity;
+
+pub use queue_failed_job_entity::*;
+pub use queue_job_entity::*;
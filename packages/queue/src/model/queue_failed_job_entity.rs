@@ -0,0 +1,34 @@
+use dirtybase_contract::{
+    db_contract::types::{IntegerField, StringField},
+    queue_contract::FailedJob,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[derive(Debug, Clone, Default, DirtyTable)]
+#[dirty(table = "queue_failed_jobs", id = "id", no_soft_delete, no_timestamp)]
+pub struct QueueFailedJobEntity {
+    id: StringField,
+    queue: StringField,
+    payload: StringField,
+    failed_at: IntegerField,
+}
+
+impl QueueFailedJobEntity {
+    /// Rebuilds the failed job record
+    pub fn to_failed_job(&self) -> Result<FailedJob, anyhow::Error> {
+        Ok(serde_json::from_str(&self.payload)?)
+    }
+}
+
+impl TryFrom<&FailedJob> for QueueFailedJobEntity {
+    type Error = anyhow::Error;
+
+    fn try_from(job: &FailedJob) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: job.id().to_string().into(),
+            queue: job.job().queue().to_string().into(),
+            payload: serde_json::to_string(job)?.into(),
+            failed_at: job.failed_at(),
+        })
+    }
+}
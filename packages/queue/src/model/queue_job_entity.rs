@@ -0,0 +1,49 @@
+use dirtybase_contract::{
+    db_contract::types::{IntegerField, StringField},
+    queue_contract::JobPayload,
+};
+use dirtybase_db_macro::DirtyTable;
+
+#[derive(Debug, Clone, Default, DirtyTable)]
+#[dirty(table = "queue_jobs", id = "id", no_soft_delete, no_timestamp)]
+pub struct QueueJobEntity {
+    id: StringField,
+    queue: StringField,
+    payload: StringField,
+    attempts: IntegerField,
+    reserved_by: StringField,
+    reserved_until: IntegerField,
+    available_at: IntegerField,
+}
+
+impl QueueJobEntity {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// Token of the worker that reserved the job. Empty when the job is not reserved
+    pub fn reserved_by(&self) -> &str {
+        self.reserved_by.as_str()
+    }
+
+    /// Rebuilds the job's payload
+    pub fn to_payload(&self) -> Result<JobPayload, anyhow::Error> {
+        Ok(serde_json::from_str(&self.payload)?)
+    }
+}
+
+impl TryFrom<&JobPayload> for QueueJobEntity {
+    type Error = anyhow::Error;
+
+    fn try_from(job: &JobPayload) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: job.id().to_string().into(),
+            queue: job.queue().to_string().into(),
+            payload: serde_json::to_string(job)?.into(),
+            attempts: job.attempts() as i64,
+            reserved_by: StringField::default(),
+            reserved_until: job.reserved_until().unwrap_or_default(),
+            available_at: job.available_at(),
+        })
+    }
+}
@@ -0,0 +1,299 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use dirtybase_contract::{
+    app_contract::Context,
+    queue_contract::{Connector, ConnectorProvider, FailedJob, Job, JobPayload},
+};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+use crate::{JobRegistry, config::QueueConfig};
+
+const LOG_TARGET: &str = "dty::queue";
+
+#[derive(Clone)]
+pub struct QueueManager {
+    connector: ConnectorProvider,
+    config: QueueConfig,
+}
+
+/// Options used when running a worker
+#[derive(Debug, Clone)]
+pub struct WorkerOption {
+    queues: Vec<String>,
+    concurrency: usize,
+    stop_when_empty: bool,
+}
+
+impl WorkerOption {
+    pub fn new(queues: Vec<String>, concurrency: usize) -> Self {
+        Self {
+            queues,
+            concurrency: concurrency.max(1),
+            stop_when_empty: false,
+        }
+    }
+
+    /// The worker exits once all the queues are empty
+    pub fn stop_when_empty(mut self) -> Self {
+        self.stop_when_empty = true;
+        self
+    }
+
+    pub fn queues(&self) -> &[String] {
+        &self.queues
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+}
+
+impl From<&QueueConfig> for WorkerOption {
+    fn from(config: &QueueConfig) -> Self {
+        Self::new(vec![config.queue().to_string()], config.concurrency())
+    }
+}
+
+impl QueueManager {
+    pub fn new(connector: ConnectorProvider, config: QueueConfig) -> Self {
+        Self { connector, config }
+    }
+
+    pub fn connector(&self) -> &ConnectorProvider {
+        &self.connector
+    }
+
+    pub fn config(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    /// Pushes the job onto its queue
+    ///
+    /// Returns the ID of the queued job
+    pub async fn dispatch<J: Job>(&self, job: J) -> Result<String, anyhow::Error> {
+        let payload = self.make_payload(&job)?;
+        let id = payload.id().to_string();
+        self.connector.push(payload).await?;
+
+        Ok(id)
+    }
+
+    /// Pushes the job onto its queue. The job will not be picked before the delay elapses
+    ///
+    /// Returns the ID of the queued job
+    pub async fn dispatch_later<J: Job>(
+        &self,
+        job: J,
+        delay: Duration,
+    ) -> Result<String, anyhow::Error> {
+        let mut payload = self.make_payload(&job)?;
+        payload.delay(delay.as_secs());
+        let id = payload.id().to_string();
+        self.connector.push(payload).await?;
+
+        Ok(id)
+    }
+
+    /// Number of jobs waiting in the queue
+    pub async fn size(&self, queue: &str) -> Result<usize, anyhow::Error> {
+        self.connector.size(queue).await
+    }
+
+    /// Returns the jobs that ran out of attempts
+    pub async fn failed(&self) -> Result<Vec<FailedJob>, anyhow::Error> {
+        self.connector.failed().await
+    }
+
+    /// Pushes a failed job back onto its queue
+    pub async fn retry(&self, id: &str) -> Result<(), anyhow::Error> {
+        match self.connector.forget(id).await? {
+            Some(failed) => {
+                let mut job = failed.into_job();
+                job.reset_attempts().release(0);
+                self.connector.push(job).await
+            }
+            None => Err(anyhow!("failed job with ID '{}' does not exist", id)),
+        }
+    }
+
+    /// Handles the jobs on the queues until the token is cancelled
+    ///
+    /// Jobs that are being handled when the token is cancelled are allowed to complete
+    pub async fn work(&self, option: WorkerOption, token: CancellationToken) {
+        let semaphore = Arc::new(Semaphore::new(option.concurrency()));
+        let sleep = Duration::from_secs(self.config.sleep());
+
+        tracing::info!(
+            target: LOG_TARGET,
+            "worker started on queue(s): {}",
+            option.queues().join(",")
+        );
+
+        loop {
+            let permit = tokio::select! {
+                permit = semaphore.clone().acquire_owned() => match permit {
+                    Ok(p) => p,
+                    Err(_) => break,
+                },
+                _ = token.cancelled() => break,
+            };
+
+            match self.next_job(option.queues()).await {
+                Some(job) => {
+                    let manager = self.clone();
+                    tokio::spawn(async move {
+                        manager.process(job).await;
+                        drop(permit);
+                    });
+                }
+                None => {
+                    drop(permit);
+                    if option.stop_when_empty {
+                        if semaphore.available_permits() == option.concurrency() {
+                            break;
+                        }
+                        // jobs in flight may be released back onto the queue
+                        _ = semaphore.acquire_many(option.concurrency() as u32).await;
+                        continue;
+                    }
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(sleep) => (),
+                        _ = token.cancelled() => break,
+                    }
+                }
+            }
+        }
+
+        // wait for the jobs in flight
+        _ = semaphore.acquire_many(option.concurrency() as u32).await;
+        tracing::info!(target: LOG_TARGET, "worker stopped");
+    }
+
+    /// Handles a single reserved job
+    ///
+    /// A job that returns an error is released back onto the queue until
+    /// it runs out of attempts, after which it is recorded as failed
+    pub async fn process(&self, job: JobPayload) {
+        let id = job.id().to_string();
+        let name = job.name().to_string();
+        tracing::debug!(target: LOG_TARGET, "handling job {}: {}", &name, &id);
+
+        let result = match JobRegistry::handle(job.clone(), Context::new().await).await {
+            Ok(_) => self.connector.delete(job).await,
+            Err(e) if job.has_attempts_left() => {
+                let delay = job.next_backoff();
+                tracing::warn!(
+                    target: LOG_TARGET,
+                    "job {}: {} failed, retrying in {} second(s): {}",
+                    &name,
+                    &id,
+                    delay,
+                    e
+                );
+                self.connector.release(job, delay).await
+            }
+            Err(e) => {
+                tracing::error!(target: LOG_TARGET, "job {}: {} failed: {}", &name, &id, e);
+                self.connector
+                    .fail(FailedJob::new(job, &e.to_string()))
+                    .await
+            }
+        };
+
+        if let Err(e) = result {
+            tracing::error!(target: LOG_TARGET, "could not update job {}: {}", &id, e);
+        }
+    }
+
+    async fn next_job(&self, queues: &[String]) -> Option<JobPayload> {
+        for queue in queues {
+            match self.connector.pop(queue, self.config.retry_after()).await {
+                Ok(Some(job)) => return Some(job),
+                Ok(None) => (),
+                Err(e) => {
+                    tracing::error!(target: LOG_TARGET, "could not fetch job from {}: {}", queue, e)
+                }
+            }
+        }
+
+        None
+    }
+
+    fn make_payload<J: Job>(&self, job: &J) -> Result<JobPayload, anyhow::Error> {
+        JobPayload::new(job, self.config.max_attempts(), self.config.backoff())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::connector::MemoryConnector;
+
+    use super::*;
+
+    static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct CountJob {
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Job for CountJob {
+        fn name() -> &'static str {
+            "test::count_job"
+        }
+
+        fn backoff(&self) -> Option<u64> {
+            Some(0)
+        }
+
+        async fn handle(&self, _context: Context) -> Result<(), anyhow::Error> {
+            HANDLED.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err(anyhow!("failed on purpose"));
+            }
+            Ok(())
+        }
+    }
+
+    fn make_manager() -> QueueManager {
+        let mut config = QueueConfig::default();
+        config.set_max_attempts(2);
+        QueueManager::new(ConnectorProvider::new(MemoryConnector::new()), config)
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_and_work() {
+        JobRegistry::register::<CountJob>().await;
+        let manager = make_manager();
+
+        manager.dispatch(CountJob { fail: false }).await.unwrap();
+        manager
+            .dispatch_later(CountJob { fail: false }, Duration::from_secs(60))
+            .await
+            .unwrap();
+        manager.dispatch(CountJob { fail: true }).await.unwrap();
+        assert_eq!(manager.size("default").await.unwrap(), 3);
+
+        let option = WorkerOption::new(vec!["default".to_string()], 2).stop_when_empty();
+        manager.work(option, CancellationToken::new()).await;
+
+        // the delayed job is still waiting
+        assert_eq!(manager.size("default").await.unwrap(), 1);
+        // the failing job is tried twice
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 3);
+
+        let failed = manager.failed().await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].job().attempts(), 2);
+
+        manager.retry(failed[0].id()).await.unwrap();
+        assert!(manager.failed().await.unwrap().is_empty());
+        assert_eq!(manager.size("default").await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,38 @@
+use dirtybase_contract::prelude::ContextResourceManager;
+
+use crate::{
+    ConnectorResolver, QueueManager,
+    config::QueueConfig,
+    connector::{DatabaseConnector, MemoryConnector, RedisConnector},
+};
+
+pub async fn register_resource_manager() {
+    ContextResourceManager::<QueueManager>::register(
+        |_context| {
+            Box::pin(async move {
+                let name = "global"; // Queues are shared by the whole application
+                let duration = 0; // Run forever;
+                Ok((name, duration).into())
+            })
+        },
+        |context| {
+            Box::pin(async move {
+                let config = context.get_config_once::<QueueConfig>("queue").await?;
+                let provider = ConnectorResolver::new(context, config.clone())
+                    .get_provider()
+                    .await?;
+                Ok(QueueManager::new(provider, config))
+            })
+        },
+        |_manager| Box::pin(async {}),
+    )
+    .await;
+
+    register_connectors().await;
+}
+
+async fn register_connectors() {
+    MemoryConnector::register().await;
+    DatabaseConnector::register().await;
+    RedisConnector::register().await;
+}